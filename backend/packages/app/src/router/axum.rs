@@ -1,12 +1,27 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use database::database::{Connection, Sources};
 use environment::Environment;
 use errors::{Error::TcpErrorConnection, Result};
 
 use redis::Client;
-use repository::user::user_repository::UserRepository;
-use service::user::user_service::UserService;
+use repository::{
+    api_key::api_key_repository::ApiKeyRepository,
+    audit_event::audit_event_repository::AuditEventRepository,
+    passkey::passkey_repository::PasskeyRepository, role::role_repository::RoleRepository,
+    user::user_repository::UserRepository,
+};
+use service::{
+    api_key::api_key_service::ApiKeyService,
+    audit::audit_service::AuditService,
+    auth::{
+        keyring::Keyring, oidc::OidcClient, password_policy::PasswordPolicy, webauthn::Webauthn,
+    },
+    mail::mailer::Mailer,
+    passkey::passkey_service::PasskeyService,
+    role::role_service::RoleService,
+    user::user_service::UserService,
+};
 use state::axum::AppState;
 
 use tracing::{error, info};
@@ -14,12 +29,12 @@ use tracing::{error, info};
 use super::axum_routes::build_routes;
 
 pub async fn run() -> Result<()> {
+    let environment = Environment::new();
+
     let mut surreal_db = database::database::DatabaseSource {
-        db_type: database::database::DatabaseType::SurrealDB,
+        db_type: database::database::DatabaseType::from_env(&environment)?,
     };
 
-    let environment = Environment::new();
-
     let redis_url = format!(
         "redis://{}:{}@{}:{}",
         environment.redis_username,
@@ -39,6 +54,11 @@ pub async fn run() -> Result<()> {
         }
     };
 
+    let mailer = Mailer::from_env(&environment)?;
+    let keyring = Keyring::from_env(&environment)?;
+    let oidc_client = OidcClient::from_env(&environment)?;
+    let webauthn = Webauthn::from_env(&environment);
+
     let conn = Arc::new(surreal_db.connect().await?);
     let ping_db = conn.ping();
 
@@ -52,11 +72,40 @@ pub async fn run() -> Result<()> {
     let user_repository = UserRepository { db: conn.clone() };
     let user_service = UserService {
         user_repo: user_repository,
+        password_policy: PasswordPolicy::from_env(&environment),
+    };
+
+    let role_repository = RoleRepository { db: conn.clone() };
+    let role_service = RoleService {
+        role_repo: role_repository,
+    };
+
+    let api_key_repository = ApiKeyRepository { db: conn.clone() };
+    let api_key_service = ApiKeyService {
+        api_key_repo: api_key_repository,
+    };
+
+    let audit_event_repository = AuditEventRepository { db: conn.clone() };
+    let audit_service = AuditService {
+        audit_repo: audit_event_repository,
+    };
+
+    let passkey_repository = PasskeyRepository { db: conn.clone() };
+    let passkey_service = PasskeyService {
+        passkey_repo: passkey_repository,
     };
 
     let app_state = AppState {
         user_service,
+        role_service,
+        api_key_service,
+        audit_service,
+        passkey_service,
         redis_client,
+        mailer,
+        keyring,
+        oidc_client,
+        webauthn,
     };
 
     let shared_state = Arc::new(app_state);
@@ -69,7 +118,7 @@ pub async fn run() -> Result<()> {
         listener,
         build_routes(shared_state.clone())
             .with_state(shared_state)
-            .into_make_service(),
+            .into_make_service_with_connect_info::<SocketAddr>(),
     )
     .await
     .map_err(|error| TcpErrorConnection(error.to_string()))?;
@@ -2,18 +2,59 @@ use std::sync::Arc;
 
 use axum::{
     middleware,
-    routing::{post, put},
+    routing::{delete, get, post, put},
     Router,
 };
 use tower_http::trace::TraceLayer;
 
 use controller::axum::{
+    admin::{
+        change_user_role, create_user, get_user, impersonate_user, list_users, logout_user,
+        require_two_factor, set_user_disabled, unlock_user, verify_user,
+    },
+    api_key::{create_api_key, list_api_keys, revoke_api_key},
+    audit::list_audit_events,
+    auth::{logout, logout_all, refresh},
+    jwks::jwks,
     jwt::jwt_auth,
-    user::{login, register, update_profile},
+    magic_link::{magic_link_confirmation, magic_link_login, request_magic_link},
+    oidc::{oidc_authorize, oidc_callback},
+    passkey::{
+        delete_passkey, finish_passkey_login, finish_passkey_registration, list_passkeys,
+        start_passkey_login, start_passkey_registration,
+    },
+    password::{forgot_password, reset_password},
+    role::{create_role, delete_role, list_roles, update_role},
+    session::{delete_session, list_sessions},
+    two_factor::{
+        confirm_two_factor, disable_two_factor, enroll_two_factor, login_two_factor,
+        login_two_factor_setup,
+    },
+    user::{change_password, login, register, update_profile},
+    verification::{resend_verification, verify_email},
 };
 use model::{
+    authorization::{
+        jwk::{Jwk, JwkSet},
+        permission::Permission,
+        scope::ApiKeyScope,
+    },
+    domain::audit_event::{AuditEventType, AuditOutcome},
     utoipa::user::User as UserUtoipa,
-    web::user::user_request::{User as UserRequest, UserLogin},
+    web::{
+        api_key::api_key_request::ApiKey as ApiKeyRequest,
+        auth::auth_request::{
+            DisableTwoFactor, ForgotPassword, MagicLinkRequest, RefreshToken, ResendVerification,
+            ResetPassword, TwoFactorChallenge, TwoFactorCode, TwoFactorLogin, TwoFactorRequirement,
+            UnlockAccount,
+        },
+        passkey::passkey_request::{PasskeyLogin, PasskeyRegistration},
+        role::role_request::{Role as RoleRequest, RoleUpdate},
+        user::user_request::{
+            ChangePassword, User as UserRequest, UserDisabled, UserLogin, UserRoleUpdate,
+            UserUpdate,
+        },
+    },
 };
 use state::axum::AppState;
 
@@ -26,9 +67,84 @@ use utoipa_swagger_ui::SwaggerUi;
     paths(
         controller::axum::user::register,
         controller::axum::user::update_profile,
+        controller::axum::user::change_password,
         controller::axum::user::login,
+        controller::axum::auth::refresh,
+        controller::axum::auth::logout,
+        controller::axum::auth::logout_all,
+        controller::axum::jwks::jwks,
+        controller::axum::magic_link::request_magic_link,
+        controller::axum::magic_link::magic_link_confirmation,
+        controller::axum::magic_link::magic_link_login,
+        controller::axum::oidc::oidc_authorize,
+        controller::axum::oidc::oidc_callback,
+        controller::axum::passkey::start_passkey_login,
+        controller::axum::passkey::finish_passkey_login,
+        controller::axum::passkey::start_passkey_registration,
+        controller::axum::passkey::finish_passkey_registration,
+        controller::axum::passkey::list_passkeys,
+        controller::axum::passkey::delete_passkey,
+        controller::axum::session::list_sessions,
+        controller::axum::session::delete_session,
+        controller::axum::verification::verify_email,
+        controller::axum::verification::resend_verification,
+        controller::axum::password::forgot_password,
+        controller::axum::password::reset_password,
+        controller::axum::api_key::create_api_key,
+        controller::axum::api_key::list_api_keys,
+        controller::axum::api_key::revoke_api_key,
+        controller::axum::admin::create_user,
+        controller::axum::admin::unlock_user,
+        controller::axum::admin::require_two_factor,
+        controller::axum::admin::list_users,
+        controller::axum::admin::get_user,
+        controller::axum::admin::set_user_disabled,
+        controller::axum::admin::change_user_role,
+        controller::axum::admin::verify_user,
+        controller::axum::admin::logout_user,
+        controller::axum::admin::impersonate_user,
+        controller::axum::audit::list_audit_events,
+        controller::axum::two_factor::enroll_two_factor,
+        controller::axum::two_factor::confirm_two_factor,
+        controller::axum::two_factor::disable_two_factor,
+        controller::axum::two_factor::login_two_factor_setup,
+        controller::axum::two_factor::login_two_factor,
+        controller::axum::role::list_roles,
+        controller::axum::role::create_role,
+        controller::axum::role::update_role,
+        controller::axum::role::delete_role,
     ),
-    components(schemas(UserUtoipa, UserRequest, UserLogin))
+    components(schemas(
+        UserUtoipa,
+        UserRequest,
+        UserLogin,
+        UserUpdate,
+        ChangePassword,
+        UserDisabled,
+        UserRoleUpdate,
+        RefreshToken,
+        ResendVerification,
+        ForgotPassword,
+        ResetPassword,
+        MagicLinkRequest,
+        PasskeyRegistration,
+        PasskeyLogin,
+        UnlockAccount,
+        TwoFactorCode,
+        TwoFactorChallenge,
+        TwoFactorLogin,
+        TwoFactorRequirement,
+        DisableTwoFactor,
+        RoleRequest,
+        RoleUpdate,
+        Permission,
+        ApiKeyRequest,
+        ApiKeyScope,
+        AuditEventType,
+        AuditOutcome,
+        Jwk,
+        JwkSet
+    ))
 )]
 struct ApiDoc;
 
@@ -37,10 +153,121 @@ pub fn user_routes(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/api/v1/user", post(register))
         .route("/api/v1/login", post(login))
+        .route("/api/v1/login/2fa", post(login_two_factor))
+        .route("/api/v1/login/2fa/setup", post(login_two_factor_setup))
+        .route("/api/v1/login/magic-link", post(request_magic_link))
+        .route(
+            "/api/v1/login/magic-link/:token",
+            get(magic_link_confirmation).post(magic_link_login),
+        )
+        .route("/api/v1/login/passkey/start", post(start_passkey_login))
+        .route("/api/v1/login/passkey/finish", post(finish_passkey_login))
+        .route("/api/v1/refresh", post(refresh))
+        .route("/.well-known/jwks.json", get(jwks))
+        .route("/api/v1/oidc/:provider/authorize", get(oidc_authorize))
+        .route("/api/v1/oidc/:provider/callback", get(oidc_callback))
+        .route("/api/v1/verify/:token", get(verify_email))
+        .route("/api/v1/verify/resend", post(resend_verification))
+        .route("/api/v1/password/forgot", post(forgot_password))
+        .route("/api/v1/password/reset", post(reset_password))
         .route(
             "/api/v1/user",
             put(update_profile).layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
         )
+        .route(
+            "/api/v1/user/password",
+            put(change_password).layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/logout",
+            post(logout).layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/logout/all",
+            post(logout_all).layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/sessions",
+            get(list_sessions).layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/sessions/:id",
+            delete(delete_session)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/user/2fa/enroll",
+            post(enroll_two_factor)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/user/2fa/confirm",
+            post(confirm_two_factor)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/user/2fa/disable",
+            post(disable_two_factor)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/user/passkeys",
+            get(list_passkeys).layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/user/passkeys/register/start",
+            post(start_passkey_registration)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/user/passkeys/register/finish",
+            post(finish_passkey_registration)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/user/passkeys/:id",
+            delete(delete_passkey)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/api-keys",
+            get(list_api_keys)
+                .post(create_api_key)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .route(
+            "/api/v1/api-keys/:id",
+            delete(revoke_api_key)
+                .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+        )
+        .with_state(app_state)
+}
+
+/// Defines admin-only routes.
+pub fn admin_routes(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/v1/admin/users", get(list_users).post(create_user))
+        .route("/api/v1/admin/users/:id", get(get_user))
+        .route("/api/v1/admin/users/:id/disabled", put(set_user_disabled))
+        .route("/api/v1/admin/users/:id/role", put(change_user_role))
+        .route("/api/v1/admin/users/:id/verify", post(verify_user))
+        .route("/api/v1/admin/users/:id/logout", post(logout_user))
+        .route(
+            "/api/v1/admin/users/:id/impersonate",
+            post(impersonate_user),
+        )
+        .route("/api/v1/admin/users/unlock", post(unlock_user))
+        .route(
+            "/api/v1/admin/users/:id/two-factor",
+            put(require_two_factor),
+        )
+        .route("/api/v1/admin/audit-events", get(list_audit_events))
+        .route("/api/v1/admin/roles", get(list_roles).post(create_role))
+        .route(
+            "/api/v1/admin/roles/:name",
+            put(update_role).delete(delete_role),
+        )
+        .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth))
         .with_state(app_state)
 }
 
@@ -66,6 +293,7 @@ pub fn build_routes(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
 
     router
         .merge(user_routes(app_state.clone()))
+        .merge(admin_routes(app_state.clone()))
         .merge(swagger_router)
         .layer(TraceLayer::new_for_http())
         .with_state(app_state)
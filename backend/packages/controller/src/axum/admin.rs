@@ -0,0 +1,553 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use validator::Validate;
+
+use errors::{
+    Error::{DataNotAvailable, InvalidUserRole, TokenError},
+    Result,
+};
+use model::{
+    authorization::jwk::KeyUse,
+    domain::{
+        audit_event::{AuditEventType, AuditOutcome},
+        page::PageRequest,
+    },
+    web::{
+        auth::auth_request::{TwoFactorRequirement, UnlockAccount},
+        user::{
+            user_request::{User as UserRequest, UserDisabled, UserListQuery, UserRoleUpdate},
+            user_response::AdminUser,
+        },
+    },
+};
+use service::{
+    auth::{
+        jwt::{generate_impersonation_token, save_token_data_to_redis},
+        login_throttle::clear_login_failures,
+        session::revoke_user_sessions,
+        verification::send_verification_email,
+    },
+    role::role_service::RoleServiceTrait,
+    user::user_service::UserServiceTrait,
+};
+use state::axum::AppState;
+
+use super::{
+    audit::AuditEntry,
+    authorization::{
+        Admin, ImpersonateUsers, ManageUsers, ReadUsers, RequirePermission, RequireRole,
+    },
+    client_info::ClientInfo,
+    jwt::JWTAuthMiddleware,
+};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Lifetime of an impersonation token in minutes, it cannot be refreshed.
+const IMPERSONATION_TOKEN_MAX_AGE: i64 = 15;
+
+/// Turns the id of a user route into its record id, rejecting anything that is not a plain id.
+fn user_record_id(id: &str) -> Result<String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(DataNotAvailable(format!("User ID '{}' not found", id)));
+    }
+    Ok(format!("user:{}", id))
+}
+
+/// Checks that a staff user may manage another account with the given roles.
+///
+/// Nobody acts on their own account, and the roles must not grant permissions the caller lacks,
+/// otherwise managing them would hand those permissions out.
+async fn ensure_manageable(
+    app_state: &AppState,
+    jwt: &JWTAuthMiddleware,
+    user_id: &str,
+    roles: &[&str],
+) -> Result<()> {
+    if user_id == jwt.user_id {
+        return Err(InvalidUserRole(
+            "You cannot change your own account through the admin API".to_string(),
+        ));
+    }
+
+    for role in roles {
+        let permissions = app_state.role_service.get_permissions(role).await?;
+        if let Some(missing) = permissions
+            .iter()
+            .find(|permission| !jwt.permissions.contains(permission))
+        {
+            return Err(InvalidUserRole(format!(
+                "Role '{}' has permission '{}' that you lack",
+                role,
+                missing.as_str()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users",
+    request_body = UserRequest,
+    tag = "admin",
+    responses(
+        (status = 200, description = "User created", content_type = "text/plain", example = super::data_example::user_registered),
+        (status = 403, description = "Caller is not an admin", content_type = "text/plain"),
+        (status = 422, description = "Password rejected by the password policy", content_type = "text/plain")
+    ),
+    description = "Create a user with any built-in or custom role, including `admin`. Only available to admins."
+)]
+pub async fn create_user(
+    RequireRole { user: jwt, .. }: RequireRole<Admin>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(payload): Json<UserRequest>,
+) -> Result<impl IntoResponse> {
+    payload.validate()?;
+
+    if !app_state.role_service.role_exists(&payload.role).await? {
+        return Err(InvalidUserRole(format!(
+            "Role '{}' does not exist",
+            payload.role
+        )));
+    }
+
+    let role = payload.role.clone();
+    let profile_registered = app_state.user_service.register_profile(payload).await?;
+
+    AuditEntry::new(AuditEventType::UserCreated, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&format!("user:{}", profile_registered.id))
+        .detail(role)
+        .record(&app_state, &client_info)
+        .await;
+
+    if let Err(e) = send_verification_email(
+        &app_state.redis_client,
+        &app_state.mailer,
+        &format!("user:{}", profile_registered.id),
+        &profile_registered.email,
+    )
+    .await
+    {
+        tracing::error!("Failed to send verification email: {}", e);
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "user": profile_registered }
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/unlock",
+    request_body = UnlockAccount,
+    tag = "admin",
+    responses(
+        (status = 200, description = "Account unlocked", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:manage", content_type = "text/plain")
+    ),
+    description = "Clear the failed login counter of an email, lifting its backoff or lockout."
+)]
+pub async fn unlock_user(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageUsers>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(payload): Json<UnlockAccount>,
+) -> Result<impl IntoResponse> {
+    payload.validate()?;
+
+    clear_login_failures(&app_state.redis_client, &payload.email).await?;
+
+    AuditEntry::new(AuditEventType::UserUnlocked, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .detail(payload.email)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/users/{id}/two-factor",
+    request_body = TwoFactorRequirement,
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "User id without the `user:` prefix")
+    ),
+    responses(
+        (status = 200, description = "Requirement updated", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:manage, your own account or a role with permissions you lack", content_type = "text/plain"),
+        (status = 404, description = "User not found", content_type = "text/plain")
+    ),
+    description = "Require (or stop requiring) two-factor authentication for a user. Users without 2FA are made to enroll on their next login. Your own account and accounts whose role has permissions you lack cannot be changed."
+)]
+pub async fn require_two_factor(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageUsers>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(id): Path<String>,
+    Json(payload): Json<TwoFactorRequirement>,
+) -> Result<impl IntoResponse> {
+    let user_id = user_record_id(&id)?;
+    let user = app_state.user_service.get_user_by_id(&user_id).await?;
+    ensure_manageable(&app_state, &jwt, &user_id, &[&user.role]).await?;
+
+    app_state
+        .user_service
+        .set_two_factor_required(&user_id, payload.required)
+        .await?;
+
+    AuditEntry::new(
+        AuditEventType::TwoFactorRequirementChanged,
+        AuditOutcome::Success,
+    )
+    .actor(&jwt.user_id)
+    .target(&user_id)
+    .detail(format!("required: {}", payload.required))
+    .record(&app_state, &client_info)
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users",
+    tag = "admin",
+    params(
+        ("search" = Option<String>, Query, description = "Matched case-insensitively against username and email"),
+        ("page" = Option<u32>, Query, description = "Page number starting at 1"),
+        ("per_page" = Option<u32>, Query, description = "Users per page, at most 100, defaults to 20"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` of the previous page, stable while users are added")
+    ),
+    responses(
+        (status = 200, description = "Page of users, newest first", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:read", content_type = "text/plain")
+    ),
+    description = "List and search user accounts."
+)]
+pub async fn list_users(
+    _auth: RequirePermission<ReadUsers>,
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<UserListQuery>,
+) -> Result<impl IntoResponse> {
+    query.validate()?;
+
+    let page = query.cursor.is_none().then(|| query.page.unwrap_or(1));
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let users = app_state
+        .user_service
+        .list_users(
+            query.search.as_deref(),
+            &PageRequest::from_query(page, per_page, query.cursor),
+        )
+        .await?
+        .map(AdminUser::from);
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "users": users.items,
+            "page": page,
+            "per_page": per_page,
+            "total": users.total,
+            "next_cursor": users.next_cursor
+        }
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "User id without the `user:` prefix")),
+    responses(
+        (status = 200, description = "User details", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:read", content_type = "text/plain"),
+        (status = 404, description = "User not found", content_type = "text/plain")
+    ),
+    description = "View a user account."
+)]
+pub async fn get_user(
+    _auth: RequirePermission<ReadUsers>,
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let user = app_state
+        .user_service
+        .get_user_by_id(&user_record_id(&id)?)
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "user": AdminUser::from(user) }
+    })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/users/{id}/disabled",
+    request_body = UserDisabled,
+    tag = "admin",
+    params(("id" = String, Path, description = "User id without the `user:` prefix")),
+    responses(
+        (status = 200, description = "Account disabled or enabled", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:manage, your own account, a role with permissions you lack, or the last admin", content_type = "text/plain"),
+        (status = 404, description = "User not found", content_type = "text/plain")
+    ),
+    description = "Disable or re-enable an account. Disabling also ends every session of the user. Your own account, accounts whose role has permissions you lack and the last enabled admin cannot be disabled."
+)]
+pub async fn set_user_disabled(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageUsers>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(id): Path<String>,
+    Json(payload): Json<UserDisabled>,
+) -> Result<impl IntoResponse> {
+    let user_id = user_record_id(&id)?;
+    let user = app_state.user_service.get_user_by_id(&user_id).await?;
+    ensure_manageable(&app_state, &jwt, &user_id, &[&user.role]).await?;
+
+    app_state
+        .user_service
+        .set_disabled(&user_id, payload.disabled)
+        .await?;
+    if payload.disabled {
+        revoke_user_sessions(&app_state.redis_client, &user_id).await?;
+    }
+
+    let event_type = if payload.disabled {
+        AuditEventType::UserDisabled
+    } else {
+        AuditEventType::UserEnabled
+    };
+    AuditEntry::new(event_type, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&user_id)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/users/{id}/role",
+    request_body = UserRoleUpdate,
+    tag = "admin",
+    params(("id" = String, Path, description = "User id without the `user:` prefix")),
+    responses(
+        (status = 200, description = "Role changed", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:manage, unknown role, your own account, a role with permissions you lack, or the last admin", content_type = "text/plain"),
+        (status = 404, description = "User not found", content_type = "text/plain")
+    ),
+    description = "Assign another built-in or custom role to a user. The user's sessions are ended so new tokens carry the new role. Neither the current nor the new role may have permissions you lack, you cannot change your own role, and the last enabled admin keeps theirs."
+)]
+pub async fn change_user_role(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageUsers>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(id): Path<String>,
+    Json(payload): Json<UserRoleUpdate>,
+) -> Result<impl IntoResponse> {
+    payload.validate()?;
+    let user_id = user_record_id(&id)?;
+
+    if !app_state.role_service.role_exists(&payload.role).await? {
+        return Err(InvalidUserRole(format!(
+            "Role '{}' does not exist",
+            payload.role
+        )));
+    }
+    let user = app_state.user_service.get_user_by_id(&user_id).await?;
+    ensure_manageable(&app_state, &jwt, &user_id, &[&user.role, &payload.role]).await?;
+
+    app_state
+        .user_service
+        .change_role(&user_id, &payload.role)
+        .await?;
+    revoke_user_sessions(&app_state.redis_client, &user_id).await?;
+
+    AuditEntry::new(AuditEventType::RoleChanged, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&user_id)
+        .detail(payload.role)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/verify",
+    tag = "admin",
+    params(("id" = String, Path, description = "User id without the `user:` prefix")),
+    responses(
+        (status = 200, description = "Email marked as verified", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:manage, your own account or a role with permissions you lack", content_type = "text/plain"),
+        (status = 404, description = "User not found", content_type = "text/plain")
+    ),
+    description = "Mark a user's email address as verified without the emailed link. Your own account and accounts whose role has permissions you lack cannot be verified this way."
+)]
+pub async fn verify_user(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageUsers>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let user_id = user_record_id(&id)?;
+    let user = app_state.user_service.get_user_by_id(&user_id).await?;
+    ensure_manageable(&app_state, &jwt, &user_id, &[&user.role]).await?;
+
+    app_state.user_service.verify_profile(&user_id).await?;
+
+    AuditEntry::new(AuditEventType::UserVerified, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&user_id)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/logout",
+    tag = "admin",
+    params(("id" = String, Path, description = "User id without the `user:` prefix")),
+    responses(
+        (status = 200, description = "Every session ended", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:manage, your own account or a role with permissions you lack", content_type = "text/plain"),
+        (status = 404, description = "User not found", content_type = "text/plain")
+    ),
+    description = "Force-logout a user by revoking all of their sessions and tokens. Your own account and accounts whose role has permissions you lack cannot be logged out this way."
+)]
+pub async fn logout_user(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageUsers>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let user_id = user_record_id(&id)?;
+    let user = app_state.user_service.get_user_by_id(&user_id).await?;
+    ensure_manageable(&app_state, &jwt, &user_id, &[&user.role]).await?;
+
+    revoke_user_sessions(&app_state.redis_client, &user.id).await?;
+
+    AuditEntry::new(AuditEventType::UserLoggedOut, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&user.id)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/impersonate",
+    tag = "admin",
+    params(("id" = String, Path, description = "User id without the `user:` prefix")),
+    responses(
+        (status = 200, description = "Impersonation access token", content_type = "text/plain"),
+        (status = 403, description = "Missing permission users:impersonate, or the user is staff", content_type = "text/plain"),
+        (status = 404, description = "User not found", content_type = "text/plain")
+    ),
+    description = "Mint a 15 minute access token to act as a customer while debugging their account. Responses to it carry the `X-Impersonated-By` header, every request made with it is audit-logged, and it cannot change passwords, email, second factors, API keys or payment data."
+)]
+pub async fn impersonate_user(
+    RequirePermission { user: jwt, .. }: RequirePermission<ImpersonateUsers>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let user_id = user_record_id(&id)?;
+    if user_id == jwt.user_id || jwt.impersonator.is_some() {
+        return Err(InvalidUserRole(
+            "Impersonation must start from your own session and target another user".to_string(),
+        ));
+    }
+
+    let user = app_state
+        .user_service
+        .get_enabled_user_by_id(&user_id)
+        .await?;
+
+    // Acting as a staff account would hand out its permissions
+    if !app_state
+        .role_service
+        .get_permissions(&user.role)
+        .await?
+        .is_empty()
+    {
+        return Err(InvalidUserRole(
+            "Users with staff permissions cannot be impersonated".to_string(),
+        ));
+    }
+
+    let token_details = generate_impersonation_token(
+        user.id.clone(),
+        &jwt.user_id,
+        IMPERSONATION_TOKEN_MAX_AGE,
+        app_state.keyring.signing_key(KeyUse::Access)?,
+        &user.role,
+    )
+    .await?;
+    save_token_data_to_redis(
+        &app_state.redis_client,
+        &token_details,
+        IMPERSONATION_TOKEN_MAX_AGE,
+    )
+    .await?;
+
+    AuditEntry::new(AuditEventType::ImpersonationStarted, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&user.id)
+        .record(&app_state, &client_info)
+        .await;
+
+    let access_token = token_details
+        .token
+        .ok_or_else(|| TokenError("Error extracting access token".to_string()))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "user_id": user.id,
+            "user_role": user.role,
+            "access_token": access_token,
+            "impersonated_by": jwt.user_id,
+            "expires_in": token_details.expires_in
+        }
+    })))
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use validator::Validate;
+
+use errors::Result;
+use model::web::api_key::api_key_request::ApiKey as ApiKeyRequest;
+use service::api_key::api_key_service::ApiKeyServiceTrait;
+use state::axum::AppState;
+
+use super::authorization::{RequireAccountOwner, RequireSession};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/api-keys",
+    request_body = ApiKeyRequest,
+    tag = "api_key",
+    responses(
+        (status = 200, description = "API key created", content_type = "text/plain"),
+        (status = 401, description = "Not logged in", content_type = "text/plain")
+    ),
+    description = "Create an API key limited to the given scopes. The key is only shown in this response; send it as `Authorization: ApiKey <key>`."
+)]
+pub async fn create_api_key(
+    RequireAccountOwner { user }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+    Json(payload): Json<ApiKeyRequest>,
+) -> Result<impl IntoResponse> {
+    payload.validate()?;
+
+    let api_key = app_state
+        .api_key_service
+        .create_api_key(&user.user_id, payload)
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "api_key": api_key }
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/api-keys",
+    tag = "api_key",
+    responses(
+        (status = 200, description = "API keys of the user", content_type = "text/plain"),
+        (status = 401, description = "Not logged in", content_type = "text/plain")
+    ),
+    description = "List the API keys of the authenticated user with their scopes and when they were last used."
+)]
+pub async fn list_api_keys(
+    RequireSession { user }: RequireSession,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let api_keys = app_state
+        .api_key_service
+        .list_api_keys(&user.user_id)
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "api_keys": api_keys }
+    })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/api-keys/{id}",
+    tag = "api_key",
+    params(
+        ("id" = String, Path, description = "API key id")
+    ),
+    responses(
+        (status = 200, description = "API key revoked", content_type = "text/plain"),
+        (status = 404, description = "API key not found", content_type = "text/plain")
+    ),
+    description = "Revoke one of the authenticated user's API keys."
+)]
+pub async fn revoke_api_key(
+    RequireAccountOwner { user }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    app_state
+        .api_key_service
+        .revoke_api_key(&user.user_id, &id)
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
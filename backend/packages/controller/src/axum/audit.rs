@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use errors::Result;
+use model::{
+    domain::{
+        audit_event::{AuditEvent, AuditEventFilter, AuditEventType, AuditOutcome},
+        page::PageRequest,
+    },
+    web::audit::audit_request::AuditEventQuery,
+};
+use service::audit::audit_service::AuditServiceTrait;
+use state::axum::AppState;
+
+use super::{
+    authorization::{ReadAudit, RequirePermission},
+    client_info::ClientInfo,
+};
+
+const DEFAULT_PER_PAGE: u32 = 50;
+
+/// Builds an audit event for the request being handled.
+pub(super) struct AuditEntry {
+    event: AuditEvent,
+}
+
+impl AuditEntry {
+    pub(super) fn new(event_type: AuditEventType, outcome: AuditOutcome) -> Self {
+        Self {
+            event: AuditEvent {
+                id: format!("evt_{}", Uuid::new_v4().simple()),
+                event_type,
+                outcome,
+                actor: None,
+                target: None,
+                ip: None,
+                user_agent: None,
+                detail: None,
+                created_at: Utc::now(),
+            },
+        }
+    }
+
+    pub(super) fn actor(mut self, actor: &str) -> Self {
+        self.event.actor = Some(actor.to_string());
+        self
+    }
+
+    pub(super) fn target(mut self, target: &str) -> Self {
+        self.event.target = Some(target.to_string());
+        self
+    }
+
+    pub(super) fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.event.detail = Some(detail.into());
+        self
+    }
+
+    /// Writes the event. A failing audit log is reported but never fails the request itself.
+    pub(super) async fn record(mut self, app_state: &AppState, client_info: &ClientInfo) {
+        self.event.ip = client_info.ip.clone();
+        self.event.user_agent = client_info.user_agent.clone();
+
+        if let Err(e) = app_state.audit_service.record_event(self.event).await {
+            tracing::error!("Failed to write audit event: {}", e);
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit-events",
+    tag = "admin",
+    params(
+        ("user" = Option<String>, Query, description = "User id matched against actor and target, e.g. `user:user_xxx`"),
+        ("event_type" = Option<AuditEventType>, Query, description = "Only events of this type"),
+        ("from" = Option<String>, Query, description = "RFC 3339 timestamp of the oldest event"),
+        ("to" = Option<String>, Query, description = "RFC 3339 timestamp of the newest event"),
+        ("page" = Option<u32>, Query, description = "Page number starting at 1"),
+        ("per_page" = Option<u32>, Query, description = "Events per page, at most 100, defaults to 50"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` of the previous page, stable while events are recorded")
+    ),
+    responses(
+        (status = 200, description = "Page of audit events, newest first", content_type = "text/plain"),
+        (status = 403, description = "Missing permission audit:read", content_type = "text/plain")
+    ),
+    description = "Search the security audit log of logins, token rejections, profile changes and admin actions."
+)]
+pub async fn list_audit_events(
+    _auth: RequirePermission<ReadAudit>,
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<AuditEventQuery>,
+) -> Result<impl IntoResponse> {
+    query.validate()?;
+
+    let page = query.cursor.is_none().then(|| query.page.unwrap_or(1));
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let filter = AuditEventFilter {
+        user: query.user,
+        event_type: query.event_type,
+        from: query.from,
+        to: query.to,
+    };
+    let events = app_state
+        .audit_service
+        .list_events(
+            &filter,
+            &PageRequest::from_query(page, per_page, query.cursor),
+        )
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "events": events.items,
+            "page": page,
+            "per_page": per_page,
+            "total": events.total,
+            "next_cursor": events.next_cursor
+        }
+    })))
+}
@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Response},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+use environment::Environment;
+use errors::{
+    Error::{StringError, TokenError},
+    Result,
+};
+use model::{
+    authorization::{jwk::KeyUse, session::SessionDetails},
+    web::auth::auth_request::RefreshToken,
+};
+use service::auth::{
+    jwt::{
+        delete_token_data_in_redis, generate_jwt_token, save_token_data_to_redis, verify_jwt_token,
+    },
+    session::{
+        create_session, delete_session_token, get_token_session, get_used_refresh_token_session,
+        mark_refresh_token_used, revoke_session, revoke_user_sessions, save_session_token,
+    },
+};
+use state::axum::AppState;
+
+use super::{
+    authorization::{RequireAccountOwner, RequireSession},
+    client_info::ClientInfo,
+};
+
+/// Starts a new session for an authenticated user and issues its first token pair.
+pub(super) async fn start_session(
+    app_state: &AppState,
+    user_id: &str,
+    user_role: &str,
+    client_info: &ClientInfo,
+) -> Result<Response<String>> {
+    let env = Environment::new();
+    let now = Utc::now();
+
+    let session = SessionDetails {
+        session_id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        ip: client_info.ip.clone(),
+        user_agent: client_info.user_agent.clone(),
+        created_at: now,
+        last_seen: now,
+    };
+
+    create_session(&app_state.redis_client, &session, env.refresh_token_max_age).await?;
+
+    issue_tokens(app_state, user_id, user_role, &session.session_id).await
+}
+
+/// Issues a new access/refresh token pair for a session and builds the login response.
+async fn issue_tokens(
+    app_state: &AppState,
+    user_id: &str,
+    user_role: &str,
+    session_id: &str,
+) -> Result<Response<String>> {
+    let env = Environment::new();
+    let redis_client = &app_state.redis_client;
+
+    let access_token_details = generate_jwt_token(
+        user_id.to_string(),
+        env.access_token_max_age,
+        app_state.keyring.signing_key(KeyUse::Access)?,
+        user_role,
+    )
+    .await?;
+
+    let refresh_token_details = generate_jwt_token(
+        user_id.to_string(),
+        env.refresh_token_max_age,
+        app_state.keyring.signing_key(KeyUse::Refresh)?,
+        user_role,
+    )
+    .await?;
+
+    save_token_data_to_redis(
+        redis_client,
+        &access_token_details,
+        env.access_token_max_age,
+    )
+    .await?;
+    save_token_data_to_redis(
+        redis_client,
+        &refresh_token_details,
+        env.refresh_token_max_age,
+    )
+    .await?;
+    save_session_token(
+        redis_client,
+        session_id,
+        &access_token_details,
+        env.access_token_max_age,
+    )
+    .await?;
+    save_session_token(
+        redis_client,
+        session_id,
+        &refresh_token_details,
+        env.refresh_token_max_age,
+    )
+    .await?;
+
+    let access_token = access_token_details
+        .token
+        .clone()
+        .ok_or_else(|| TokenError("Error extracting access token".to_string()))?;
+
+    let refresh_token = refresh_token_details
+        .token
+        .ok_or_else(|| TokenError("Error extracting refresh token".to_string()))?;
+
+    let cookies = vec![
+        Cookie::build(("access_token", access_token.clone()))
+            .path("/")
+            .max_age(time::Duration::minutes(env.access_token_max_age))
+            .same_site(SameSite::Lax)
+            .http_only(true),
+        Cookie::build(("refresh_token", refresh_token))
+            .path("/")
+            .max_age(time::Duration::minutes(env.refresh_token_max_age))
+            .same_site(SameSite::Lax)
+            .http_only(true),
+        Cookie::build(("logged_in", "true"))
+            .path("/")
+            .max_age(time::Duration::minutes(env.access_token_max_age))
+            .same_site(SameSite::Lax)
+            .http_only(false),
+    ];
+
+    let mut response = Response::new(
+        json!({
+            "status": "success",
+            "data": {
+                "user_id": user_id,
+                "user_role": user_role,
+                "access_token": access_token
+            }
+        })
+        .to_string(),
+    );
+
+    append_cookies(&mut response, cookies)?;
+    Ok(response)
+}
+
+/// Builds a response that expires every cookie set by `issue_tokens`.
+fn logged_out_response() -> Result<Response<String>> {
+    let cookies = ["access_token", "refresh_token", "logged_in"]
+        .into_iter()
+        .map(|name| {
+            Cookie::build((name, ""))
+                .path("/")
+                .max_age(time::Duration::minutes(-1))
+                .same_site(SameSite::Lax)
+                .http_only(name != "logged_in")
+        })
+        .collect();
+
+    let mut response = Response::new(
+        json!({
+            "status": "success",
+            "data": {}
+        })
+        .to_string(),
+    );
+
+    append_cookies(&mut response, cookies)?;
+    Ok(response)
+}
+
+fn append_cookies<'c>(
+    response: &mut Response<String>,
+    cookies: Vec<impl Into<Cookie<'c>>>,
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    for cookie in cookies {
+        headers.append(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&cookie.into().to_string())
+                .map_err(|err| StringError(err.to_string()))?,
+        );
+    }
+
+    response.headers_mut().extend(headers);
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/refresh",
+    request_body = RefreshToken,
+    tag = "auth",
+    responses(
+        (status = 200, description = "Tokens rotated", content_type = "text/plain"),
+        (status = 401, description = "Refresh token invalid, expired or reused", content_type = "text/plain")
+    ),
+    description = "Exchange the `refresh_token` cookie (or body field) for a new access & refresh token pair. Presenting an already used refresh token revokes the whole session."
+)]
+pub async fn refresh(
+    State(app_state): State<Arc<AppState>>,
+    cookie_jar: CookieJar,
+    body: Option<Json<RefreshToken>>,
+) -> Result<impl IntoResponse> {
+    let env = Environment::new();
+    let redis_client = &app_state.redis_client;
+
+    let refresh_token = cookie_jar
+        .get("refresh_token")
+        .map(|cookie| cookie.value().to_string())
+        .or_else(|| body.map(|Json(payload)| payload.refresh_token))
+        .ok_or_else(|| TokenError("Could not refresh access token".to_string()))?;
+
+    let refresh_token_details =
+        verify_jwt_token(&app_state.keyring, KeyUse::Refresh, &refresh_token)
+            .await
+            .map_err(|e| TokenError(format!("fail: {}", e)))?;
+
+    let token_uuid = refresh_token_details.token_uuid.to_string();
+
+    // A verified refresh token that is no longer live has either expired or already been rotated
+    let Some(session_id) = get_token_session(redis_client, &token_uuid).await? else {
+        if let Some(session_id) = get_used_refresh_token_session(redis_client, &token_uuid).await? {
+            revoke_session(redis_client, &refresh_token_details.user_id, &session_id).await?;
+            return Err(TokenError(
+                "fail: Refresh token reuse detected, session revoked".to_string(),
+            ));
+        }
+        return Err(TokenError(
+            "fail: Token is invalid or session has expired".to_string(),
+        ));
+    };
+
+    // Concurrent requests with the same token race here; only the first one may rotate it
+    if !mark_refresh_token_used(
+        redis_client,
+        &token_uuid,
+        &session_id,
+        env.refresh_token_max_age,
+    )
+    .await?
+    {
+        revoke_session(redis_client, &refresh_token_details.user_id, &session_id).await?;
+        return Err(TokenError(
+            "fail: Refresh token reuse detected, session revoked".to_string(),
+        ));
+    }
+
+    delete_token_data_in_redis(redis_client, token_uuid.clone()).await?;
+    delete_session_token(redis_client, &session_id, &token_uuid).await?;
+
+    issue_tokens(
+        &app_state,
+        &refresh_token_details.user_id,
+        &refresh_token_details.user_role,
+        &session_id,
+    )
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/logout",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Session revoked", content_type = "text/plain"),
+        (status = 401, description = "Not logged in", content_type = "text/plain")
+    ),
+    description = "Revoke the current session (access and paired refresh token) and clear the auth cookies."
+)]
+pub async fn logout(
+    State(app_state): State<Arc<AppState>>,
+    RequireSession { user: jwt }: RequireSession,
+) -> Result<impl IntoResponse> {
+    let redis_client = &app_state.redis_client;
+    let access_token_uuid = jwt.access_token_uuid.to_string();
+
+    match get_token_session(redis_client, &access_token_uuid).await? {
+        Some(session_id) => revoke_session(redis_client, &jwt.user_id, &session_id).await?,
+        None => delete_token_data_in_redis(redis_client, access_token_uuid).await?,
+    }
+
+    logged_out_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/logout/all",
+    tag = "auth",
+    responses(
+        (status = 200, description = "All sessions revoked", content_type = "text/plain"),
+        (status = 401, description = "Not logged in", content_type = "text/plain")
+    ),
+    description = "Revoke every session of the authenticated user on every device and clear the auth cookies."
+)]
+pub async fn logout_all(
+    State(app_state): State<Arc<AppState>>,
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+) -> Result<impl IntoResponse> {
+    let redis_client = &app_state.redis_client;
+
+    revoke_user_sessions(redis_client, &jwt.user_id).await?;
+    // Tokens issued before sessions were tracked are not indexed by user
+    delete_token_data_in_redis(redis_client, jwt.access_token_uuid.to_string()).await?;
+
+    logged_out_response()
+}
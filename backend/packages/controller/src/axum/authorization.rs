@@ -0,0 +1,296 @@
+use std::marker::PhantomData;
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+
+use errors::{
+    Error::{self, InvalidUserRole, UserUnauthorized},
+    Result,
+};
+use model::authorization::{permission::Permission, role::UserRole, scope::ApiKeyScope};
+
+use super::jwt::JWTAuthMiddleware;
+
+/* Type-level markers for the roles a route can require */
+pub trait Role: Send + Sync {
+    const ROLE: UserRole;
+}
+
+pub struct Customer;
+pub struct Seller;
+pub struct Admin;
+
+impl Role for Customer {
+    const ROLE: UserRole = UserRole::Customer;
+}
+
+impl Role for Seller {
+    const ROLE: UserRole = UserRole::Seller;
+}
+
+impl Role for Admin {
+    const ROLE: UserRole = UserRole::Admin;
+}
+
+/* A set of roles, any of which grants access */
+pub trait RoleSet: Send + Sync {
+    fn roles() -> Vec<UserRole>;
+}
+
+impl<A: Role> RoleSet for (A,) {
+    fn roles() -> Vec<UserRole> {
+        vec![A::ROLE]
+    }
+}
+
+impl<A: Role, B: Role> RoleSet for (A, B) {
+    fn roles() -> Vec<UserRole> {
+        vec![A::ROLE, B::ROLE]
+    }
+}
+
+impl<A: Role, B: Role, C: Role> RoleSet for (A, B, C) {
+    fn roles() -> Vec<UserRole> {
+        vec![A::ROLE, B::ROLE, C::ROLE]
+    }
+}
+
+/* Type-level markers for the permissions a route can require */
+pub trait RequiredPermission: Send + Sync {
+    const PERMISSION: Permission;
+}
+
+macro_rules! permission_marker {
+    ($($name:ident => $permission:ident),* $(,)?) => {
+        $(pub struct $name;
+
+        impl RequiredPermission for $name {
+            const PERMISSION: Permission = Permission::$permission;
+        })*
+    };
+}
+
+permission_marker!(
+    ReadUsers => UsersRead,
+    ManageUsers => UsersManage,
+    ImpersonateUsers => UsersImpersonate,
+    ManageRoles => RolesManage,
+    ModerateCatalog => CatalogModerate,
+    ReadFinance => FinanceRead,
+    ReadAudit => AuditRead,
+);
+
+/* Type-level markers for the API key scopes a route can require */
+pub trait RequiredScope: Send + Sync {
+    const SCOPE: ApiKeyScope;
+}
+
+macro_rules! scope_marker {
+    ($($name:ident => $scope:ident),* $(,)?) => {
+        $(pub struct $name;
+
+        impl RequiredScope for $name {
+            const SCOPE: ApiKeyScope = ApiKeyScope::$scope;
+        })*
+    };
+}
+
+scope_marker!(
+    ReadProfile => ProfileRead,
+    WriteProfile => ProfileWrite,
+    ReadCatalog => CatalogRead,
+    WriteCatalog => CatalogWrite,
+    WriteInventory => InventoryWrite,
+    ReadOrders => OrdersRead,
+);
+
+/// Returns the authenticated user put into the request by `jwt_auth`.
+fn authenticated_user(parts: &Parts) -> Result<JWTAuthMiddleware> {
+    parts
+        .extensions
+        .get::<JWTAuthMiddleware>()
+        .cloned()
+        .ok_or_else(|| UserUnauthorized("You are not logged in".to_string()))
+}
+
+/// Checks the role of a login session the user started themselves.
+///
+/// API keys carry their owner's role and impersonation tokens the role of the impersonated user,
+/// neither of them is allowed to act with it on role guarded routes.
+fn ensure_role(jwt: &JWTAuthMiddleware, allowed: &[UserRole]) -> Result<()> {
+    if jwt.api_key_id.is_some() || jwt.impersonator.is_some() {
+        return Err(InvalidUserRole(
+            "This action requires a login session of your own".to_string(),
+        ));
+    }
+
+    let role = jwt.user_type.parse::<UserRole>()?;
+
+    if !allowed.contains(&role) {
+        let allowed: Vec<&str> = allowed.iter().map(UserRole::as_str).collect();
+        return Err(InvalidUserRole(format!(
+            "This action requires one of the roles: {}",
+            allowed.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Extracts the authenticated user, rejecting anyone without role `R`.
+///
+/// API keys and impersonation tokens are always rejected. Must be used on routes layered with `jwt_auth`.
+pub struct RequireRole<R: Role> {
+    pub user: JWTAuthMiddleware,
+    role: PhantomData<R>,
+}
+
+#[async_trait]
+impl<S: Send + Sync, R: Role> FromRequestParts<S> for RequireRole<R> {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        let jwt = authenticated_user(parts)?;
+        ensure_role(&jwt, &[R::ROLE])?;
+        Ok(Self {
+            user: jwt,
+            role: PhantomData,
+        })
+    }
+}
+
+/// Extracts the authenticated user, rejecting anyone without one of the roles in `R`.
+///
+/// API keys and impersonation tokens are always rejected. Must be used on routes layered with `jwt_auth`.
+pub struct RequireAnyRole<R: RoleSet> {
+    pub user: JWTAuthMiddleware,
+    roles: PhantomData<R>,
+}
+
+#[async_trait]
+impl<S: Send + Sync, R: RoleSet> FromRequestParts<S> for RequireAnyRole<R> {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        let jwt = authenticated_user(parts)?;
+        ensure_role(&jwt, &R::roles())?;
+        Ok(Self {
+            user: jwt,
+            roles: PhantomData,
+        })
+    }
+}
+
+/// Extracts the authenticated user, rejecting anyone whose role lacks permission `P`.
+///
+/// Must be used on routes layered with `jwt_auth`.
+pub struct RequirePermission<P: RequiredPermission> {
+    pub user: JWTAuthMiddleware,
+    permission: PhantomData<P>,
+}
+
+#[async_trait]
+impl<S: Send + Sync, P: RequiredPermission> FromRequestParts<S> for RequirePermission<P> {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        let jwt = authenticated_user(parts)?;
+
+        if !jwt.permissions.contains(&P::PERMISSION) {
+            return Err(InvalidUserRole(format!(
+                "Missing permission '{}'",
+                P::PERMISSION.as_str()
+            )));
+        }
+
+        Ok(Self {
+            user: jwt,
+            permission: PhantomData,
+        })
+    }
+}
+
+/// Extracts the authenticated user, rejecting API keys that were not granted scope `Sc`.
+///
+/// Login sessions are not scope limited and always pass. Must be used on routes layered
+/// with `jwt_auth`.
+pub struct RequireScope<Sc: RequiredScope> {
+    pub user: JWTAuthMiddleware,
+    scope: PhantomData<Sc>,
+}
+
+#[async_trait]
+impl<S: Send + Sync, Sc: RequiredScope> FromRequestParts<S> for RequireScope<Sc> {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        let jwt = authenticated_user(parts)?;
+
+        if let Some(scopes) = &jwt.scopes {
+            if !scopes.contains(&Sc::SCOPE) {
+                return Err(InvalidUserRole(format!(
+                    "API key is missing scope '{}'",
+                    Sc::SCOPE.as_str()
+                )));
+            }
+        }
+
+        Ok(Self {
+            user: jwt,
+            scope: PhantomData,
+        })
+    }
+}
+
+/// Rejects requests made with an impersonation token.
+pub(super) fn ensure_not_impersonated(jwt: &JWTAuthMiddleware) -> Result<()> {
+    if jwt.impersonator.is_some() {
+        return Err(UserUnauthorized(
+            "This action is not available while impersonating a user".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Extracts the authenticated user, rejecting requests authenticated with an API key.
+///
+/// Used on account management routes that only a logged in person may call. Must be used on
+/// routes layered with `jwt_auth`.
+pub struct RequireSession {
+    pub user: JWTAuthMiddleware,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequireSession {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        let jwt = authenticated_user(parts)?;
+
+        if jwt.api_key_id.is_some() {
+            return Err(UserUnauthorized(
+                "This action requires a login session, API keys are not accepted".to_string(),
+            ));
+        }
+
+        Ok(Self { user: jwt })
+    }
+}
+
+/// Extracts the authenticated user of a login session the user started themselves.
+///
+/// Like `RequireSession`, but also rejects impersonation tokens. Used on routes that change
+/// credentials, second factors or payment data. Must be used on routes layered with `jwt_auth`.
+pub struct RequireAccountOwner {
+    pub user: JWTAuthMiddleware,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequireAccountOwner {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        let RequireSession { user: jwt } = RequireSession::from_request_parts(parts, state).await?;
+        ensure_not_impersonated(&jwt)?;
+        Ok(Self { user: jwt })
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, HeaderMap},
+};
+
+use environment::Environment;
+
+/// Network details of the client that sent the request.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// Reverse proxies whose `X-Forwarded-For` header is believed, from `TRUSTED_PROXIES`.
+pub fn trusted_proxies(env: &Environment) -> Vec<IpAddr> {
+    env.trusted_proxies
+        .split(',')
+        .filter_map(|proxy| proxy.trim().parse().ok())
+        .collect()
+}
+
+impl ClientInfo {
+    /// Takes the client address from the connection, or from `X-Forwarded-For` when the
+    /// connection comes from a trusted proxy.
+    ///
+    /// Anyone can send the header, so it is only read when a trusted proxy sent the request. The
+    /// proxies append the address they received the request from, which makes the last entry
+    /// not added by a trusted proxy the client.
+    pub fn from_parts(
+        headers: &HeaderMap,
+        remote_addr: Option<SocketAddr>,
+        trusted_proxies: &[IpAddr],
+    ) -> Self {
+        let remote_ip = remote_addr.map(|addr| addr.ip());
+
+        let ip = match remote_ip {
+            Some(remote_ip) if trusted_proxies.contains(&remote_ip) => {
+                let forwarded = headers
+                    .get_all("x-forwarded-for")
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .flat_map(|value| value.split(','))
+                    .collect::<Vec<_>>();
+
+                let mut client_ip = remote_ip;
+                for entry in forwarded.into_iter().rev() {
+                    match entry.trim().parse::<IpAddr>() {
+                        Ok(ip) if trusted_proxies.contains(&ip) => client_ip = ip,
+                        Ok(ip) => {
+                            client_ip = ip;
+                            break;
+                        }
+                        // Nothing before an entry that is not an address can be believed
+                        Err(_) => break,
+                    }
+                }
+                Some(client_ip)
+            }
+            _ => remote_ip,
+        };
+
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        Self {
+            ip: ip.map(|ip| ip.to_string()),
+            user_agent,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> core::result::Result<Self, Self::Rejection> {
+        // Also picks up `MockConnectInfo` when the router is driven without a listener
+        let remote_addr = ConnectInfo::<SocketAddr>::from_request_parts(parts, state)
+            .await
+            .ok()
+            .map(|ConnectInfo(addr)| addr);
+
+        Ok(Self::from_parts(
+            &parts.headers,
+            remote_addr,
+            &trusted_proxies(&Environment::new()),
+        ))
+    }
+}
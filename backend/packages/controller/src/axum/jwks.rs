@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderValue},
+    response::IntoResponse,
+    Json,
+};
+
+use model::authorization::jwk::JwkSet;
+use state::axum::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Public keys verifying access tokens", body = JwkSet)
+    ),
+    description = "Publish the public keys of every trusted access token key, matched to tokens by their `kid` header, so other services can verify access tokens on their own."
+)]
+pub async fn jwks(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=300"),
+        )],
+        Json(app_state.keyring.jwks()),
+    )
+}
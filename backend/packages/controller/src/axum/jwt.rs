@@ -3,7 +3,7 @@ use std::sync::Arc;
 use axum::{
     body::Body,
     extract::State,
-    http::{header, Request},
+    http::{header, HeaderMap, HeaderValue, Request},
     middleware::Next,
     response::IntoResponse,
 };
@@ -12,34 +12,169 @@ use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use environment::Environment;
 use errors::{
-    Error::{DatabaseErrorExecution, TokenError},
+    Error::{DatabaseErrorExecution, StringError, TokenError, UserNotVerified},
     Result,
 };
+use model::{
+    authorization::{jwk::KeyUse, permission::Permission, scope::ApiKeyScope, token::TokenDetails},
+    domain::{
+        api_key::ApiKey as ApiKeyData,
+        audit_event::{AuditEventType, AuditOutcome},
+    },
+};
+use service::{api_key::api_key_service::ApiKeyServiceTrait, role::role_service::RoleServiceTrait};
 use state::axum::AppState;
 
+use super::{audit::AuditEntry, client_info::ClientInfo};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JWTAuthMiddleware {
     pub entity_id: String,
     pub access_token_uuid: Uuid,
     pub user_type: String,
     pub user_id: String,
+    pub session_id: Option<String>,
+    pub permissions: Vec<Permission>,
+    /// Set when the request is authenticated with an API key instead of a session
+    pub api_key_id: Option<String>,
+    /// Scopes of the API key, `None` for sessions which are not scope limited
+    pub scopes: Option<Vec<ApiKeyScope>>,
+    /// Staff user acting as `user_id` through an impersonation token
+    pub impersonator: Option<String>,
+}
+
+/// Response header flagging every response served to an impersonation token.
+pub const IMPERSONATED_BY_HEADER: &str = "x-impersonated-by";
+
+/// Credential a request carries that checked out as issued by us.
+enum Credential {
+    ApiKey(ApiKeyData),
+    AccessToken(TokenDetails),
+}
+
+impl Credential {
+    /// User the credential was issued to.
+    fn user_id(&self) -> &str {
+        match self {
+            Credential::ApiKey(api_key) => &api_key.user_id,
+            Credential::AccessToken(token) => &token.user_id,
+        }
+    }
+}
+
+/// Builds the request context of an `Authorization: ApiKey ...` request.
+///
+/// API keys carry the owner's role but none of its permissions, and role guarded routes reject
+/// them, so they cannot reach admin routes.
+async fn api_key_auth(data: &AppState, api_key: ApiKeyData) -> Result<JWTAuthMiddleware> {
+    let user = data
+        .user_service
+        .get_enabled_user_by_id(&api_key.user_id)
+        .await?;
+
+    if !user.verified {
+        return Err(UserNotVerified("User is not verified".to_string()));
+    }
+
+    Ok(JWTAuthMiddleware {
+        entity_id: api_key.user_id.clone(),
+        // API keys are not backed by an access token
+        access_token_uuid: Uuid::nil(),
+        user_type: user.role,
+        user_id: api_key.user_id,
+        session_id: None,
+        permissions: Vec::new(),
+        api_key_id: Some(api_key.id),
+        scopes: Some(api_key.scopes),
+        impersonator: None,
+    })
 }
 
 #[tracing::instrument(err, skip_all)]
 pub async fn jwt_auth(
     cookie_jar: CookieJar,
     State(data): State<Arc<AppState>>,
+    client_info: ClientInfo,
     mut req: Request<Body>,
     next: Next,
 ) -> Result<impl IntoResponse> {
+    let request_line = format!("{} {}", req.method(), req.uri().path());
+
+    // Missing, malformed and forged credentials are turned away without an audit row, anyone can
+    // send those in bulk
+    let credential = credential(&data, &cookie_jar, req.headers()).await?;
+    let user_id = credential.user_id().to_string();
+
+    let context = match authenticate(&data, credential).await {
+        Ok(context) => context,
+        Err(e) => {
+            AuditEntry::new(AuditEventType::TokenRejected, AuditOutcome::Failure)
+                .target(&user_id)
+                .detail(format!("{}: {}", request_line, e))
+                .record(&data, &client_info)
+                .await;
+            return Err(e);
+        }
+    };
+
+    let Some(impersonator) = context.impersonator.clone() else {
+        req.extensions_mut().insert(context);
+        return Ok(next.run(req).await);
+    };
+
+    // Everything done while impersonating is attributed to the staff user in the audit log
+    let user_id = context.user_id.clone();
+    req.extensions_mut().insert(context);
+    let mut response = next.run(req).await;
+
+    let outcome = if response.status().is_success() {
+        AuditOutcome::Success
+    } else {
+        AuditOutcome::Failure
+    };
+    AuditEntry::new(AuditEventType::ImpersonatedRequest, outcome)
+        .actor(&impersonator)
+        .target(&user_id)
+        .detail(format!(
+            "{} -> {}",
+            request_line,
+            response.status().as_u16()
+        ))
+        .record(&data, &client_info)
+        .await;
+
+    response.headers_mut().insert(
+        IMPERSONATED_BY_HEADER,
+        HeaderValue::from_str(&impersonator).map_err(|e| StringError(e.to_string()))?,
+    );
+    Ok(response)
+}
+
+/// Finds the API key or access token of a request and checks that we issued it.
+async fn credential(
+    data: &AppState,
+    cookie_jar: &CookieJar,
+    headers: &HeaderMap,
+) -> Result<Credential> {
+    // Integrations authenticate with an API key instead of a session
+    let api_key = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|auth_header| auth_header.to_str().ok())
+        .and_then(|auth_value| auth_value.strip_prefix("ApiKey "))
+        .map(String::from);
+
+    if let Some(api_key) = api_key {
+        let api_key = data.api_key_service.authenticate(&api_key).await?;
+        return Ok(Credential::ApiKey(api_key));
+    }
+
     // Attempt to retrieve the access token from cookie or authorization header
     let option_access_token = cookie_jar
         .get("access_token")
         .map(|cookie| cookie.value().to_string())
         .or_else(|| {
-            req.headers()
+            headers
                 .get(header::AUTHORIZATION)
                 .and_then(|auth_header| auth_header.to_str().ok())
                 .and_then(|auth_value| auth_value.strip_prefix("Bearer ").map(String::from))
@@ -49,13 +184,22 @@ pub async fn jwt_auth(
     let access_token = option_access_token
         .ok_or_else(|| TokenError("You are not logged in, please provide a token".to_string()))?;
 
-    // Verify JWT token using public key from environment
-    let env = Environment::new();
+    // Verify JWT token against the signing keyring
     let access_token_details =
-        service::auth::jwt::verify_jwt_token(env.access_token_public_key.to_owned(), &access_token)
+        service::auth::jwt::verify_jwt_token(&data.keyring, KeyUse::Access, &access_token)
             .await
             .map_err(|e| TokenError(format!("fail: {}", e)))?;
 
+    Ok(Credential::AccessToken(access_token_details))
+}
+
+/// Resolves who a request with a genuine credential is authenticated as.
+async fn authenticate(data: &AppState, credential: Credential) -> Result<JWTAuthMiddleware> {
+    let access_token_details = match credential {
+        Credential::ApiKey(api_key) => return api_key_auth(data, api_key).await,
+        Credential::AccessToken(access_token_details) => access_token_details,
+    };
+
     // Parse UUID from token details
     let access_token_uuid = Uuid::parse_str(&access_token_details.token_uuid.to_string())
         .map_err(|_| TokenError("fail: Invalid token".to_string()))?;
@@ -73,17 +217,40 @@ pub async fn jwt_auth(
         .await
         .map_err(|_| TokenError("fail: Token is invalid or session has expired".to_string()))?;
 
+    // Track activity on the session the token belongs to
+    let session_id = service::auth::session::get_token_session(
+        &data.redis_client,
+        &access_token_uuid.to_string(),
+    )
+    .await?;
+    if let Some(session_id) = &session_id {
+        service::auth::session::touch_session(&data.redis_client, session_id).await?;
+    }
+
     let user_type = access_token_details.user_role;
     let user_id = access_token_details.user_id;
+    let impersonator = access_token_details.impersonator;
 
-    // Insert authenticated user details into request extensions
-    req.extensions_mut().insert(JWTAuthMiddleware {
+    // Disabled accounts are locked out even while their tokens are still valid
+    data.user_service.get_enabled_user_by_id(&user_id).await?;
+    if let Some(impersonator) = &impersonator {
+        data.user_service
+            .get_enabled_user_by_id(impersonator)
+            .await?;
+    }
+
+    // Resolve permissions on every request so role changes apply without re-login
+    let permissions = data.role_service.get_permissions(&user_type).await?;
+
+    Ok(JWTAuthMiddleware {
         access_token_uuid,
         entity_id,
         user_type,
         user_id,
-    });
-
-    // Continue handling the request with the next middleware or handler
-    Ok(next.run(req).await)
+        session_id,
+        permissions,
+        api_key_id: None,
+        scopes: None,
+        impersonator,
+    })
 }
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse},
+    Json,
+};
+use serde_json::json;
+use validator::Validate;
+
+use environment::Environment;
+use errors::{Error::TokenError, Result};
+use model::{
+    domain::audit_event::{AuditEventType, AuditOutcome},
+    web::auth::auth_request::MagicLinkRequest,
+};
+use service::auth::{
+    login_throttle::{throttle_magic_link_request, LoginThrottle},
+    one_time_token::{consume_one_time_token, peek_one_time_token, TokenPurpose},
+    verification::send_magic_link_email,
+};
+use state::axum::AppState;
+
+use super::{
+    audit::AuditEntry, auth::start_session, client_info::ClientInfo,
+    two_factor::two_factor_challenge,
+};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/login/magic-link",
+    request_body = MagicLinkRequest,
+    tag = "auth",
+    responses(
+        (status = 200, description = "Login link sent if the account exists", content_type = "text/plain"),
+        (status = 429, description = "Too many links requested for the email or from the IP, see the `Retry-After` header", content_type = "text/plain")
+    ),
+    description = "Email a single-use login link valid for 15 minutes. The response does not reveal whether the email is registered. Requests are throttled per email and per IP like failed logins."
+)]
+pub async fn request_magic_link(
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(body): Json<MagicLinkRequest>,
+) -> Result<impl IntoResponse> {
+    body.validate()?;
+
+    throttle_magic_link_request(
+        &app_state.redis_client,
+        &LoginThrottle::from_env(&Environment::new()),
+        &body.email,
+        client_info.ip.as_deref(),
+    )
+    .await?;
+
+    // Unverified accounts get no link, opening it would log in before the address is confirmed
+    if let Ok(user) = app_state.user_service.login(body.email.clone()).await {
+        // Delivery problems must not change the response, otherwise they reveal the account
+        if let Err(e) = send_magic_link_email(
+            &app_state.redis_client,
+            &app_state.mailer,
+            &user.id,
+            &user.email,
+        )
+        .await
+        {
+            tracing::error!("Failed to send magic link email: {}", e);
+        }
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+/// Page asking to confirm the login, the form posts back to the link itself.
+const CONFIRMATION_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><meta name="referrer" content="no-referrer"><title>Log in to VirtuMart</title></head>
+<body>
+<h1>Log in to VirtuMart</h1>
+<form method="post"><button type="submit">Log in</button></form>
+</body>
+</html>
+"#;
+
+const EXPIRED_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><title>Log in to VirtuMart</title></head>
+<body>
+<h1>This login link is invalid or has expired</h1>
+<p>Request a new link to log in.</p>
+</body>
+</html>
+"#;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/login/magic-link/{token}",
+    tag = "auth",
+    params(("token" = String, Path, description = "Login token received by email")),
+    responses(
+        (status = 200, description = "Page with a button confirming the login", content_type = "text/html"),
+        (status = 404, description = "Token invalid or expired", content_type = "text/html")
+    ),
+    description = "Show the page an emailed login link opens. It does not use up the link, mail scanners and link previews fetch it too; the login happens when the page posts back to the same URL."
+)]
+pub async fn magic_link_confirmation(
+    State(app_state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse> {
+    let user_id =
+        peek_one_time_token(&app_state.redis_client, TokenPurpose::MagicLink, &token).await?;
+
+    Ok(match user_id {
+        Some(_) => (StatusCode::OK, Html(CONFIRMATION_PAGE)),
+        None => (StatusCode::NOT_FOUND, Html(EXPIRED_PAGE)),
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/login/magic-link/{token}",
+    tag = "auth",
+    params(("token" = String, Path, description = "Login token received by email")),
+    responses(
+        (status = 200, description = "User authenticated, or a `challenge_token` when the account uses 2FA", content_type = "text/plain", example = super::data_example::user_registered),
+        (status = 401, description = "Token invalid or expired", content_type = "text/plain")
+    ),
+    description = "Exchange an emailed login link for the same tokens and cookies as `/api/v1/login`. The link can only be used once."
+)]
+pub async fn magic_link_login(
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse> {
+    let user_id = consume_one_time_token(&app_state.redis_client, TokenPurpose::MagicLink, &token)
+        .await?
+        .ok_or_else(|| TokenError("Login link is invalid or has expired".to_string()))?;
+
+    let user = app_state
+        .user_service
+        .get_enabled_user_by_id(&user_id)
+        .await?;
+
+    if user.totp_secret.is_some() || user.two_factor_required {
+        return two_factor_challenge(&app_state, &user).await;
+    }
+
+    AuditEntry::new(AuditEventType::Login, AuditOutcome::Success)
+        .actor(&user.id)
+        .detail("magic link")
+        .record(&app_state, &client_info)
+        .await;
+
+    start_session(&app_state, &user.id, &user.role, &client_info).await
+}
@@ -1,3 +1,18 @@
+pub mod admin;
+pub mod api_key;
+pub mod audit;
+pub mod auth;
+pub mod authorization;
+pub mod client_info;
 pub mod data_example;
+pub mod jwks;
 pub mod jwt;
+pub mod magic_link;
+pub mod oidc;
+pub mod passkey;
+pub mod password;
+pub mod role;
+pub mod session;
+pub mod two_factor;
 pub mod user;
+pub mod verification;
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use serde_json::json;
+
+use errors::{
+    Error::{TokenError, UserUnauthorized},
+    Result,
+};
+use model::{
+    domain::{
+        audit_event::{AuditEventType, AuditOutcome},
+        user::User as UserData,
+    },
+    web::auth::auth_request::OidcCallback,
+};
+use service::{
+    auth::oidc::{state_binding, state_matches, AUTHORIZATION_MAX_AGE},
+    user::user_service::UserServiceTrait,
+};
+use state::axum::AppState;
+
+use super::{
+    audit::AuditEntry, auth::start_session, client_info::ClientInfo,
+    two_factor::two_factor_challenge,
+};
+
+/// Cookie binding a started login to the browser, see `service::auth::oidc`.
+const STATE_COOKIE: &str = "oidc_state";
+
+fn state_cookie(value: String, max_age: time::Duration) -> Cookie<'static> {
+    Cookie::build((STATE_COOKIE, value))
+        .path("/api/v1/oidc")
+        .max_age(max_age)
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .build()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/oidc/{provider}/authorize",
+    tag = "auth",
+    params(("provider" = String, Path, description = "Name of a configured OIDC provider")),
+    responses(
+        (status = 200, description = "URL to send the user to", content_type = "text/plain"),
+        (status = 404, description = "Unknown provider", content_type = "text/plain")
+    ),
+    description = "Start logging in with an OpenID Connect provider. The returned `authorization_url` carries the state, nonce and PKCE challenge of this login. The `oidc_state` cookie set alongside it must come back with the callback."
+)]
+pub async fn oidc_authorize(
+    cookie_jar: CookieJar,
+    State(app_state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+) -> Result<impl IntoResponse> {
+    let authorization = app_state
+        .oidc_client
+        .authorization_url(&app_state.redis_client, &provider)
+        .await?;
+
+    let cookie_jar = cookie_jar.add(state_cookie(
+        state_binding(&authorization.state),
+        time::Duration::minutes(AUTHORIZATION_MAX_AGE),
+    ));
+
+    Ok((
+        cookie_jar,
+        Json(json!({
+            "status": "success",
+            "data": { "authorization_url": authorization.url }
+        })),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/oidc/{provider}/callback",
+    tag = "auth",
+    params(
+        ("provider" = String, Path, description = "Name of a configured OIDC provider"),
+        ("state" = String, Query, description = "State returned by the provider"),
+        ("code" = Option<String>, Query, description = "Authorization code"),
+        ("error" = Option<String>, Query, description = "Error reported by the provider")
+    ),
+    responses(
+        (status = 200, description = "User authenticated, or a `challenge_token` when the account uses 2FA", content_type = "text/plain", example = super::data_example::user_registered),
+        (status = 401, description = "Login refused, state invalid or not started in this browser", content_type = "text/plain")
+    ),
+    description = "Finish an OpenID Connect login started in the same browser. The identity is linked to the verified account with the same email, or a new customer account is created for it. An unverified account with that email is never linked."
+)]
+pub async fn oidc_callback(
+    cookie_jar: CookieJar,
+    State(app_state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    client_info: ClientInfo,
+    Query(params): Query<OidcCallback>,
+) -> Result<impl IntoResponse> {
+    let binding = cookie_jar
+        .get(STATE_COOKIE)
+        .map(|cookie| cookie.value().to_string());
+    let cookie_jar = cookie_jar.remove(state_cookie(String::new(), time::Duration::ZERO));
+
+    let user = match identity_user(&app_state, &provider, binding.as_deref(), params).await {
+        Ok(user) => user,
+        Err(e) => {
+            AuditEntry::new(AuditEventType::Login, AuditOutcome::Failure)
+                .detail(format!("oidc:{}: {}", provider, e))
+                .record(&app_state, &client_info)
+                .await;
+            return Err(e);
+        }
+    };
+
+    if user.totp_secret.is_some() || user.two_factor_required {
+        return Ok((cookie_jar, two_factor_challenge(&app_state, &user).await?));
+    }
+
+    AuditEntry::new(AuditEventType::Login, AuditOutcome::Success)
+        .actor(&user.id)
+        .detail(format!("oidc:{}", provider))
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok((
+        cookie_jar,
+        start_session(&app_state, &user.id, &user.role, &client_info).await?,
+    ))
+}
+
+/// Completes the provider's callback and returns the user the identity belongs to.
+async fn identity_user(
+    app_state: &AppState,
+    provider: &str,
+    binding: Option<&str>,
+    params: OidcCallback,
+) -> Result<UserData> {
+    if let Some(error) = params.error {
+        return Err(UserUnauthorized(format!("OIDC login failed: {}", error)));
+    }
+    // Checked before the state is used up, a forged callback must not cancel the real login
+    if !binding.is_some_and(|binding| state_matches(&params.state, binding)) {
+        return Err(TokenError(
+            "OIDC login was not started in this browser".to_string(),
+        ));
+    }
+    let code = params
+        .code
+        .ok_or_else(|| TokenError("OIDC callback is missing the code".to_string()))?;
+
+    let identity = app_state
+        .oidc_client
+        .complete(&app_state.redis_client, provider, &code, &params.state)
+        .await?;
+
+    app_state.user_service.login_with_identity(&identity).await
+}
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use validator::Validate;
+
+use errors::{Error::UserNotVerified, Result};
+use model::{
+    domain::{
+        audit_event::{AuditEventType, AuditOutcome},
+        user::User as UserData,
+    },
+    web::passkey::passkey_request::{PasskeyLogin, PasskeyRegistration},
+};
+use service::passkey::passkey_service::PasskeyServiceTrait;
+use state::axum::AppState;
+
+use super::{
+    audit::AuditEntry,
+    auth::start_session,
+    authorization::{RequireAccountOwner, RequireSession},
+    client_info::ClientInfo,
+};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/passkeys/register/start",
+    tag = "passkey",
+    responses(
+        (status = 200, description = "Options for `navigator.credentials.create()`", content_type = "text/plain"),
+        (status = 401, description = "Not logged in", content_type = "text/plain")
+    ),
+    description = "Start registering a passkey for the authenticated user. The returned options are valid for 5 minutes."
+)]
+pub async fn start_passkey_registration(
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let user = app_state
+        .user_service
+        .get_enabled_user_by_id(&jwt.user_id)
+        .await?;
+    let exclude_credentials = app_state
+        .passkey_service
+        .credential_ids(&jwt.user_id)
+        .await?;
+
+    let options = app_state
+        .webauthn
+        .start_registration(
+            &app_state.redis_client,
+            &jwt.user_id,
+            &user.email,
+            &exclude_credentials,
+        )
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "public_key": options }
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/passkeys/register/finish",
+    request_body = PasskeyRegistration,
+    tag = "passkey",
+    responses(
+        (status = 200, description = "Passkey registered", content_type = "text/plain"),
+        (status = 401, description = "Attestation rejected or registration expired", content_type = "text/plain")
+    ),
+    description = "Store the passkey created by the authenticator, after checking its challenge, origin and user verification."
+)]
+pub async fn finish_passkey_registration(
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(payload): Json<PasskeyRegistration>,
+) -> Result<impl IntoResponse> {
+    payload.validate()?;
+
+    let credential = app_state
+        .webauthn
+        .finish_registration(&app_state.redis_client, &jwt.user_id, &payload)
+        .await?;
+    let passkey = app_state
+        .passkey_service
+        .add_passkey(&jwt.user_id, &payload.name, credential)
+        .await?;
+
+    AuditEntry::new(AuditEventType::PasskeyAdded, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&jwt.user_id)
+        .detail(payload.name)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "passkey": passkey }
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/passkeys",
+    tag = "passkey",
+    responses(
+        (status = 200, description = "Passkeys of the user", content_type = "text/plain"),
+        (status = 401, description = "Not logged in", content_type = "text/plain")
+    ),
+    description = "List the passkeys of the authenticated user and when they were last used."
+)]
+pub async fn list_passkeys(
+    RequireSession { user: jwt }: RequireSession,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let passkeys = app_state
+        .passkey_service
+        .list_passkeys(&jwt.user_id)
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "passkeys": passkeys }
+    })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/user/passkeys/{id}",
+    tag = "passkey",
+    params(("id" = String, Path, description = "Passkey id")),
+    responses(
+        (status = 200, description = "Passkey removed", content_type = "text/plain"),
+        (status = 404, description = "Passkey not found", content_type = "text/plain")
+    ),
+    description = "Remove one of the authenticated user's passkeys, it can no longer be used to log in."
+)]
+pub async fn delete_passkey(
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    app_state
+        .passkey_service
+        .remove_passkey(&jwt.user_id, &id)
+        .await?;
+
+    AuditEntry::new(AuditEventType::PasskeyRemoved, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&jwt.user_id)
+        .detail(id)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/login/passkey/start",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Options for `navigator.credentials.get()`", content_type = "text/plain")
+    ),
+    description = "Start logging in with a passkey. The authenticator offers the passkeys it holds for this site, no email is needed."
+)]
+pub async fn start_passkey_login(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let options = app_state
+        .webauthn
+        .start_authentication(&app_state.redis_client)
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "public_key": options }
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/login/passkey/finish",
+    request_body = PasskeyLogin,
+    tag = "auth",
+    responses(
+        (status = 200, description = "User authenticated", content_type = "text/plain", example = super::data_example::user_registered),
+        (status = 401, description = "Assertion rejected or login expired", content_type = "text/plain"),
+        (status = 406, description = "Email not verified", content_type = "text/plain")
+    ),
+    description = "Exchange a passkey assertion for the same tokens and cookies as `/api/v1/login`. Passkeys verify the user themselves, so no second factor is asked."
+)]
+pub async fn finish_passkey_login(
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(body): Json<PasskeyLogin>,
+) -> Result<impl IntoResponse> {
+    let user = match passkey_user(&app_state, &body).await {
+        Ok(user) => user,
+        Err(e) => {
+            AuditEntry::new(AuditEventType::Login, AuditOutcome::Failure)
+                .detail(format!("passkey: {}", e))
+                .record(&app_state, &client_info)
+                .await;
+            return Err(e);
+        }
+    };
+
+    AuditEntry::new(AuditEventType::Login, AuditOutcome::Success)
+        .actor(&user.id)
+        .detail("passkey")
+        .record(&app_state, &client_info)
+        .await;
+
+    start_session(&app_state, &user.id, &user.role, &client_info).await
+}
+
+/// Verifies a passkey assertion and returns the user the passkey belongs to.
+async fn passkey_user(app_state: &AppState, login: &PasskeyLogin) -> Result<UserData> {
+    let passkey = app_state
+        .passkey_service
+        .get_by_credential_id(&login.credential_id)
+        .await?;
+    let sign_count = app_state
+        .webauthn
+        .finish_authentication(&app_state.redis_client, &passkey, login)
+        .await?;
+
+    let user = app_state
+        .user_service
+        .get_enabled_user_by_id(&passkey.user_id)
+        .await?;
+    // The authenticator counted this use, so the count is kept even when the login is refused
+    app_state
+        .passkey_service
+        .record_use(&passkey, sign_count)
+        .await?;
+
+    // Like password and magic link logins, an account waiting on its email confirmation stays out
+    if !user.verified {
+        return Err(UserNotVerified("User is not verified".to_string()));
+    }
+
+    Ok(user)
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse, Json};
+use serde_json::json;
+use validator::Validate;
+
+use environment::Environment;
+use errors::{Error::TokenError, Result};
+use model::{
+    domain::audit_event::{AuditEventType, AuditOutcome},
+    web::auth::auth_request::{ForgotPassword, ResetPassword},
+};
+use service::{
+    auth::{
+        login_throttle::{throttle_password_reset_request, LoginThrottle},
+        one_time_token::{
+            consume_one_time_token, peek_one_time_token, revoke_one_time_tokens, TokenPurpose,
+        },
+        session::revoke_user_sessions,
+        verification::send_password_reset_email,
+    },
+    user::user_service::UserServiceTrait,
+};
+use state::axum::AppState;
+
+use super::{audit::AuditEntry, client_info::ClientInfo};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/password/forgot",
+    request_body = ForgotPassword,
+    tag = "auth",
+    responses(
+        (status = 200, description = "Reset email sent if the account exists", content_type = "text/plain"),
+        (status = 429, description = "Too many links requested for the email or from the IP, see the `Retry-After` header", content_type = "text/plain")
+    ),
+    description = "Request a password reset link. The response does not reveal whether the email is registered. Requests are throttled per email and per IP like failed logins."
+)]
+pub async fn forgot_password(
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(body): Json<ForgotPassword>,
+) -> Result<impl IntoResponse> {
+    body.validate()?;
+
+    throttle_password_reset_request(
+        &app_state.redis_client,
+        &LoginThrottle::from_env(&Environment::new()),
+        &body.email,
+        client_info.ip.as_deref(),
+    )
+    .await?;
+
+    if let Ok(user) = app_state.user_service.get_user_by_email(&body.email).await {
+        // Delivery problems must not change the response, otherwise they reveal the account
+        if let Err(e) = send_password_reset_email(
+            &app_state.redis_client,
+            &app_state.mailer,
+            &user.id,
+            &user.email,
+        )
+        .await
+        {
+            tracing::error!("Failed to send password reset email: {}", e);
+        }
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/password/reset",
+    request_body = ResetPassword,
+    tag = "auth",
+    responses(
+        (status = 200, description = "Password changed", content_type = "text/plain"),
+        (status = 401, description = "Token invalid or expired", content_type = "text/plain"),
+        (status = 422, description = "Password rejected by the password policy, the token stays valid", content_type = "text/plain")
+    ),
+    description = "Set a new password using the emailed reset token. The token works once, and every other reset token and existing session of the user is revoked."
+)]
+pub async fn reset_password(
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(body): Json<ResetPassword>,
+) -> Result<impl IntoResponse> {
+    body.validate()?;
+
+    let invalid = || TokenError("Reset link is invalid or has expired".to_string());
+    let usvc = &app_state.user_service;
+
+    // A password rejected by the policy leaves the link usable for another attempt
+    let user_id = peek_one_time_token(
+        &app_state.redis_client,
+        TokenPurpose::PasswordReset,
+        &body.token,
+    )
+    .await?
+    .ok_or_else(invalid)?;
+    usvc.check_password(&user_id, &body.password).await?;
+
+    // Only the request that takes the token out of Redis gets to set the password
+    consume_one_time_token(
+        &app_state.redis_client,
+        TokenPurpose::PasswordReset,
+        &body.token,
+    )
+    .await?
+    .filter(|consumed_by| *consumed_by == user_id)
+    .ok_or_else(invalid)?;
+
+    usvc.update_password(&user_id, &body.password).await?;
+
+    revoke_one_time_tokens(
+        &app_state.redis_client,
+        TokenPurpose::PasswordReset,
+        &user_id,
+    )
+    .await?;
+    revoke_user_sessions(&app_state.redis_client, &user_id).await?;
+
+    AuditEntry::new(AuditEventType::PasswordChanged, AuditOutcome::Success)
+        .actor(&user_id)
+        .target(&user_id)
+        .detail("password reset")
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use validator::Validate;
+
+use errors::Result;
+use model::{
+    domain::audit_event::{AuditEventType, AuditOutcome},
+    web::role::role_request::{Role as RoleRequest, RoleUpdate},
+};
+use service::role::role_service::RoleServiceTrait;
+use state::axum::AppState;
+
+use super::{
+    audit::AuditEntry,
+    authorization::{ManageRoles, RequirePermission},
+    client_info::ClientInfo,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/roles",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Built-in and custom roles", content_type = "text/plain"),
+        (status = 403, description = "Missing permission roles:manage", content_type = "text/plain")
+    ),
+    description = "List every role together with the permissions it grants."
+)]
+pub async fn list_roles(
+    _auth: RequirePermission<ManageRoles>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let roles = app_state.role_service.list_roles().await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "roles": roles }
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/roles",
+    request_body = RoleRequest,
+    tag = "admin",
+    responses(
+        (status = 200, description = "Role created", content_type = "text/plain"),
+        (status = 403, description = "Missing permission roles:manage", content_type = "text/plain"),
+        (status = 406, description = "Role already exists", content_type = "text/plain")
+    ),
+    description = "Define a new role with a set of named permissions."
+)]
+pub async fn create_role(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageRoles>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(payload): Json<RoleRequest>,
+) -> Result<impl IntoResponse> {
+    payload.validate()?;
+
+    let role = app_state.role_service.create_role(payload).await?;
+
+    AuditEntry::new(AuditEventType::RoleCreated, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&role.name)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "role": role }
+    })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/roles/{name}",
+    request_body = RoleUpdate,
+    tag = "admin",
+    params(("name" = String, Path, description = "Role name")),
+    responses(
+        (status = 200, description = "Role updated", content_type = "text/plain"),
+        (status = 403, description = "Missing permission or built-in role", content_type = "text/plain"),
+        (status = 404, description = "Role not found", content_type = "text/plain")
+    ),
+    description = "Change the description or permissions of a custom role."
+)]
+pub async fn update_role(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageRoles>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(name): Path<String>,
+    Json(payload): Json<RoleUpdate>,
+) -> Result<impl IntoResponse> {
+    payload.validate()?;
+
+    app_state.role_service.update_role(&name, payload).await?;
+
+    AuditEntry::new(AuditEventType::RoleUpdated, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&name)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/roles/{name}",
+    tag = "admin",
+    params(("name" = String, Path, description = "Role name")),
+    responses(
+        (status = 200, description = "Role deleted", content_type = "text/plain"),
+        (status = 403, description = "Missing permission or built-in role", content_type = "text/plain"),
+        (status = 404, description = "Role not found", content_type = "text/plain")
+    ),
+    description = "Delete a custom role. Users still holding it lose every permission it granted."
+)]
+pub async fn delete_role(
+    RequirePermission { user: jwt, .. }: RequirePermission<ManageRoles>,
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse> {
+    app_state.role_service.delete_role(&name).await?;
+
+    AuditEntry::new(AuditEventType::RoleDeleted, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&name)
+        .record(&app_state, &client_info)
+        .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use errors::{Error::DataNotAvailable, Result};
+use service::auth::session::{get_session, get_user_sessions, revoke_session};
+use state::axum::AppState;
+
+use super::authorization::{RequireAccountOwner, RequireSession};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/sessions",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Active sessions of the user", content_type = "text/plain"),
+        (status = 401, description = "Not logged in", content_type = "text/plain")
+    ),
+    description = "List every active session (device) of the authenticated user."
+)]
+pub async fn list_sessions(
+    State(app_state): State<Arc<AppState>>,
+    RequireSession { user: jwt }: RequireSession,
+) -> Result<impl IntoResponse> {
+    let sessions = get_user_sessions(&app_state.redis_client, &jwt.user_id).await?;
+
+    let sessions: Vec<_> = sessions
+        .into_iter()
+        .map(|session| {
+            json!({
+                "id": session.session_id,
+                "ip": session.ip,
+                "user_agent": session.user_agent,
+                "created_at": session.created_at,
+                "last_seen": session.last_seen,
+                "current": jwt.session_id.as_deref() == Some(session.session_id.as_str()),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "sessions": sessions }
+    })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/sessions/{id}",
+    tag = "auth",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "Session revoked", content_type = "text/plain"),
+        (status = 404, description = "Session not found", content_type = "text/plain")
+    ),
+    description = "Revoke a single session of the authenticated user, logging that device out."
+)]
+pub async fn delete_session(
+    State(app_state): State<Arc<AppState>>,
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let redis_client = &app_state.redis_client;
+
+    // Sessions of other users are reported as missing to avoid leaking their existence
+    let session = get_session(redis_client, &id)
+        .await?
+        .filter(|session| session.user_id == jwt.user_id)
+        .ok_or_else(|| DataNotAvailable(format!("Session '{}' not found", id)))?;
+
+    revoke_session(redis_client, &jwt.user_id, &session.session_id).await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
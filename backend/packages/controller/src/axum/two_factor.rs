@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::Response, response::IntoResponse, Json};
+use serde_json::{json, Value};
+
+use environment::Environment;
+use errors::{
+    Error::{DataExist, DataNotAvailable, StringError, TokenError, UserUnauthorized},
+    Result,
+};
+use model::{
+    authorization::two_factor::LoginChallenge,
+    domain::{
+        audit_event::{AuditEventType, AuditOutcome},
+        user::User as UserData,
+    },
+    web::auth::auth_request::{
+        DisableTwoFactor, TwoFactorChallenge, TwoFactorCode, TwoFactorLogin,
+    },
+};
+use service::{
+    auth::{
+        login_throttle::{
+            check_login_allowed, clear_login_failures, record_login_failure, LoginThrottle,
+        },
+        two_factor::{
+            create_login_challenge, delete_enrollment_secret, delete_login_challenge,
+            generate_recovery_codes, generate_secret, get_enrollment_secret, get_login_challenge,
+            hash_recovery_code, otpauth_uri, record_challenge_failure, save_enrollment_secret,
+            verify_code_once,
+        },
+    },
+    user::user_service::UserServiceTrait,
+};
+use state::axum::AppState;
+
+use super::{
+    audit::AuditEntry, auth::start_session, authorization::RequireAccountOwner,
+    client_info::ClientInfo,
+};
+
+/// Answers a password-verified login with a challenge instead of tokens.
+pub(super) async fn two_factor_challenge(
+    app_state: &AppState,
+    user: &UserData,
+) -> Result<Response<String>> {
+    let challenge = LoginChallenge {
+        user_id: user.id.clone(),
+        user_role: user.role.clone(),
+        email: user.email.clone(),
+        setup_required: user.totp_secret.is_none(),
+    };
+    let challenge_token = create_login_challenge(&app_state.redis_client, &challenge).await?;
+
+    Ok(Response::new(
+        json!({
+            "status": "success",
+            "data": {
+                "two_factor_required": true,
+                "setup_required": challenge.setup_required,
+                "challenge_token": challenge_token
+            }
+        })
+        .to_string(),
+    ))
+}
+
+/// Confirms a pending enrollment, returning the recovery codes to show the user once.
+async fn confirm_enrollment(
+    app_state: &AppState,
+    user_id: &str,
+    code: &str,
+) -> Result<Vec<String>> {
+    let redis_client = &app_state.redis_client;
+
+    let secret = get_enrollment_secret(redis_client, user_id)
+        .await?
+        .ok_or_else(|| DataNotAvailable("No two-factor enrollment in progress".to_string()))?;
+
+    if !verify_code_once(redis_client, user_id, &secret, code).await? {
+        return Err(UserUnauthorized("Invalid two-factor code".to_string()));
+    }
+
+    let (recovery_codes, recovery_code_hashes) = generate_recovery_codes();
+    app_state
+        .user_service
+        .enable_two_factor(user_id, &secret, recovery_code_hashes)
+        .await?;
+    delete_enrollment_secret(redis_client, user_id).await?;
+
+    Ok(recovery_codes)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/2fa/enroll",
+    tag = "two_factor",
+    responses(
+        (status = 200, description = "Secret and otpauth URI to scan", content_type = "text/plain"),
+        (status = 406, description = "Two-factor authentication already enabled", content_type = "text/plain")
+    ),
+    description = "Start enrolling an authenticator app. 2FA is only turned on once a code is confirmed with `/api/v1/user/2fa/confirm`."
+)]
+pub async fn enroll_two_factor(
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let user = app_state.user_service.get_user_by_id(&jwt.user_id).await?;
+    if user.totp_secret.is_some() {
+        return Err(DataExist(
+            "Two-factor authentication is already enabled".to_string(),
+        ));
+    }
+
+    let secret = generate_secret();
+    save_enrollment_secret(&app_state.redis_client, &user.id, &secret).await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "secret": secret,
+            "otpauth_uri": otpauth_uri(&secret, &user.email)?
+        }
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/2fa/confirm",
+    request_body = TwoFactorCode,
+    tag = "two_factor",
+    responses(
+        (status = 200, description = "2FA enabled, recovery codes returned once", content_type = "text/plain"),
+        (status = 401, description = "Invalid code", content_type = "text/plain")
+    ),
+    description = "Confirm enrollment with a code from the authenticator app. Returns recovery codes that are never shown again."
+)]
+pub async fn confirm_two_factor(
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+    Json(body): Json<TwoFactorCode>,
+) -> Result<impl IntoResponse> {
+    let recovery_codes = confirm_enrollment(&app_state, &jwt.user_id, &body.code).await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "recovery_codes": recovery_codes }
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/2fa/disable",
+    request_body = DisableTwoFactor,
+    tag = "two_factor",
+    responses(
+        (status = 200, description = "2FA disabled", content_type = "text/plain"),
+        (status = 401, description = "Password is incorrect", content_type = "text/plain"),
+        (status = 403, description = "2FA is required for this account", content_type = "text/plain")
+    ),
+    description = "Turn off two-factor authentication. Not allowed when an admin requires 2FA for the account."
+)]
+pub async fn disable_two_factor(
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    State(app_state): State<Arc<AppState>>,
+    Json(body): Json<DisableTwoFactor>,
+) -> Result<impl IntoResponse> {
+    app_state
+        .user_service
+        .disable_two_factor(&jwt.user_id, &body.password)
+        .await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/login/2fa/setup",
+    request_body = TwoFactorChallenge,
+    tag = "two_factor",
+    responses(
+        (status = 200, description = "Secret and otpauth URI to scan", content_type = "text/plain"),
+        (status = 401, description = "Challenge invalid or expired", content_type = "text/plain")
+    ),
+    description = "Enroll during login when an admin requires 2FA for an account that has none yet. Finish by sending a code to `/api/v1/login/2fa`."
+)]
+pub async fn login_two_factor_setup(
+    State(app_state): State<Arc<AppState>>,
+    Json(body): Json<TwoFactorChallenge>,
+) -> Result<impl IntoResponse> {
+    let challenge = get_login_challenge(&app_state.redis_client, &body.challenge_token)
+        .await?
+        .filter(|challenge| challenge.setup_required)
+        .ok_or_else(|| TokenError("Login challenge is invalid or has expired".to_string()))?;
+
+    let secret = generate_secret();
+    save_enrollment_secret(&app_state.redis_client, &challenge.user_id, &secret).await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "secret": secret,
+            "otpauth_uri": otpauth_uri(&secret, &challenge.email)?
+        }
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/login/2fa",
+    request_body = TwoFactorLogin,
+    tag = "two_factor",
+    responses(
+        (status = 200, description = "User authenticated", content_type = "text/plain"),
+        (status = 401, description = "Invalid code or challenge", content_type = "text/plain"),
+        (status = 429, description = "Too many failed attempts, see the `Retry-After` header", content_type = "text/plain")
+    ),
+    description = "Second login step: exchange the challenge token and an authenticator or recovery code for access & refresh tokens."
+)]
+pub async fn login_two_factor(
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(body): Json<TwoFactorLogin>,
+) -> Result<impl IntoResponse> {
+    let redis_client = &app_state.redis_client;
+    let usvc = &app_state.user_service;
+    let throttle = LoginThrottle::from_env(&Environment::new());
+    let ip = client_info.ip.as_deref();
+
+    let challenge = get_login_challenge(redis_client, &body.challenge_token)
+        .await?
+        .ok_or_else(|| TokenError("Login challenge is invalid or has expired".to_string()))?;
+
+    check_login_allowed(redis_client, &challenge.email, ip).await?;
+
+    let (is_valid, recovery_codes) = if challenge.setup_required {
+        match confirm_enrollment(&app_state, &challenge.user_id, &body.code).await {
+            Ok(recovery_codes) => (true, Some(recovery_codes)),
+            Err(UserUnauthorized(_)) => (false, None),
+            Err(e) => return Err(e),
+        }
+    } else {
+        let user = usvc.get_user_by_id(&challenge.user_id).await?;
+        let is_totp_valid = match &user.totp_secret {
+            Some(secret) => verify_code_once(redis_client, &user.id, secret, &body.code).await?,
+            None => false,
+        };
+        let is_valid = is_totp_valid
+            || usvc
+                .consume_recovery_code(&user.id, &hash_recovery_code(&body.code))
+                .await?;
+        (is_valid, None)
+    };
+
+    if !is_valid {
+        record_challenge_failure(redis_client, &body.challenge_token).await?;
+        record_login_failure(redis_client, &throttle, &challenge.email, ip).await?;
+        AuditEntry::new(AuditEventType::Login, AuditOutcome::Failure)
+            .target(&challenge.user_id)
+            .detail("invalid two-factor code")
+            .record(&app_state, &client_info)
+            .await;
+        return Err(UserUnauthorized("Invalid two-factor code".to_string()));
+    }
+
+    // Concurrent requests with the same challenge race here; only the first one logs in
+    if !delete_login_challenge(redis_client, &body.challenge_token).await? {
+        return Err(TokenError(
+            "Login challenge is invalid or has expired".to_string(),
+        ));
+    }
+    clear_login_failures(redis_client, &challenge.email).await?;
+
+    AuditEntry::new(AuditEventType::Login, AuditOutcome::Success)
+        .actor(&challenge.user_id)
+        .detail("two-factor")
+        .record(&app_state, &client_info)
+        .await;
+
+    let mut response = start_session(
+        &app_state,
+        &challenge.user_id,
+        &challenge.user_role,
+        &client_info,
+    )
+    .await?;
+
+    if let Some(recovery_codes) = recovery_codes {
+        let mut body: Value =
+            serde_json::from_str(response.body()).map_err(|e| StringError(e.to_string()))?;
+        body["data"]["recovery_codes"] = json!(recovery_codes);
+        *response.body_mut() = body.to_string();
+    }
+
+    Ok(response)
+}
@@ -1,28 +1,43 @@
 use std::sync::Arc;
 
 use argon2::{Argon2, PasswordHash, PasswordVerifier};
-use axum::{
-    extract::State,
-    http::{header, HeaderMap, HeaderValue, Response},
-    response::IntoResponse,
-    Extension, Json,
-};
-use axum_extra::extract::cookie::{Cookie, SameSite};
-use serde_json::{json, Value};
+use axum::{extract::State, response::IntoResponse, Json};
+use serde_json::json;
 use validator::Validate;
 
+use environment::Environment;
 use errors::{
-    Error::{LoginFail, StringError, TokenError},
+    Error::{DataNotAvailable, InvalidUserRole, LoginFail},
     Result,
 };
-use model::web::user::user_request::{User as UserRequest, UserLogin};
+use model::{
+    authorization::role::UserRole,
+    domain::{
+        audit_event::{AuditEventType, AuditOutcome},
+        user::User as UserData,
+    },
+    web::user::user_request::{ChangePassword, User as UserRequest, UserLogin, UserUpdate},
+};
 use service::{
-    auth::jwt::{generate_jwt_token, save_token_data_to_redis},
+    auth::{
+        login_throttle::{
+            check_login_allowed, clear_login_failures, record_login_failure, LoginThrottle,
+        },
+        one_time_token::{revoke_one_time_tokens, TokenPurpose},
+        session::revoke_other_sessions,
+        verification::send_verification_email,
+    },
     user::user_service::UserServiceTrait,
 };
 use state::axum::AppState;
 
-use super::jwt::JWTAuthMiddleware;
+use super::{
+    audit::AuditEntry,
+    auth::start_session,
+    authorization::{ensure_not_impersonated, RequireAccountOwner, RequireScope, WriteProfile},
+    client_info::ClientInfo,
+    two_factor::two_factor_challenge,
+};
 
 #[utoipa::path(
     post,
@@ -31,7 +46,8 @@ use super::jwt::JWTAuthMiddleware;
     tag = "user",
     responses(
         (status = 200, description = "User found", content_type = "text/plain", example = super::data_example::user_registered),
-        (status = 404, description = "User not found", content_type = "text/plain")
+        (status = 404, description = "User not found", content_type = "text/plain"),
+        (status = 422, description = "Password rejected by the password policy", content_type = "text/plain")
     ),
 )]
 pub async fn register(
@@ -41,8 +57,28 @@ pub async fn register(
     let usvc = &app_state.user_service;
     payload.0.validate()?;
 
+    // Admin accounts can only be created by other admins
+    if !payload.0.role.parse::<UserRole>()?.is_self_assignable() {
+        return Err(InvalidUserRole(format!(
+            "Role '{}' cannot be self-assigned",
+            payload.0.role
+        )));
+    }
+
     let profile_registered = usvc.register_profile(payload.0).await?;
 
+    // The account exists at this point, a failed delivery can be retried through the resend endpoint
+    if let Err(e) = send_verification_email(
+        &app_state.redis_client,
+        &app_state.mailer,
+        &format!("user:{}", profile_registered.id),
+        &profile_registered.email,
+    )
+    .await
+    {
+        tracing::error!("Failed to send verification email: {}", e);
+    }
+
     Ok(Json(json!({
         "status": "success",
         "data": { "user": profile_registered }
@@ -52,21 +88,117 @@ pub async fn register(
 #[utoipa::path(
     put,
     path = "/api/v1/user",
-    request_body = UserRequest,
+    request_body = UserUpdate,
     tag = "user",
     responses(
         (status = 200, description = "User updated", content_type = "text/plain", example = super::data_example::user_registered),
-        (status = 404, description = "User not found", content_type = "text/plain")
+        (status = 404, description = "User not found", content_type = "text/plain"),
+        (status = 406, description = "Username or email already registered", content_type = "text/plain"),
+        (status = 422, description = "Unknown or protected field in the request", content_type = "text/plain")
     ),
-    description = "Update user information. You can update all fields or select specific fields."
+    description = "Update the username, email or profile fields of the authenticated user. Only the fields present are changed; role, verification state, password and timestamps cannot be set here. A new email leaves the account unverified until the link sent to it is opened."
 )]
 pub async fn update_profile(
     State(app_state): State<Arc<AppState>>,
-    Extension(jwt): Extension<JWTAuthMiddleware>,
-    payload: Json<Value>,
+    RequireScope { user: jwt, .. }: RequireScope<WriteProfile>,
+    client_info: ClientInfo,
+    Json(payload): Json<UserUpdate>,
 ) -> Result<impl IntoResponse> {
+    // The email is where password resets go, support staff must not be able to redirect it
+    ensure_not_impersonated(&jwt)?;
+    payload.validate()?;
+
     let usvc = &app_state.user_service;
-    usvc.update_profile(&jwt.user_id, payload.0).await?;
+    let fields = serde_json::to_value(&payload)
+        .ok()
+        .and_then(|value| {
+            value
+                .as_object()
+                .map(|fields| fields.keys().cloned().collect::<Vec<_>>())
+        })
+        .unwrap_or_default();
+    usvc.update_profile(&jwt.user_id, payload).await?;
+
+    AuditEntry::new(AuditEventType::ProfileUpdated, AuditOutcome::Success)
+        .actor(&jwt.user_id)
+        .target(&jwt.user_id)
+        .detail(format!("fields: {}", fields.join(", ")))
+        .record(&app_state, &client_info)
+        .await;
+
+    // Only a changed email leaves the verified account unverified
+    let user = usvc.get_user_by_id(&jwt.user_id).await?;
+    if !user.verified {
+        // Links already sent out were meant for the old address
+        revoke_one_time_tokens(
+            &app_state.redis_client,
+            TokenPurpose::EmailVerification,
+            &user.id,
+        )
+        .await?;
+
+        // The change is saved, a failed delivery can be retried through the resend endpoint
+        if let Err(e) = send_verification_email(
+            &app_state.redis_client,
+            &app_state.mailer,
+            &user.id,
+            &user.email,
+        )
+        .await
+        {
+            tracing::error!("Failed to send verification email: {}", e);
+        }
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/password",
+    request_body = ChangePassword,
+    tag = "user",
+    responses(
+        (status = 200, description = "Password changed", content_type = "text/plain"),
+        (status = 401, description = "Not logged in or current password is incorrect", content_type = "text/plain"),
+        (status = 422, description = "New password rejected by the password policy", content_type = "text/plain")
+    ),
+    description = "Change the password of the authenticated user. Every other session of the user is revoked, the current one stays logged in."
+)]
+pub async fn change_password(
+    State(app_state): State<Arc<AppState>>,
+    RequireAccountOwner { user: jwt }: RequireAccountOwner,
+    client_info: ClientInfo,
+    Json(body): Json<ChangePassword>,
+) -> Result<impl IntoResponse> {
+    body.validate()?;
+
+    let result = app_state
+        .user_service
+        .change_password(&jwt.user_id, &body.current_password, &body.new_password)
+        .await;
+
+    let entry = match &result {
+        Ok(_) => AuditEntry::new(AuditEventType::PasswordChanged, AuditOutcome::Success),
+        Err(e) => AuditEntry::new(AuditEventType::PasswordChanged, AuditOutcome::Failure)
+            .detail(e.to_string()),
+    };
+    entry
+        .actor(&jwt.user_id)
+        .target(&jwt.user_id)
+        .record(&app_state, &client_info)
+        .await;
+    result?;
+
+    revoke_other_sessions(
+        &app_state.redis_client,
+        &jwt.user_id,
+        jwt.session_id.as_deref(),
+    )
+    .await?;
 
     Ok(Json(json!({
         "status": "success",
@@ -80,19 +212,67 @@ pub async fn update_profile(
     request_body = UserLogin,
     tag = "user",
     responses(
-        (status = 200, description = "User authenticated", content_type = "text/plain", example = super::data_example::user_registered),
-        (status = 404, description = "User not found", content_type = "text/plain")
+        (status = 200, description = "User authenticated, or a `challenge_token` when the account uses 2FA", content_type = "text/plain", example = super::data_example::user_registered),
+        (status = 404, description = "User not found", content_type = "text/plain"),
+        (status = 429, description = "Too many failed attempts, see the `Retry-After` header", content_type = "text/plain")
     ),
-    description = "Authenticate a user and generate access & refresh tokens."
+    description = "Authenticate a user and generate access & refresh tokens. Accounts with 2FA get a short-lived `challenge_token` instead, to be completed at `/api/v1/login/2fa`. Repeated failures for an email or IP are throttled with an exponential backoff and end in a temporary lockout."
 )]
 pub async fn login(
     State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
     Json(body): Json<UserLogin>,
 ) -> Result<impl IntoResponse> {
-    let env = environment::Environment::new();
+    let user = match verify_credentials(&app_state, &client_info, &body).await {
+        Ok(user) => user,
+        Err(e) => {
+            AuditEntry::new(AuditEventType::Login, AuditOutcome::Failure)
+                .detail(format!("password login for {}: {}", body.email, e))
+                .record(&app_state, &client_info)
+                .await;
+            return Err(e);
+        }
+    };
+
+    // Failures are only cleared once the second factor is verified too
+    if user.totp_secret.is_some() || user.two_factor_required {
+        return two_factor_challenge(&app_state, &user).await;
+    }
+
+    clear_login_failures(&app_state.redis_client, &body.email).await?;
+
+    AuditEntry::new(AuditEventType::Login, AuditOutcome::Success)
+        .actor(&user.id)
+        .detail("password")
+        .record(&app_state, &client_info)
+        .await;
+
+    start_session(&app_state, &user.id, &user.role, &client_info).await
+}
+
+/// Checks an email and password, counting every failure towards the login throttle.
+async fn verify_credentials(
+    app_state: &AppState,
+    client_info: &ClientInfo,
+    body: &UserLogin,
+) -> Result<UserData> {
     let usvc = &app_state.user_service;
+    let redis_client = &app_state.redis_client;
+    let throttle = LoginThrottle::from_env(&Environment::new());
+    let ip = client_info.ip.as_deref();
+
+    // Checked before the Argon2 verification so throttled attempts stay cheap
+    check_login_allowed(redis_client, &body.email, ip).await?;
+
+    let user = match usvc.login(body.email.clone()).await {
+        Ok(user) => user,
+        Err(e @ DataNotAvailable(_)) => {
+            record_login_failure(redis_client, &throttle, &body.email, ip).await?;
+            return Err(e);
+        }
+        Err(e) => return Err(e),
+    };
 
-    let user = usvc.login(body.email).await?;
     let is_valid = PasswordHash::new(&user.password)
         .ok()
         .and_then(|parsed_hash| {
@@ -103,78 +283,9 @@ pub async fn login(
         .is_some();
 
     if !is_valid {
+        record_login_failure(redis_client, &throttle, &body.email, ip).await?;
         return Err(LoginFail);
     }
 
-    let user_id = user.id.to_string();
-
-    let access_token_details = generate_jwt_token(
-        user_id.clone(),
-        60,
-        env.access_token_private_key.to_owned(),
-        &user.role,
-    )
-    .await?;
-
-    let refresh_token_details = generate_jwt_token(
-        user_id,
-        60,
-        env.refresh_token_private_key.to_owned(),
-        &user.role,
-    )
-    .await?;
-
-    save_token_data_to_redis(&app_state.redis_client, &access_token_details, 60).await?;
-    save_token_data_to_redis(&app_state.redis_client, &refresh_token_details, 60).await?;
-
-    let access_token = access_token_details
-        .token
-        .clone()
-        .ok_or_else(|| TokenError("Error extracting access token".to_string()))?;
-
-    let refresh_token = refresh_token_details
-        .token
-        .ok_or_else(|| TokenError("Error extracting refresh token".to_string()))?;
-
-    let cookies = vec![
-        Cookie::build(("access_token", access_token.clone()))
-            .path("/")
-            .max_age(time::Duration::minutes(60 * 60))
-            .same_site(SameSite::Lax)
-            .http_only(true),
-        Cookie::build(("refresh_token", refresh_token.clone()))
-            .path("/")
-            .max_age(time::Duration::minutes(60 * 60))
-            .same_site(SameSite::Lax)
-            .http_only(true),
-        Cookie::build(("logged_in", "true"))
-            .path("/")
-            .max_age(time::Duration::minutes(60 * 60))
-            .same_site(SameSite::Lax)
-            .http_only(false),
-    ];
-
-    let mut response = Response::new(
-        json!({
-            "status": "success",
-            "data": {
-                "user_id": user.id.to_string(),
-                "user_role": &user.role,
-                "access_token": access_token
-            }
-        })
-        .to_string(),
-    );
-
-    let mut headers = HeaderMap::new();
-    for cookie in cookies {
-        headers.append(
-            header::SET_COOKIE,
-            HeaderValue::from_str(&cookie.to_string())
-                .map_err(|err| StringError(err.to_string()))?,
-        );
-    }
-
-    response.headers_mut().extend(headers);
-    Ok(response)
+    Ok(user)
 }
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use validator::Validate;
+
+use environment::Environment;
+use errors::{Error::TokenError, Result};
+use model::web::auth::auth_request::ResendVerification;
+use service::{
+    auth::{
+        login_throttle::{throttle_verification_request, LoginThrottle},
+        one_time_token::{consume_bound_one_time_token, TokenPurpose},
+        verification::send_verification_email,
+    },
+    user::user_service::UserServiceTrait,
+};
+use state::axum::AppState;
+
+use super::client_info::ClientInfo;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify/{token}",
+    tag = "user",
+    params(("token" = String, Path, description = "Verification token received by email")),
+    responses(
+        (status = 200, description = "Email verified", content_type = "text/plain"),
+        (status = 401, description = "Token invalid or expired", content_type = "text/plain")
+    ),
+    description = "Confirm the email address of a newly registered account."
+)]
+pub async fn verify_email(
+    State(app_state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse> {
+    let invalid = || TokenError("Verification link is invalid or has expired".to_string());
+    let (user_id, email) = consume_bound_one_time_token(
+        &app_state.redis_client,
+        TokenPurpose::EmailVerification,
+        &token,
+    )
+    .await?
+    .ok_or_else(invalid)?;
+
+    // A link sent to an address the account has since moved away from verifies nothing
+    let user = app_state.user_service.get_user_by_id(&user_id).await?;
+    if user.email != email {
+        return Err(invalid());
+    }
+
+    app_state.user_service.verify_profile(&user_id).await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/resend",
+    request_body = ResendVerification,
+    tag = "user",
+    responses(
+        (status = 200, description = "Verification email sent if the account needs one", content_type = "text/plain"),
+        (status = 429, description = "Too many links requested for the email or from the IP, see the `Retry-After` header", content_type = "text/plain")
+    ),
+    description = "Send a new verification link. The response does not reveal whether the email is registered. Requests are throttled per email and per IP like failed logins."
+)]
+pub async fn resend_verification(
+    State(app_state): State<Arc<AppState>>,
+    client_info: ClientInfo,
+    Json(body): Json<ResendVerification>,
+) -> Result<impl IntoResponse> {
+    body.validate()?;
+
+    throttle_verification_request(
+        &app_state.redis_client,
+        &LoginThrottle::from_env(&Environment::new()),
+        &body.email,
+        client_info.ip.as_deref(),
+    )
+    .await?;
+
+    if let Ok(user) = app_state.user_service.get_user_by_email(&body.email).await {
+        if !user.verified {
+            // Delivery problems must not change the response, otherwise they reveal the account
+            if let Err(e) = send_verification_email(
+                &app_state.redis_client,
+                &app_state.mailer,
+                &user.id,
+                &user.email,
+            )
+            .await
+            {
+                tracing::error!("Failed to send verification email: {}", e);
+            }
+        }
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {}
+    })))
+}
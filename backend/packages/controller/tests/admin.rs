@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        http::{header, Method, StatusCode},
+        middleware,
+        routing::{post, put},
+        Extension, Router,
+    };
+    use common::{app_state, create_user, delete_user, json_request, send, session, PASSWORD};
+    use controller::axum::{
+        admin::{
+            change_user_role, create_user as create_user_route, logout_user, require_two_factor,
+            set_user_disabled, verify_user,
+        },
+        jwt::{jwt_auth, JWTAuthMiddleware},
+    };
+    use errors::Result;
+    use model::{
+        authorization::{permission::Permission, role::UserRole, scope::ApiKeyScope},
+        web::api_key::api_key_request::ApiKey,
+    };
+    use serde_json::json;
+    use service::api_key::api_key_service::ApiKeyServiceTrait;
+    use state::axum::AppState;
+
+    use tokio::test;
+
+    mod common;
+
+    /// Routes managing other users, called as the given staff user.
+    fn manage_router(app_state: Arc<AppState>, jwt: JWTAuthMiddleware) -> Router {
+        Router::new()
+            .route("/api/v1/admin/users/:id/role", put(change_user_role))
+            .route("/api/v1/admin/users/:id/disabled", put(set_user_disabled))
+            .route("/api/v1/admin/users/:id/verify", post(verify_user))
+            .route("/api/v1/admin/users/:id/logout", post(logout_user))
+            .route(
+                "/api/v1/admin/users/:id/two-factor",
+                put(require_two_factor),
+            )
+            .layer(Extension(jwt))
+            .with_state(app_state)
+    }
+
+    fn key(id: &str) -> &str {
+        id.trim_start_matches("user:")
+    }
+
+    /// Status of every route managing the user, in the order role, disabled, verify, logout and
+    /// two-factor.
+    async fn manage_statuses(router: Router, user_id: &str) -> Vec<StatusCode> {
+        let requests = [
+            (Method::PUT, "role", json!({ "role": "seller" })),
+            (Method::PUT, "disabled", json!({ "disabled": true })),
+            (Method::POST, "verify", json!({})),
+            (Method::POST, "logout", json!({})),
+            (Method::PUT, "two-factor", json!({ "required": true })),
+        ];
+
+        let mut statuses = Vec::new();
+        for (method, action, body) in requests {
+            let uri = format!("/api/v1/admin/users/{}/{}", key(user_id), action);
+            let (status, _) = send(router.clone(), json_request(method, &uri, body)).await;
+            statuses.push(status);
+        }
+        statuses
+    }
+
+    #[test]
+    async fn test_admin_api_key_cannot_create_users() -> Result<()> {
+        let app_state = app_state().await?;
+        let admin_id = create_user(&app_state, "admin").await?;
+        let api_key = app_state
+            .api_key_service
+            .create_api_key(
+                &admin_id,
+                ApiKey {
+                    name: "integration".to_string(),
+                    scopes: vec![ApiKeyScope::ProfileRead],
+                },
+            )
+            .await?;
+        let router = Router::new()
+            .route(
+                "/api/v1/admin/users",
+                post(create_user_route)
+                    .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+            )
+            .with_state(app_state.clone());
+
+        let mut request = json_request(
+            Method::POST,
+            "/api/v1/admin/users",
+            json!({
+                "username": "apikeyadmin",
+                "email": "apikeyadmin@example.com",
+                "password": PASSWORD,
+                "role": "admin"
+            }),
+        );
+        request.headers_mut().insert(
+            header::AUTHORIZATION,
+            format!("ApiKey {}", api_key.key).parse().unwrap(),
+        );
+        let (status, _) = send(router, request).await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        delete_user(&app_state, &admin_id).await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_cannot_manage_own_account() -> Result<()> {
+        let app_state = app_state().await?;
+        let admin_id = create_user(&app_state, "customer").await?;
+        let router = manage_router(app_state.clone(), session(&admin_id, UserRole::Admin));
+
+        let statuses = manage_statuses(router, &admin_id).await;
+        let admin = app_state.user_service.get_user_by_id(&admin_id).await?;
+        delete_user(&app_state, &admin_id).await?;
+
+        assert_eq!(statuses, vec![StatusCode::FORBIDDEN; 5]);
+        assert!(!admin.two_factor_required);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_cannot_manage_roles_beyond_own_permissions() -> Result<()> {
+        let app_state = app_state().await?;
+        let support_id = create_user(&app_state, "customer").await?;
+        let customer_id = create_user(&app_state, "customer").await?;
+        let admin_id = create_user(&app_state, "admin").await?;
+        let support = JWTAuthMiddleware {
+            user_type: "support".to_string(),
+            permissions: vec![Permission::UsersRead, Permission::UsersManage],
+            ..session(&support_id, UserRole::Customer)
+        };
+        let router = manage_router(app_state.clone(), support);
+
+        // Promoting someone would grant permissions the caller does not have
+        let (status, _) = send(
+            router.clone(),
+            json_request(
+                Method::PUT,
+                &format!("/api/v1/admin/users/{}/role", key(&customer_id)),
+                json!({ "role": "admin" }),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // So would managing someone who already has them
+        let (status, _) = send(
+            router.clone(),
+            json_request(
+                Method::PUT,
+                &format!("/api/v1/admin/users/{}/role", key(&admin_id)),
+                json!({ "role": "customer" }),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Nor may they act on someone who has them in any other way
+        let statuses = manage_statuses(router, &admin_id).await;
+        assert_eq!(statuses, vec![StatusCode::FORBIDDEN; 5]);
+
+        let admin = app_state.user_service.get_user_by_id(&admin_id).await?;
+        assert_eq!(admin.role, "admin");
+        assert!(!admin.disabled);
+        assert!(!admin.two_factor_required);
+
+        for id in [support_id, customer_id, admin_id] {
+            delete_user(&app_state, &id).await?;
+        }
+        Ok(())
+    }
+}
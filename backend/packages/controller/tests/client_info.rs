@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, SocketAddr};
+
+    use axum::http::{header, HeaderMap};
+    use controller::axum::client_info::ClientInfo;
+
+    const PROXY: &str = "10.0.0.1";
+
+    fn headers(forwarded_for: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", forwarded_for.parse().unwrap());
+        headers.insert(header::USER_AGENT, "test-agent".parse().unwrap());
+        headers
+    }
+
+    fn peer(ip: &str) -> Option<SocketAddr> {
+        Some(SocketAddr::new(ip.parse().unwrap(), 40000))
+    }
+
+    fn proxies() -> Vec<IpAddr> {
+        vec![PROXY.parse().unwrap(), "10.0.0.2".parse().unwrap()]
+    }
+
+    #[test]
+    fn test_forwarded_for_is_ignored_from_untrusted_peers() {
+        let info =
+            ClientInfo::from_parts(&headers("198.51.100.7"), peer("203.0.113.9"), &proxies());
+        assert_eq!(info.ip.as_deref(), Some("203.0.113.9"));
+        assert_eq!(info.user_agent.as_deref(), Some("test-agent"));
+
+        let info = ClientInfo::from_parts(&headers("198.51.100.7"), peer(PROXY), &[]);
+        assert_eq!(info.ip.as_deref(), Some(PROXY));
+    }
+
+    #[test]
+    fn test_forwarded_for_is_read_from_trusted_proxies() {
+        // The client prepended a made up address, the proxies appended the real one
+        let info = ClientInfo::from_parts(
+            &headers("192.0.2.1, 198.51.100.7, 10.0.0.2"),
+            peer(PROXY),
+            &proxies(),
+        );
+        assert_eq!(info.ip.as_deref(), Some("198.51.100.7"));
+    }
+
+    #[test]
+    fn test_garbage_forwarded_for_falls_back_to_the_proxy_chain() {
+        let info = ClientInfo::from_parts(&headers("not-an-ip"), peer(PROXY), &proxies());
+        assert_eq!(info.ip.as_deref(), Some(PROXY));
+
+        let info = ClientInfo::from_parts(&HeaderMap::new(), None, &proxies());
+        assert_eq!(info.ip, None);
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{Method, StatusCode},
+        routing::put,
+        Extension, Router,
+    };
+    use common::{app_state, create_user, delete_user, json_request, send, session};
+    use controller::axum::admin::{change_user_role, set_user_disabled};
+    use database::{
+        interface::DBInterface as _,
+        query::{Columns, Filter},
+    };
+    use errors::Result;
+    use model::{authorization::role::UserRole, domain::user::User};
+    use serde_json::json;
+
+    use tokio::test;
+
+    mod common;
+
+    // Runs in a test binary of its own, it needs every other admin out of the way
+    #[test]
+    async fn test_last_admin_is_kept() -> Result<()> {
+        let app_state = app_state().await?;
+        let db = app_state.user_service.user_repo.db.clone();
+
+        let admins: Vec<User> = db
+            .select_where(
+                "user",
+                Filter::eq("role", "admin").and(Filter::ne("disabled", true)),
+                Columns::All,
+            )
+            .await?;
+        for admin in &admins {
+            db.update_record(&admin.id, "user", json!({ "disabled": true }))
+                .await?;
+        }
+
+        let admin_id = create_user(&app_state, "admin").await?;
+        let key = admin_id.trim_start_matches("user:");
+        let router = Router::new()
+            .route("/api/v1/admin/users/:id/role", put(change_user_role))
+            .route("/api/v1/admin/users/:id/disabled", put(set_user_disabled))
+            .layer(Extension(session("user:staff", UserRole::Admin)))
+            .with_state(app_state.clone());
+
+        let (role_status, _) = send(
+            router.clone(),
+            json_request(
+                Method::PUT,
+                &format!("/api/v1/admin/users/{}/role", key),
+                json!({ "role": "customer" }),
+            ),
+        )
+        .await;
+        let (disabled_status, _) = send(
+            router,
+            json_request(
+                Method::PUT,
+                &format!("/api/v1/admin/users/{}/disabled", key),
+                json!({ "disabled": true }),
+            ),
+        )
+        .await;
+        let admin = app_state.user_service.get_user_by_id(&admin_id).await?;
+
+        delete_user(&app_state, &admin_id).await?;
+        for admin in &admins {
+            db.update_record(&admin.id, "user", json!({ "disabled": false }))
+                .await?;
+        }
+
+        assert_eq!(role_status, StatusCode::FORBIDDEN);
+        assert_eq!(disabled_status, StatusCode::FORBIDDEN);
+        assert_eq!(admin.role, "admin");
+        assert!(!admin.disabled);
+        Ok(())
+    }
+}
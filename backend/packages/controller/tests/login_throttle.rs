@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use axum::{
+        extract::connect_info::MockConnectInfo,
+        http::{Method, StatusCode},
+        routing::post,
+        Router,
+    };
+    use common::{app_state, json_request, send};
+    use controller::axum::user::login;
+    use errors::Result;
+    use redis::AsyncCommands;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    mod common;
+
+    /// Peer address no other test uses, so its counters start at zero.
+    fn random_peer() -> SocketAddr {
+        let bytes = Uuid::new_v4().into_bytes();
+        SocketAddr::from(([100, bytes[0] | 64, bytes[1], bytes[2]], 40000))
+    }
+
+    #[test]
+    async fn test_forwarded_for_does_not_reset_the_ip_throttle() -> Result<()> {
+        let app_state = app_state().await?;
+        let peer = random_peer();
+        let router = Router::new()
+            .route("/api/v1/login", post(login))
+            .layer(MockConnectInfo(peer))
+            .with_state(app_state.clone());
+
+        // Every attempt claims another client address and targets an unknown account, only the
+        // untrusted peer address stays the same
+        let mut statuses = Vec::new();
+        for attempt in 0..3 {
+            let mut request = json_request(
+                Method::POST,
+                "/api/v1/login",
+                json!({
+                    "email": format!("{}@example.com", Uuid::new_v4().simple()),
+                    "password": "Wrong-password-1"
+                }),
+            );
+            request.headers_mut().insert(
+                "x-forwarded-for",
+                format!("198.51.100.{}", attempt).parse().unwrap(),
+            );
+            let (status, _) = send(router.clone(), request).await;
+            statuses.push(status);
+        }
+
+        let mut redis = app_state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+        let failures: i64 = redis
+            .get(format!("login_failures:ip:{}", peer.ip()))
+            .await?;
+        let forwarded_failures: Option<i64> = redis.get("login_failures:ip:198.51.100.0").await?;
+
+        assert_eq!(statuses[0], StatusCode::NOT_FOUND);
+        assert_eq!(statuses[1], StatusCode::NOT_FOUND);
+        // The second failure put the peer address into a backoff
+        assert_eq!(statuses[2], StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(failures, 2);
+        assert_eq!(forwarded_failures, None);
+        Ok(())
+    }
+}
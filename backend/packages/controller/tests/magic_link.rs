@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+    use axum::{
+        body::Body,
+        extract::connect_info::MockConnectInfo,
+        http::{Method, Request, StatusCode},
+        routing::{get, post},
+        Router,
+    };
+    use common::{app_state, create_user, delete_user, json_request, send};
+    use controller::axum::magic_link::{
+        magic_link_confirmation, magic_link_login, request_magic_link,
+    };
+    use errors::Result;
+    use http_body_util::BodyExt;
+    use redis::AsyncCommands;
+    use serde_json::json;
+    use service::auth::one_time_token::{save_one_time_token, TokenPurpose};
+    use state::axum::AppState;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    mod common;
+
+    /// Peer address no other test uses, so its counters start at zero.
+    fn random_peer() -> SocketAddr {
+        let bytes = Uuid::new_v4().into_bytes();
+        SocketAddr::from(([100, bytes[0] | 64, bytes[1], bytes[2]], 40000))
+    }
+
+    fn router(app_state: Arc<AppState>) -> Router {
+        Router::new()
+            .route("/api/v1/login/magic-link", post(request_magic_link))
+            .route(
+                "/api/v1/login/magic-link/:token",
+                get(magic_link_confirmation).post(magic_link_login),
+            )
+            .layer(MockConnectInfo(random_peer()))
+            .with_state(app_state)
+    }
+
+    fn request(method: Method, token: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(format!("/api/v1/login/magic-link/{}", token))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    /// Opens the link like a browser or a mail scanner, returning the status and the page.
+    async fn open(router: &Router, token: &str) -> (StatusCode, String) {
+        let response = router
+            .clone()
+            .oneshot(request(Method::GET, token))
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8_lossy(&body).to_string())
+    }
+
+    fn link_request(email: &str) -> Request<Body> {
+        json_request(
+            Method::POST,
+            "/api/v1/login/magic-link",
+            json!({ "email": email }),
+        )
+    }
+
+    #[test]
+    async fn test_link_is_confirmed_then_consumed_once() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let token = save_one_time_token(
+            &app_state.redis_client,
+            TokenPurpose::MagicLink,
+            &user_id,
+            15,
+        )
+        .await?;
+        let router = router(app_state.clone());
+
+        // Opening the link only shows the confirmation, however often it happens
+        let (status, page) = open(&router, &token).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(page.contains(r#"<form method="post">"#));
+        assert_eq!(open(&router, &token).await.0, StatusCode::OK);
+
+        let (status, body) = send(router.clone(), request(Method::POST, &token)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["access_token"].is_string());
+
+        let (status, _) = send(router.clone(), request(Method::POST, &token)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(open(&router, &token).await.0, StatusCode::NOT_FOUND);
+
+        delete_user(&app_state, &user_id).await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_expired_link_is_rejected() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let token = save_one_time_token(
+            &app_state.redis_client,
+            TokenPurpose::MagicLink,
+            &user_id,
+            15,
+        )
+        .await?;
+        let router = router(app_state.clone());
+
+        let mut redis = app_state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+        let _: () = redis.pexpire(format!("magic_link:{}", token), 1).await?;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(open(&router, &token).await.0, StatusCode::NOT_FOUND);
+        let (status, _) = send(router, request(Method::POST, &token)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        delete_user(&app_state, &user_id).await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unknown_email_gets_no_link() -> Result<()> {
+        let app_state = app_state().await?;
+        let router = router(app_state.clone());
+
+        let email = format!("{}@example.com", Uuid::new_v4().simple());
+        let (status, body) = send(router, link_request(&email)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "success");
+        assert!(app_state.mailer.sent_messages().await.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_link_requests_are_throttled_per_email() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let user = app_state
+            .user_service
+            .user_repo
+            .get_data_by_id(&user_id)
+            .await?;
+
+        // Every request comes from another address, only the email stays the same
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let (status, _) = send(router(app_state.clone()), link_request(&user.email)).await;
+            statuses.push(status);
+        }
+        let messages = app_state.mailer.sent_messages().await;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(statuses[..2], [StatusCode::OK, StatusCode::OK]);
+        assert_eq!(statuses[2], StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(messages.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_link_requests_are_throttled_per_ip() -> Result<()> {
+        let app_state = app_state().await?;
+        let router = router(app_state.clone());
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let email = format!("{}@example.com", Uuid::new_v4().simple());
+            let (status, _) = send(router.clone(), link_request(&email)).await;
+            statuses.push(status);
+        }
+
+        assert_eq!(statuses[..2], [StatusCode::OK, StatusCode::OK]);
+        assert_eq!(statuses[2], StatusCode::TOO_MANY_REQUESTS);
+        Ok(())
+    }
+}
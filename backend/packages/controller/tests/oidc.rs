@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use common::{app_state, create_user, delete_user, register_user, send};
+    use controller::axum::oidc::oidc_callback;
+    use errors::{Error::UserNotVerified, Result};
+    use model::authorization::oidc::OidcIdentity;
+    use service::{auth::oidc::state_binding, user::user_service::UserServiceTrait};
+
+    use tokio::test;
+
+    mod common;
+
+    fn identity(email: &str) -> OidcIdentity {
+        OidcIdentity {
+            issuer: "https://issuer.example.com".to_string(),
+            subject: email.to_string(),
+            email: Some(email.to_string()),
+            email_verified: true,
+            name: None,
+        }
+    }
+
+    #[test]
+    async fn test_identity_is_not_linked_to_unverified_account() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = register_user(&app_state, "customer").await?;
+        let user = app_state.user_service.get_user_by_id(&user_id).await?;
+
+        let result = app_state
+            .user_service
+            .login_with_identity(&identity(&user.email))
+            .await;
+        assert!(matches!(result, Err(UserNotVerified(_))));
+
+        let user = app_state.user_service.get_user_by_id(&user_id).await?;
+        assert!(!user.verified);
+        assert!(user.identities.is_empty());
+
+        delete_user(&app_state, &user_id).await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_identity_is_linked_to_verified_account() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let user = app_state.user_service.get_user_by_id(&user_id).await?;
+
+        let linked = app_state
+            .user_service
+            .login_with_identity(&identity(&user.email))
+            .await?;
+        assert_eq!(linked.id, user_id);
+        assert_eq!(linked.identities.len(), 1);
+
+        delete_user(&app_state, &user_id).await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_callback_requires_state_cookie() -> Result<()> {
+        let app_state = app_state().await?;
+        let router = Router::new()
+            .route("/api/v1/oidc/:provider/callback", get(oidc_callback))
+            .with_state(app_state);
+
+        for cookie in [None, Some(state_binding("state-2"))] {
+            let mut request = Request::builder()
+                .uri("/api/v1/oidc/mock/callback?state=state-1&code=code-1")
+                .body(Body::empty())
+                .unwrap();
+            if let Some(cookie) = cookie {
+                request.headers_mut().insert(
+                    header::COOKIE,
+                    format!("oidc_state={}", cookie).parse().unwrap(),
+                );
+            }
+
+            let (status, body) = send(router.clone(), request).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(body["error"], "OIDC login was not started in this browser");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        middleware,
+        routing::{get, post},
+        Extension, Router,
+    };
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use ciborium::Value as CborValue;
+    use common::{app_state, create_user, delete_user, json_request, send, session};
+    use controller::axum::{
+        jwt::jwt_auth,
+        passkey::{
+            finish_passkey_login, finish_passkey_registration, list_passkeys, start_passkey_login,
+            start_passkey_registration,
+        },
+    };
+    use database::interface::DBInterface as _;
+    use errors::Result;
+    use model::{
+        authorization::role::UserRole,
+        domain::passkey::Passkey as PasskeyData,
+        web::passkey::passkey_request::{PasskeyLogin, PasskeyRegistration},
+    };
+    use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+    use serde_json::{json, Value};
+    use service::passkey::passkey_service::PasskeyServiceTrait;
+    use sha2::{Digest, Sha256};
+    use state::axum::AppState;
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    mod common;
+
+    /* Relying party of the test application state */
+    const RP_ID: &str = "localhost";
+    const ORIGIN: &str = "http://localhost";
+
+    const FLAG_USER_PRESENT: u8 = 0x01;
+    const FLAG_USER_VERIFIED: u8 = 0x04;
+    const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+    /* A software authenticator holding a single ES256 passkey */
+    struct SoftwareAuthenticator {
+        key: SigningKey,
+        credential_id: Vec<u8>,
+        sign_count: u32,
+    }
+
+    impl SoftwareAuthenticator {
+        fn new() -> Self {
+            // Credential ids are looked up across every user, so each test gets its own
+            SoftwareAuthenticator {
+                key: SigningKey::from_slice(&[7; 32]).unwrap(),
+                credential_id: Uuid::new_v4().into_bytes().to_vec(),
+                sign_count: 0,
+            }
+        }
+
+        fn cose_key(&self) -> Vec<u8> {
+            let point = self.key.verifying_key().to_encoded_point(false);
+            let cose_key = CborValue::Map(vec![
+                (CborValue::from(1), CborValue::from(2)),
+                (CborValue::from(3), CborValue::from(-7)),
+                (CborValue::from(-1), CborValue::from(1)),
+                (
+                    CborValue::from(-2),
+                    CborValue::Bytes(point.x().unwrap().to_vec()),
+                ),
+                (
+                    CborValue::from(-3),
+                    CborValue::Bytes(point.y().unwrap().to_vec()),
+                ),
+            ]);
+            let mut bytes = Vec::new();
+            ciborium::into_writer(&cose_key, &mut bytes).unwrap();
+            bytes
+        }
+
+        fn authenticator_data(&self, attested: bool) -> Vec<u8> {
+            let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+            let mut flags = FLAG_USER_PRESENT | FLAG_USER_VERIFIED;
+            if attested {
+                flags |= FLAG_ATTESTED_CREDENTIAL;
+            }
+            data.push(flags);
+            data.extend(self.sign_count.to_be_bytes());
+            if attested {
+                data.extend([0; 16]);
+                data.extend((self.credential_id.len() as u16).to_be_bytes());
+                data.extend(&self.credential_id);
+                data.extend(self.cose_key());
+            }
+            data
+        }
+
+        fn client_data(ceremony: &str, challenge: &str) -> Vec<u8> {
+            json!({ "type": ceremony, "challenge": challenge, "origin": ORIGIN })
+                .to_string()
+                .into_bytes()
+        }
+
+        /// Answers `navigator.credentials.create()` with the options the server sent.
+        fn create(&self, options: &Value) -> PasskeyRegistration {
+            let challenge = options["challenge"].as_str().unwrap();
+            let attestation_object = CborValue::Map(vec![
+                (CborValue::from("fmt"), CborValue::from("none")),
+                (CborValue::from("attStmt"), CborValue::Map(Vec::new())),
+                (
+                    CborValue::from("authData"),
+                    CborValue::Bytes(self.authenticator_data(true)),
+                ),
+            ]);
+            let mut attestation_bytes = Vec::new();
+            ciborium::into_writer(&attestation_object, &mut attestation_bytes).unwrap();
+
+            PasskeyRegistration {
+                name: "laptop".to_string(),
+                client_data_json: URL_SAFE_NO_PAD
+                    .encode(Self::client_data("webauthn.create", challenge)),
+                attestation_object: URL_SAFE_NO_PAD.encode(attestation_bytes),
+            }
+        }
+
+        /// Answers `navigator.credentials.get()` with the options the server sent, bumping the
+        /// signature counter.
+        fn get(&mut self, options: &Value, user_id: &str) -> PasskeyLogin {
+            self.sign_count += 1;
+            let authenticator_data = self.authenticator_data(false);
+            let client_data =
+                Self::client_data("webauthn.get", options["challenge"].as_str().unwrap());
+
+            let mut signed = authenticator_data.clone();
+            signed.extend(Sha256::digest(&client_data));
+            let signature: Signature = self.key.sign(&signed);
+
+            PasskeyLogin {
+                credential_id: URL_SAFE_NO_PAD.encode(&self.credential_id),
+                client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                authenticator_data: URL_SAFE_NO_PAD.encode(authenticator_data),
+                signature: URL_SAFE_NO_PAD.encode(signature.to_der().as_bytes()),
+                user_handle: Some(URL_SAFE_NO_PAD.encode(user_id)),
+            }
+        }
+    }
+
+    /// Passkey routes as mounted by the application, with the owner's session on the
+    /// registration routes and access tokens checked on the listing.
+    fn router(app_state: Arc<AppState>, user_id: &str) -> Router {
+        let owner_routes = Router::new()
+            .route(
+                "/api/v1/user/passkeys/register/start",
+                post(start_passkey_registration),
+            )
+            .route(
+                "/api/v1/user/passkeys/register/finish",
+                post(finish_passkey_registration),
+            )
+            .layer(Extension(session(user_id, UserRole::Customer)));
+
+        Router::new()
+            .route(
+                "/api/v1/user/passkeys",
+                get(list_passkeys)
+                    .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+            )
+            .route("/api/v1/login/passkey/start", post(start_passkey_login))
+            .route("/api/v1/login/passkey/finish", post(finish_passkey_login))
+            .merge(owner_routes)
+            .with_state(app_state)
+    }
+
+    async fn options(router: &Router, uri: &str) -> Value {
+        let (status, body) = send(router.clone(), json_request(Method::POST, uri, json!({}))).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["public_key"].clone()
+    }
+
+    async fn register(
+        router: &Router,
+        authenticator: &SoftwareAuthenticator,
+    ) -> (StatusCode, PasskeyRegistration) {
+        let options = options(router, "/api/v1/user/passkeys/register/start").await;
+        let registration = authenticator.create(&options);
+        let (status, _) = send(
+            router.clone(),
+            json_request(
+                Method::POST,
+                "/api/v1/user/passkeys/register/finish",
+                json!(registration),
+            ),
+        )
+        .await;
+        (status, registration)
+    }
+
+    async fn finish_login(router: &Router, login: &PasskeyLogin) -> (StatusCode, Value) {
+        send(
+            router.clone(),
+            json_request(Method::POST, "/api/v1/login/passkey/finish", json!(login)),
+        )
+        .await
+    }
+
+    /// Passkey record stored for the authenticator, removed again so no test sees it later.
+    async fn take_passkey(
+        app_state: &AppState,
+        authenticator: &SoftwareAuthenticator,
+    ) -> Result<PasskeyData> {
+        let passkey = app_state
+            .passkey_service
+            .get_by_credential_id(&URL_SAFE_NO_PAD.encode(&authenticator.credential_id))
+            .await?;
+        app_state
+            .passkey_service
+            .passkey_repo
+            .db
+            .delete(&passkey.id)
+            .await?;
+        Ok(passkey)
+    }
+
+    #[test]
+    async fn test_passkey_registers_and_logs_in() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let router = router(app_state.clone(), &user_id);
+        let mut authenticator = SoftwareAuthenticator::new();
+
+        let (registered, registration) = register(&router, &authenticator).await;
+        // The challenge was taken out of Redis by the first attempt
+        let (replayed_registration, _) = send(
+            router.clone(),
+            json_request(
+                Method::POST,
+                "/api/v1/user/passkeys/register/finish",
+                json!(registration),
+            ),
+        )
+        .await;
+
+        let options_one = options(&router, "/api/v1/login/passkey/start").await;
+        let login = authenticator.get(&options_one, &user_id);
+        let (logged_in, body) = finish_login(&router, &login).await;
+        let (replayed_login, _) = finish_login(&router, &login).await;
+
+        let options_two = options(&router, "/api/v1/login/passkey/start").await;
+        let (second_login, _) =
+            finish_login(&router, &authenticator.get(&options_two, &user_id)).await;
+
+        // The issued access token works on routes behind the token check
+        let access_token = body["data"]["access_token"].as_str().unwrap_or_default();
+        let (listed, passkeys) = send(
+            router,
+            Request::builder()
+                .uri("/api/v1/user/passkeys")
+                .header(header::AUTHORIZATION, format!("Bearer {}", access_token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        let passkey = take_passkey(&app_state, &authenticator).await?;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(registered, StatusCode::OK);
+        assert_eq!(replayed_registration, StatusCode::UNAUTHORIZED);
+        assert_eq!(logged_in, StatusCode::OK);
+        assert_eq!(replayed_login, StatusCode::UNAUTHORIZED);
+        assert_eq!(second_login, StatusCode::OK);
+        assert_eq!(listed, StatusCode::OK);
+        let passkeys = passkeys["data"]["passkeys"].as_array().unwrap();
+        assert_eq!(passkeys.len(), 1);
+        assert!(!passkeys[0]["last_used_at"].is_null());
+        assert_eq!(passkey.sign_count, 2);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unverified_account_cannot_log_in() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let router = router(app_state.clone(), &user_id);
+        let mut authenticator = SoftwareAuthenticator::new();
+
+        let (registered, _) = register(&router, &authenticator).await;
+        // Like after an email change
+        app_state
+            .user_service
+            .user_repo
+            .db
+            .update_record(&user_id, "user", json!({ "verified": false }))
+            .await?;
+
+        let options = options(&router, "/api/v1/login/passkey/start").await;
+        let (status, body) = finish_login(&router, &authenticator.get(&options, &user_id)).await;
+        let passkey = take_passkey(&app_state, &authenticator).await?;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(registered, StatusCode::OK);
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+        assert!(body["data"]["access_token"].is_null());
+        assert_eq!(passkey.sign_count, 1);
+        Ok(())
+    }
+}
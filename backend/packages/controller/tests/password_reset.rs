@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, sync::Arc};
+
+    use axum::{
+        extract::connect_info::MockConnectInfo,
+        http::{Method, StatusCode},
+        routing::post,
+        Router,
+    };
+    use common::{app_state, create_user, delete_user, json_request, send};
+    use controller::axum::password::{forgot_password, reset_password};
+    use errors::Result;
+    use serde_json::json;
+    use service::auth::one_time_token::{save_one_time_token, TokenPurpose};
+    use state::axum::AppState;
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    mod common;
+
+    const NEW_PASSWORD: &str = "Battery-staple-77";
+
+    /// Peer address no other test uses, so its counters start at zero.
+    fn random_peer() -> SocketAddr {
+        let bytes = Uuid::new_v4().into_bytes();
+        SocketAddr::from(([100, bytes[0] | 64, bytes[1], bytes[2]], 40000))
+    }
+
+    fn router(app_state: Arc<AppState>) -> Router {
+        Router::new()
+            .route("/api/v1/password/forgot", post(forgot_password))
+            .route("/api/v1/password/reset", post(reset_password))
+            .layer(MockConnectInfo(random_peer()))
+            .with_state(app_state)
+    }
+
+    async fn reset_token(app_state: &AppState, user_id: &str) -> Result<String> {
+        save_one_time_token(
+            &app_state.redis_client,
+            TokenPurpose::PasswordReset,
+            user_id,
+            30,
+        )
+        .await
+    }
+
+    async fn reset(router: Router, token: &str, password: &str) -> StatusCode {
+        let (status, _) = send(
+            router,
+            json_request(
+                Method::POST,
+                "/api/v1/password/reset",
+                json!({ "token": token, "password": password }),
+            ),
+        )
+        .await;
+        status
+    }
+
+    #[test]
+    async fn test_token_sets_the_password_once() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let router = router(app_state.clone());
+        let token = reset_token(&app_state, &user_id).await?;
+
+        // A rejected password leaves the token usable
+        let rejected = reset(router.clone(), &token, "short").await;
+        let (first, second) = tokio::join!(
+            reset(router.clone(), &token, NEW_PASSWORD),
+            reset(router.clone(), &token, "Another-password-88"),
+        );
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(rejected, StatusCode::UNPROCESSABLE_ENTITY);
+        let mut statuses = [first, second];
+        statuses.sort();
+        assert_eq!(statuses, [StatusCode::OK, StatusCode::UNAUTHORIZED]);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_other_reset_tokens_are_revoked() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let router = router(app_state.clone());
+        let used = reset_token(&app_state, &user_id).await?;
+        let other = reset_token(&app_state, &user_id).await?;
+
+        let used_status = reset(router.clone(), &used, NEW_PASSWORD).await;
+        let other_status = reset(router, &other, "Another-password-88").await;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(used_status, StatusCode::OK);
+        assert_eq!(other_status, StatusCode::UNAUTHORIZED);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_reset_requests_are_throttled_per_email() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let email = app_state.user_service.get_user_by_id(&user_id).await?.email;
+
+        // Every request comes from another address, only the email stays the same
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let (status, _) = send(
+                router(app_state.clone()),
+                json_request(
+                    Method::POST,
+                    "/api/v1/password/forgot",
+                    json!({ "email": email }),
+                ),
+            )
+            .await;
+            statuses.push(status);
+        }
+        let messages = app_state.mailer.sent_messages().await;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(statuses[..2], [StatusCode::OK, StatusCode::OK]);
+        assert_eq!(statuses[2], StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(messages.len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+        routing::{get, put},
+        Extension, Router,
+    };
+    use common::{app_state, create_user, delete_user, json_request, send, session};
+    use controller::axum::{user::update_profile, verification::verify_email};
+    use errors::Result;
+    use model::authorization::role::UserRole;
+    use redis::AsyncCommands;
+    use serde_json::json;
+    use service::auth::one_time_token::{save_bound_one_time_token, TokenPurpose};
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    mod common;
+
+    #[test]
+    async fn test_email_change_requires_verification() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let router = Router::new()
+            .route("/api/v1/user", put(update_profile))
+            .layer(Extension(session(&user_id, UserRole::Customer)))
+            .with_state(app_state.clone());
+
+        // Other fields leave the verification alone
+        let (status, _) = send(
+            router.clone(),
+            json_request(
+                Method::PUT,
+                "/api/v1/user",
+                json!({ "full_name": "Jane Doe" }),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(
+            app_state
+                .user_service
+                .get_user_by_id(&user_id)
+                .await?
+                .verified
+        );
+        assert!(app_state.mailer.sent_messages().await.is_empty());
+
+        let email = format!("user{}@example.com", Uuid::new_v4().simple());
+        let (status, _) = send(
+            router,
+            json_request(Method::PUT, "/api/v1/user", json!({ "email": email })),
+        )
+        .await;
+        let user = app_state.user_service.get_user_by_id(&user_id).await?;
+        let messages = app_state.mailer.sent_messages().await;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(user.email, email);
+        assert!(!user.verified);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].0.to()[0].to_string(), email);
+        assert!(messages[0].1.contains("Verify your VirtuMart account"));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_old_verification_token_is_refused_after_email_change() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let old_email = app_state.user_service.get_user_by_id(&user_id).await?.email;
+        let router = Router::new()
+            .route("/api/v1/user", put(update_profile))
+            .route("/api/v1/verify/:token", get(verify_email))
+            .layer(Extension(session(&user_id, UserRole::Customer)))
+            .with_state(app_state.clone());
+        let verify = |token: &str| {
+            Request::builder()
+                .uri(format!("/api/v1/verify/{}", token))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // Collected while the account still had the old address
+        let token = |email: String| {
+            let app_state = app_state.clone();
+            let user_id = user_id.clone();
+            async move {
+                save_bound_one_time_token(
+                    &app_state.redis_client,
+                    TokenPurpose::EmailVerification,
+                    &user_id,
+                    &email,
+                    60,
+                )
+                .await
+            }
+        };
+        let revoked_token = token(old_email.clone()).await?;
+
+        let new_email = format!("user{}@example.com", Uuid::new_v4().simple());
+        let (status, _) = send(
+            router.clone(),
+            json_request(Method::PUT, "/api/v1/user", json!({ "email": new_email })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let mut redis = app_state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+        let revoked: Option<String> = redis.get(format!("verify_email:{}", revoked_token)).await?;
+        assert!(revoked.is_none());
+
+        let (revoked_status, _) = send(router.clone(), verify(&revoked_token)).await;
+        // Issued for the old address after the change, so only the binding stands in the way
+        let stale_token = token(old_email).await?;
+        let (stale_status, _) = send(router.clone(), verify(&stale_token)).await;
+        let unverified = app_state.user_service.get_user_by_id(&user_id).await?;
+
+        let fresh_token = token(new_email).await?;
+        let (fresh_status, _) = send(router, verify(&fresh_token)).await;
+        let verified = app_state.user_service.get_user_by_id(&user_id).await?;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(revoked_status, StatusCode::UNAUTHORIZED);
+        assert_eq!(stale_status, StatusCode::UNAUTHORIZED);
+        assert!(!unverified.verified);
+        assert_eq!(fresh_status, StatusCode::OK);
+        assert!(verified.verified);
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use common::{app_state, create_user, delete_user};
+    use errors::Result;
+    use service::{
+        auth::two_factor::{generate_recovery_codes, generate_secret, hash_recovery_code},
+        user::user_service::UserServiceTrait,
+    };
+
+    use tokio::test;
+
+    mod common;
+
+    #[test]
+    async fn test_recovery_code_is_consumed_once() -> Result<()> {
+        let app_state = app_state().await?;
+        let usvc = &app_state.user_service;
+        let user_id = create_user(&app_state, "customer").await?;
+        let (codes, hashes) = generate_recovery_codes();
+        usvc.enable_two_factor(&user_id, &generate_secret(), hashes)
+            .await?;
+
+        // Racing requests with the same code, only one of them gets in
+        let hash = hash_recovery_code(&codes[0]);
+        let (first, second) = tokio::join!(
+            usvc.consume_recovery_code(&user_id, &hash),
+            usvc.consume_recovery_code(&user_id, &hash)
+        );
+        assert!(first? ^ second?);
+
+        // Racing requests with different codes must not bring back each other's code
+        let (first_hash, second_hash) =
+            (hash_recovery_code(&codes[1]), hash_recovery_code(&codes[2]));
+        let (first, second) = tokio::join!(
+            usvc.consume_recovery_code(&user_id, &first_hash),
+            usvc.consume_recovery_code(&user_id, &second_hash)
+        );
+        assert!(first? && second?);
+
+        let user = usvc.get_user_by_id(&user_id).await?;
+        assert_eq!(user.recovery_codes.len(), codes.len() - 3);
+        assert!(!usvc.consume_recovery_code(&user_id, &hash).await?);
+
+        delete_user(&app_state, &user_id).await?;
+        Ok(())
+    }
+}
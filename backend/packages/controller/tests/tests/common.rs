@@ -0,0 +1,182 @@
+// Each test binary only uses some of these helpers
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use base64::{engine::general_purpose, Engine};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use controller::axum::jwt::JWTAuthMiddleware;
+use database::{
+    database::{DatabaseSource, DatabaseType, Sources},
+    interface::DBInterface as _,
+};
+use environment::Environment;
+use errors::Result;
+use model::{
+    authorization::{
+        jwk::{KeyUse, KeyringConfig, SigningKeyConfig},
+        oidc::OidcProvidersConfig,
+        role::UserRole,
+    },
+    web::user::user_request::User,
+};
+use repository::{
+    api_key::api_key_repository::ApiKeyRepository,
+    audit_event::audit_event_repository::AuditEventRepository,
+    passkey::passkey_repository::PasskeyRepository, role::role_repository::RoleRepository,
+    user::user_repository::UserRepository,
+};
+use service::{
+    api_key::api_key_service::ApiKeyService,
+    audit::audit_service::AuditService,
+    auth::{
+        keyring::Keyring, oidc::OidcClient, password_policy::PasswordPolicy, webauthn::Webauthn,
+    },
+    mail::mailer::{MailTransport, Mailer},
+    passkey::passkey_service::PasskeyService,
+    role::role_service::RoleService,
+    user::user_service::{UserService, UserServiceTrait},
+};
+use state::axum::AppState;
+
+pub(super) const PASSWORD: &str = "Correct-horse-42";
+
+const PRIVATE_KEY: &str = include_str!("../../../service/tests/keys/current.pem");
+const PUBLIC_KEY: &str = include_str!("../../../service/tests/keys/current.pub.pem");
+
+/// Signing keys for access and refresh tokens, made from the service test key pair.
+fn keyring_config() -> KeyringConfig {
+    let key = |kid: &str, key_use| SigningKeyConfig {
+        kid: kid.to_string(),
+        key_use,
+        private_key: Some(general_purpose::STANDARD.encode(PRIVATE_KEY)),
+        public_key: general_purpose::STANDARD.encode(PUBLIC_KEY),
+        retired_at: None,
+    };
+    KeyringConfig {
+        keys: vec![
+            key("access-1", KeyUse::Access),
+            key("refresh-1", KeyUse::Refresh),
+        ],
+    }
+}
+
+/// Builds the application state on the database and Redis of the environment, with an in-memory
+/// mailer, the test signing keys and no OIDC providers.
+pub(super) async fn app_state() -> Result<Arc<AppState>> {
+    let environment = Environment::new();
+
+    let mut source = DatabaseSource {
+        db_type: DatabaseType::from_env(&environment)?,
+    };
+    let conn = Arc::new(source.connect().await?);
+
+    let redis_url = if environment.redis_host == "none" {
+        "redis://127.0.0.1:6379".to_string()
+    } else {
+        format!(
+            "redis://{}:{}@{}:{}",
+            environment.redis_username,
+            environment.redis_password,
+            environment.redis_host,
+            environment.redis_port
+        )
+    };
+
+    Ok(Arc::new(AppState {
+        user_service: UserService {
+            user_repo: UserRepository { db: conn.clone() },
+            password_policy: PasswordPolicy::default(),
+        },
+        role_service: RoleService {
+            role_repo: RoleRepository { db: conn.clone() },
+        },
+        api_key_service: ApiKeyService {
+            api_key_repo: ApiKeyRepository { db: conn.clone() },
+        },
+        audit_service: AuditService {
+            audit_repo: AuditEventRepository { db: conn.clone() },
+        },
+        passkey_service: PasskeyService {
+            passkey_repo: PasskeyRepository { db: conn.clone() },
+        },
+        redis_client: redis::Client::open(redis_url)?,
+        mailer: Mailer {
+            from: "Test <noreply@example.com>".parse().unwrap(),
+            transport: MailTransport::Memory(lettre::transport::stub::AsyncStubTransport::new_ok()),
+        },
+        keyring: Keyring::new(keyring_config(), 0)?,
+        oidc_client: OidcClient::new(OidcProvidersConfig::default()),
+        webauthn: Webauthn::new("localhost", "Test", "http://localhost"),
+    }))
+}
+
+/// Registers a user with a unique username and email, returning its record id.
+pub(super) async fn register_user(app_state: &AppState, role: &str) -> Result<String> {
+    let name = format!("user{}", Uuid::new_v4().simple());
+    let user = app_state
+        .user_service
+        .register_profile(User {
+            username: name.clone(),
+            email: format!("{}@example.com", name),
+            password: PASSWORD.to_string(),
+            role: role.to_string(),
+        })
+        .await?;
+
+    Ok(format!("user:{}", user.id))
+}
+
+/// Registers a user like `register_user` and verifies its email.
+pub(super) async fn create_user(app_state: &AppState, role: &str) -> Result<String> {
+    let id = register_user(app_state, role).await?;
+    app_state.user_service.verify_profile(&id).await?;
+    Ok(id)
+}
+
+pub(super) async fn delete_user(app_state: &AppState, id: &str) -> Result<()> {
+    app_state.user_service.user_repo.db.delete(id).await?;
+    Ok(())
+}
+
+/// Request context of a login session the user started themselves.
+pub(super) fn session(user_id: &str, role: UserRole) -> JWTAuthMiddleware {
+    JWTAuthMiddleware {
+        entity_id: user_id.to_string(),
+        access_token_uuid: Uuid::new_v4(),
+        user_type: role.as_str().to_string(),
+        user_id: user_id.to_string(),
+        session_id: None,
+        permissions: role.permissions(),
+        api_key_id: None,
+        scopes: None,
+        impersonator: None,
+    }
+}
+
+/// Builds a request with a JSON body.
+pub(super) fn json_request(method: Method, uri: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Sends a request through the router, returning the status and the JSON body.
+pub(super) async fn send(router: Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        middleware,
+        routing::get,
+        Router,
+    };
+    use common::{app_state, create_user, delete_user, send};
+    use controller::axum::jwt::jwt_auth;
+    use database::interface::DBInterface as _;
+    use errors::Result;
+    use model::{authorization::jwk::KeyUse, domain::audit_event::AuditEvent};
+    use service::auth::jwt::generate_jwt_token;
+    use state::axum::AppState;
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    mod common;
+
+    /// A protected route whose path is unique to the test, to find the audit rows it causes.
+    fn protected_router(app_state: Arc<AppState>) -> (Router, String) {
+        let path = format!("/api/v1/probe/{}", Uuid::new_v4().simple());
+        let router = Router::new()
+            .route(
+                &path,
+                get(|| async { "ok" })
+                    .layer(middleware::from_fn_with_state(app_state.clone(), jwt_auth)),
+            )
+            .with_state(app_state);
+        (router, path)
+    }
+
+    fn bearer_request(path: &str, token: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn rejections(app_state: &AppState, path: &str) -> Result<Vec<AuditEvent>> {
+        let events: Vec<AuditEvent> = app_state
+            .audit_service
+            .audit_repo
+            .db
+            .select("audit_event")
+            .await?;
+        Ok(events
+            .into_iter()
+            .filter(|event| {
+                event
+                    .detail
+                    .as_deref()
+                    .is_some_and(|detail| detail.contains(path))
+            })
+            .collect())
+    }
+
+    #[test]
+    async fn test_unrecognised_tokens_are_not_audited() -> Result<()> {
+        let app_state = app_state().await?;
+        let (router, path) = protected_router(app_state.clone());
+
+        for _ in 0..3 {
+            let (status, _) = send(router.clone(), bearer_request(&path, "not-a-token")).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        let request = Request::builder().uri(&path).body(Body::empty()).unwrap();
+        let (status, _) = send(router, request).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        assert!(rejections(&app_state, &path).await?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_rejected_genuine_token_is_audited() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = create_user(&app_state, "customer").await?;
+        let (router, path) = protected_router(app_state.clone());
+
+        // Signed by us but never saved to Redis, like a token whose session was revoked
+        let token = generate_jwt_token(
+            user_id.clone(),
+            15,
+            app_state.keyring.signing_key(KeyUse::Access)?,
+            "customer",
+        )
+        .await?;
+        let (status, _) = send(router, bearer_request(&path, &token.token.unwrap())).await;
+        let events = rejections(&app_state, &path).await?;
+
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].target.as_deref(), Some(user_id.as_str()));
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, sync::Arc};
+
+    use axum::{
+        extract::connect_info::MockConnectInfo,
+        http::{Method, StatusCode},
+        routing::post,
+        Router,
+    };
+    use common::{app_state, delete_user, json_request, register_user, send};
+    use controller::axum::verification::resend_verification;
+    use errors::Result;
+    use serde_json::json;
+    use service::mail::mailer::{MailTransport, Mailer};
+    use state::axum::AppState;
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    mod common;
+
+    /// Peer address no other test uses, so its counters start at zero.
+    fn random_peer() -> SocketAddr {
+        let bytes = Uuid::new_v4().into_bytes();
+        SocketAddr::from(([100, bytes[0] | 64, bytes[1], bytes[2]], 40000))
+    }
+
+    fn router(app_state: Arc<AppState>) -> Router {
+        Router::new()
+            .route("/api/v1/verify/resend", post(resend_verification))
+            .layer(MockConnectInfo(random_peer()))
+            .with_state(app_state)
+    }
+
+    async fn resend(router: Router, email: &str) -> StatusCode {
+        let (status, _) = send(
+            router,
+            json_request(
+                Method::POST,
+                "/api/v1/verify/resend",
+                json!({ "email": email }),
+            ),
+        )
+        .await;
+        status
+    }
+
+    #[test]
+    async fn test_failed_delivery_does_not_reveal_the_account() -> Result<()> {
+        let app_state = app_state().await?;
+        let user_id = register_user(&app_state, "customer").await?;
+        let email = app_state.user_service.get_user_by_id(&user_id).await?.email;
+        let failing = Arc::new(AppState {
+            mailer: Mailer {
+                from: app_state.mailer.from.clone(),
+                transport: MailTransport::Memory(
+                    lettre::transport::stub::AsyncStubTransport::new_error(),
+                ),
+            },
+            ..(*app_state).clone()
+        });
+
+        let registered = resend(router(failing.clone()), &email).await;
+        let unknown = resend(
+            router(failing),
+            &format!("{}@example.com", Uuid::new_v4().simple()),
+        )
+        .await;
+        delete_user(&app_state, &user_id).await?;
+
+        assert_eq!(registered, StatusCode::OK);
+        assert_eq!(unknown, StatusCode::OK);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_resend_requests_are_throttled_per_ip() -> Result<()> {
+        let app_state = app_state().await?;
+        let router = router(app_state);
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let email = format!("{}@example.com", Uuid::new_v4().simple());
+            statuses.push(resend(router.clone(), &email).await);
+        }
+
+        assert_eq!(statuses[..2], [StatusCode::OK, StatusCode::OK]);
+        assert_eq!(statuses[2], StatusCode::TOO_MANY_REQUESTS);
+        Ok(())
+    }
+}
@@ -2,26 +2,73 @@
 use async_trait::async_trait;
 
 use environment::Environment;
-use errors::Result;
+use errors::{
+    Error,
+    Error::{DataExist, DatabaseErrorExecution, UnsupportedEngine},
+    Result,
+};
 
 use surrealdb::{
-    engine::remote::ws::{Client, Ws},
+    engine::any::{self, Any},
     opt::auth::Root,
     Surreal,
 };
+use tokio::sync::{oneshot, OnceCell};
+
+#[cfg(feature = "sqlite")]
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Debug)]
 pub struct SurrealDb {
-    pub client: Option<Surreal<Client>>,
+    pub client: Option<Surreal<Any>>,
+}
+
+/* SQLite database file, every table holds records as JSON documents */
+#[cfg(feature = "sqlite")]
+#[derive(Clone, Debug)]
+pub struct SqliteDb {
+    pub connection: Arc<Mutex<rusqlite::Connection>>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum DatabaseType {
+    /// Remote SurrealDB server over WebSocket
     SurrealDB,
+    /// Embedded SurrealDB kept in memory, shared by the whole process and lost when it exits
+    SurrealMemory,
+    /// Embedded SurrealDB stored in the SurrealKV files of a directory
+    SurrealKv(String),
+    /// SQLite database file, `:memory:` for one that is lost on exit
+    #[cfg(feature = "sqlite")]
+    Sqlite(String),
+}
+
+impl DatabaseType {
+    /// Reads the engine from `DB_ENGINE`, one of `surrealdb`, `memory`, `surrealkv` or `sqlite`.
+    pub fn from_env(env: &Environment) -> Result<Self> {
+        match env.db_engine.as_str() {
+            "surrealdb" => Ok(DatabaseType::SurrealDB),
+            "memory" => Ok(DatabaseType::SurrealMemory),
+            "surrealkv" => Ok(DatabaseType::SurrealKv(env.db_path.clone())),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(DatabaseType::Sqlite(env.db_path.clone())),
+            #[cfg(not(feature = "sqlite"))]
+            "sqlite" => Err(UnsupportedEngine(
+                "The sqlite database engine needs the sqlite feature".to_string(),
+            )),
+            other => Err(UnsupportedEngine(format!(
+                "Unsupported database engine '{}'",
+                other
+            ))),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum DatabaseClient {
     Surreal(SurrealDb),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteDb),
     // Add other database clients here, e.g., Postgres(PostgresDb)
 }
 
@@ -48,26 +95,142 @@ pub trait Sources {
     async fn connect(&mut self) -> Result<DatabaseClient>;
 }
 
-#[async_trait]
-impl Initializable for SurrealDb {
-    async fn init(&self) -> Result<DatabaseClient> {
+/* Fields whose values must be unique within their table. Each one gets an index named
+`unique_{table}_{field}` from the engine, which rejects a duplicate even when two writes race
+past the checks done before them. */
+pub const UNIQUE_FIELDS: &[(&str, &str)] = &[("user", "email"), ("user", "username")];
+
+pub(crate) fn unique_index_name(tb_name: &str, field: &str) -> String {
+    format!("unique_{}_{}", tb_name, field)
+}
+
+/// Turns an engine error about a unique index into the `DataExist` of its field.
+pub(crate) fn unique_violation(message: &str) -> Option<Error> {
+    UNIQUE_FIELDS
+        .iter()
+        .find(|(tb_name, field)| message.contains(&unique_index_name(tb_name, field)))
+        .map(|(_, field)| {
+            let mut chars = field.chars();
+            let field = chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default();
+            DataExist(format!("{} already exists", field))
+        })
+}
+
+/* Tables whose records are never updated or deleted once written. The engine refuses both, so
+the log cannot be rewritten through any code path that reaches the database. */
+pub const APPEND_ONLY_TABLES: &[&str] = &["audit_event"];
+
+pub(crate) fn append_only_message(tb_name: &str) -> String {
+    format!("{} is append-only", tb_name)
+}
+
+/* The in-memory database of the process, see `SurrealDb::memory` */
+static MEMORY: OnceCell<Surreal<Any>> = OnceCell::const_new();
+
+impl SurrealDb {
+    /// Opens a client to the database of the given type, using the namespace and database of the
+    /// environment. Only the remote server needs signing in.
+    pub async fn open(db_type: &DatabaseType) -> Result<Surreal<Any>> {
         let env = Environment::new();
-        let hostname = format!("{}:{}", env.db_host, env.db_port);
-        let temp_client = Surreal::new::<Ws>(hostname).await?;
 
-        temp_client
-            .signin(Root {
-                username: &env.db_user,
-                password: &env.db_pass,
+        let client = match db_type {
+            DatabaseType::SurrealDB => {
+                let client = any::connect(format!("ws://{}:{}", env.db_host, env.db_port)).await?;
+                client
+                    .signin(Root {
+                        username: &env.db_user,
+                        password: &env.db_pass,
+                    })
+                    .await?;
+                client
+            }
+            DatabaseType::SurrealMemory => Self::memory().await?,
+            DatabaseType::SurrealKv(path) => any::connect(format!("surrealkv://{}", path)).await?,
+            #[cfg(feature = "sqlite")]
+            DatabaseType::Sqlite(_) => {
+                return Err(UnsupportedEngine(
+                    "SQLite is not a SurrealDB engine".to_string(),
+                ))
+            }
+        };
+
+        client.use_ns(env.db_namespace).use_db(env.db_name).await?;
+        Self::define_schema(&client).await?;
+        Ok(client)
+    }
+
+    /// Defines the unique indexes of `UNIQUE_FIELDS` and guards the `APPEND_ONLY_TABLES`,
+    /// leaving the definitions already there alone.
+    ///
+    /// Permissions do not bind the root user the backend signs in as, so an event aborts the
+    /// updates and deletes of append-only tables as well.
+    async fn define_schema(client: &Surreal<Any>) -> Result<()> {
+        let indexes = UNIQUE_FIELDS.iter().map(|(tb_name, field)| {
+            format!(
+                "DEFINE INDEX IF NOT EXISTS {} ON TABLE {} FIELDS {} UNIQUE;",
+                unique_index_name(tb_name, field),
+                tb_name,
+                field
+            )
+        });
+        let append_only = APPEND_ONLY_TABLES.iter().map(|tb_name| {
+            format!(
+                "DEFINE TABLE IF NOT EXISTS {tb_name} SCHEMALESS \
+                 PERMISSIONS FOR select, create FULL, FOR update, delete NONE; \
+                 DEFINE EVENT IF NOT EXISTS append_only ON TABLE {tb_name} \
+                 WHEN $event = 'UPDATE' OR $event = 'DELETE' THEN {{ THROW '{}' }};",
+                append_only_message(tb_name)
+            )
+        });
+        let statements = indexes.chain(append_only).collect::<String>();
+
+        client.query(statements).await?.check()?;
+        Ok(())
+    }
+
+    /// Every connection to the in-memory engine would start an empty database, so the process
+    /// keeps one. It runs on a runtime of its own because an embedded engine stops with the
+    /// runtime that started it, which for tests is the runtime of a single test.
+    async fn memory() -> Result<Surreal<Any>> {
+        let client = MEMORY
+            .get_or_try_init(|| async {
+                let (sender, receiver) = oneshot::channel();
+                std::thread::spawn(move || {
+                    let runtime = match tokio::runtime::Runtime::new() {
+                        Ok(runtime) => runtime,
+                        Err(e) => {
+                            let _ = sender.send(Err(DatabaseErrorExecution(e.to_string())));
+                            return;
+                        }
+                    };
+                    runtime.block_on(async move {
+                        let client = any::connect("mem://").await.map_err(Into::into);
+                        if sender.send(client).is_ok() {
+                            std::future::pending::<()>().await;
+                        }
+                    });
+                });
+
+                receiver.await.map_err(|_| {
+                    DatabaseErrorExecution(
+                        "surrealdb: in-memory engine failed to start".to_string(),
+                    )
+                })?
             })
             .await?;
 
-        temp_client
-            .use_ns(env.db_namespace)
-            .use_db(env.db_name)
-            .await?;
+        Ok(client.clone())
+    }
+}
 
-        let client = Some(temp_client);
+#[async_trait]
+impl Initializable for SurrealDb {
+    async fn init(&self) -> Result<DatabaseClient> {
+        let db_type = DatabaseType::from_env(&Environment::new())?;
+        let client = Some(Self::open(&db_type).await?);
         Ok(DatabaseClient::Surreal(SurrealDb { client }))
     }
 }
@@ -87,6 +250,8 @@ impl Connection for DatabaseClient {
     fn ping(&self) -> String {
         match self {
             DatabaseClient::Surreal(surrealdb) => surrealdb.ping(),
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(_) => String::from("Pong!"),
             // Add other database client pings here
         }
     }
@@ -97,10 +262,13 @@ impl Connection for DatabaseClient {
 impl Sources for DatabaseSource {
     async fn connect(&mut self) -> Result<DatabaseClient> {
         match &self.db_type {
-            DatabaseType::SurrealDB => {
-                let surrealdb = SurrealDb { client: None };
-                surrealdb.init().await
-            } // Add other database types here
+            DatabaseType::SurrealDB | DatabaseType::SurrealMemory | DatabaseType::SurrealKv(_) => {
+                let client = Some(SurrealDb::open(&self.db_type).await?);
+                Ok(DatabaseClient::Surreal(SurrealDb { client }))
+            }
+            #[cfg(feature = "sqlite")]
+            DatabaseType::Sqlite(path) => Ok(DatabaseClient::Sqlite(SqliteDb::open(path)?)),
+            // Add other database types here
         }
     }
 }
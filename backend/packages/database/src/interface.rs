@@ -1,8 +1,13 @@
 use async_trait::async_trait;
 use errors::Result;
+use model::domain::page::{Page, PageRequest};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::database::DatabaseClient;
+use crate::{
+    database::DatabaseClient,
+    query::{Columns, Filter, Query},
+    transaction::Transaction,
+};
 
 /* Trait for database interface operations */
 #[async_trait]
@@ -31,13 +36,28 @@ pub trait DBInterface {
         data: T,
     ) -> Result<bool>;
 
-    /* Method to select records from the database */
+    /* Method to select the records matching a query from the database */
     async fn select_where<T: DeserializeOwned + Sync>(
         &self,
         tb_name: &str,
-        filter: &str,
-        columns: &str, // separate columns by ',' in string format
+        query: impl Into<Query> + Send,
+        columns: Columns,
     ) -> Result<Vec<T>>;
+
+    /* Method to select one page of the records matching a query, ordered by its sort fields.
+    The page replaces the limit and start of the query. */
+    async fn select_page<T: Serialize + DeserializeOwned + Sync + Send>(
+        &self,
+        tb_name: &str,
+        query: impl Into<Query> + Send,
+        page: &PageRequest,
+    ) -> Result<Page<T>>;
+
+    /* Method to count the records matching a filter */
+    async fn count(&self, tb_name: &str, filter: &Filter) -> Result<u64>;
+
+    /* Method to apply the operations of a transaction at once, none of them when one fails */
+    async fn commit(&self, transaction: Transaction) -> Result<()>;
 }
 
 /* Implementation of the DBInterface trait for DatabaseClient */
@@ -54,6 +74,8 @@ impl DBInterface for DatabaseClient {
     ) -> Result<Option<U>> {
         match self {
             DatabaseClient::Surreal(surrealdb) => surrealdb.insert_record(tb_name, data).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.insert_record(tb_name, data).await,
             // Add other database client implementations here
         }
     }
@@ -62,6 +84,8 @@ impl DBInterface for DatabaseClient {
     async fn select<T: DeserializeOwned + Sync>(&self, tb_name: &str) -> Result<Vec<T>> {
         match self {
             DatabaseClient::Surreal(surrealdb) => surrealdb.select(tb_name).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.select(tb_name).await,
             // Add other database client implementations here
         }
     }
@@ -70,6 +94,8 @@ impl DBInterface for DatabaseClient {
     async fn delete(&self, id: &str) -> Result<bool> {
         match self {
             DatabaseClient::Surreal(surrealdb) => surrealdb.delete(id).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.delete(id).await,
             // Add other database client implementations here
         }
     }
@@ -82,19 +108,52 @@ impl DBInterface for DatabaseClient {
     ) -> Result<bool> {
         match self {
             DatabaseClient::Surreal(surrealdb) => surrealdb.update_record(id, tb_name, data).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.update_record(id, tb_name, data).await,
         }
     }
 
     async fn select_where<T: DeserializeOwned + Sync>(
         &self,
         tb_name: &str,
-        filter: &str,
-        columns: &str, // separate columns by ',' in string format
+        query: impl Into<Query> + Send,
+        columns: Columns,
     ) -> Result<Vec<T>> {
         match self {
             DatabaseClient::Surreal(surrealdb) => {
-                surrealdb.select_where(tb_name, filter, columns).await
+                surrealdb.select_where(tb_name, query, columns).await
             }
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.select_where(tb_name, query, columns).await,
+        }
+    }
+
+    async fn select_page<T: Serialize + DeserializeOwned + Sync + Send>(
+        &self,
+        tb_name: &str,
+        query: impl Into<Query> + Send,
+        page: &PageRequest,
+    ) -> Result<Page<T>> {
+        match self {
+            DatabaseClient::Surreal(surrealdb) => surrealdb.select_page(tb_name, query, page).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.select_page(tb_name, query, page).await,
+        }
+    }
+
+    async fn count(&self, tb_name: &str, filter: &Filter) -> Result<u64> {
+        match self {
+            DatabaseClient::Surreal(surrealdb) => surrealdb.count(tb_name, filter).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.count(tb_name, filter).await,
+        }
+    }
+
+    async fn commit(&self, transaction: Transaction) -> Result<()> {
+        match self {
+            DatabaseClient::Surreal(surrealdb) => surrealdb.commit(transaction).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseClient::Sqlite(sqlite) => sqlite.commit(transaction).await,
         }
     }
 }
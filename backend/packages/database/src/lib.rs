@@ -1,3 +1,8 @@
 pub mod database;
 pub mod interface;
+pub mod page;
+pub mod query;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod surrealdb;
+pub mod transaction;
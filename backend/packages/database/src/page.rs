@@ -0,0 +1,152 @@
+/* Keyset pagination. A cursor holds the sort values of the last record of a page, the next page
+starts right after them, so records added meanwhile never shift or repeat results. */
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value as JsonValue;
+
+use errors::{
+    Error::{DatabaseErrorExecution, DecodeError},
+    Result,
+};
+use model::domain::page::{Page, PageRequest, PageStart};
+
+use crate::{
+    interface::DBInterface,
+    query::{Columns, Direction, Filter, Query, Value},
+};
+
+/* Order of a paged query, always ending with `id` so that no two records tie */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyset {
+    order_by: Vec<(&'static str, Direction)>,
+}
+
+impl Keyset {
+    pub fn new(order_by: &[(&'static str, Direction)]) -> Self {
+        let mut order_by = order_by.to_vec();
+        if !order_by.iter().any(|(field, _)| *field == "id") {
+            let direction = order_by
+                .last()
+                .map(|(_, direction)| *direction)
+                .unwrap_or(Direction::Asc);
+            order_by.push(("id", direction));
+        }
+        Keyset { order_by }
+    }
+
+    pub fn order_by(&self) -> &[(&'static str, Direction)] {
+        &self.order_by
+    }
+
+    /// Cursor pointing right after a record, read from the sort fields of its stored form.
+    pub fn cursor(&self, record: &impl Serialize) -> Result<String> {
+        let record = serde_json::to_value(record)
+            .map_err(|e| DatabaseErrorExecution(format!("page cursor: {}", e)))?;
+
+        let values = self
+            .order_by
+            .iter()
+            .map(|(field, _)| {
+                record.get(field).cloned().ok_or_else(|| {
+                    DatabaseErrorExecution(format!("page cursor: record has no field {}", field))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(URL_SAFE_NO_PAD.encode(JsonValue::Array(values).to_string()))
+    }
+
+    /// Condition matching the records after a cursor.
+    ///
+    /// For an order `a, b, id` that is `a > $a OR (a = $a AND b > $b) OR (a = $a AND b = $b AND
+    /// id > $id)`, with `<` for descending fields.
+    pub fn after(&self, cursor: &str) -> Result<Filter> {
+        let invalid = || DecodeError("Invalid page cursor".to_string());
+
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let values = match serde_json::from_slice::<JsonValue>(&bytes) {
+            Ok(JsonValue::Array(values)) if values.len() == self.order_by.len() => values,
+            _ => return Err(invalid()),
+        };
+
+        let values = self
+            .order_by
+            .iter()
+            .zip(values)
+            .map(|((field, direction), value)| {
+                let value = match (*field, value) {
+                    // Ids are stored as record links, cursors carry them in `table:key` form
+                    ("id", JsonValue::String(id)) => Value::record(id),
+                    ("id", _) => return Err(invalid()),
+                    // Timestamps compare as datetimes, whether stored as strings or datetimes
+                    (_, JsonValue::String(text)) => match text.parse::<DateTime<Utc>>() {
+                        Ok(datetime) => Value::DateTime(datetime),
+                        Err(_) => Value::String(text),
+                    },
+                    (_, value) => Value::Json(value),
+                };
+                Ok((*field, *direction, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut filter = Filter::Or(Vec::new());
+        for (position, (field, direction, value)) in values.iter().enumerate() {
+            let ties = values[..position]
+                .iter()
+                .fold(Filter::All, |ties, (field, _, value)| {
+                    ties.and(Filter::eq(field, value.clone()))
+                });
+            let beyond = match direction {
+                Direction::Asc => Filter::gt(field, value.clone()),
+                Direction::Desc => Filter::lt(field, value.clone()),
+            };
+            filter = filter.or(ties.and(beyond));
+        }
+        Ok(filter)
+    }
+}
+
+/// Selects one page of a query through `select_where` and `count`, the same for every backend.
+pub(crate) async fn select_page<D, T>(
+    db: &D,
+    tb_name: &str,
+    query: Query,
+    page: &PageRequest,
+) -> Result<Page<T>>
+where
+    D: DBInterface + Sync,
+    T: Serialize + DeserializeOwned + Sync + Send,
+{
+    let total = db.count(tb_name, &query.filter).await?;
+
+    let keyset = Keyset::new(&query.order_by);
+    let mut page_query = Query {
+        order_by: keyset.order_by().to_vec(),
+        // One record more than asked tells whether there is a next page
+        limit: Some(page.limit.saturating_add(1)),
+        start: None,
+        ..query
+    };
+    match &page.start {
+        PageStart::Offset(offset) => page_query.start = Some(*offset),
+        PageStart::After(cursor) => {
+            page_query.filter = page_query.filter.and(keyset.after(cursor)?);
+        }
+    }
+
+    let mut items: Vec<T> = db.select_where(tb_name, page_query, Columns::All).await?;
+
+    let next_cursor = if items.len() > page.limit as usize {
+        items.truncate(page.limit as usize);
+        items.last().map(|last| keyset.cursor(last)).transpose()?
+    } else {
+        None
+    };
+
+    Ok(Page {
+        items,
+        total,
+        next_cursor,
+    })
+}
@@ -0,0 +1,230 @@
+/* Typed queries for `DBInterface::select_where`. Field names are `&'static str` so they can only
+come from code, values are always sent as bound parameters and never spliced into the query. */
+use chrono::{DateTime, Utc};
+
+/* A value compared against a field */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    /// Compared as a point in time rather than as text
+    DateTime(DateTime<Utc>),
+    /// Record id in `table:key` form
+    Record(String),
+    /// Nested data, e.g. an object inside an array field
+    Json(serde_json::Value),
+}
+
+impl Value {
+    /// A record id such as `user:user_123`.
+    pub fn record(id: impl Into<String>) -> Self {
+        Value::Record(id.into())
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&String> for Value {
+    fn from(value: &String) -> Self {
+        Value::String(value.clone())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::Int(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(value: DateTime<Utc>) -> Self {
+        Value::DateTime(value)
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        Value::Json(value)
+    }
+}
+
+/* Condition records have to match */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Matches every record
+    All,
+    Eq(&'static str, Value),
+    Ne(&'static str, Value),
+    Gt(&'static str, Value),
+    Lt(&'static str, Value),
+    /// Matches when the field equals one of the values, never when there are none
+    In(&'static str, Vec<Value>),
+    /// Inclusive bounds, a missing bound is open
+    Range {
+        field: &'static str,
+        min: Option<Value>,
+        max: Option<Value>,
+    },
+    /// Array field holding the value, or string field holding the substring
+    Contains(&'static str, Value),
+    /// String field holding the substring, ignoring case
+    ContainsIgnoreCase(&'static str, String),
+    /// Matches when every filter matches, `All` when empty
+    And(Vec<Filter>),
+    /// Matches when any filter matches, nothing when empty
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    pub fn eq(field: &'static str, value: impl Into<Value>) -> Self {
+        Filter::Eq(field, value.into())
+    }
+
+    pub fn ne(field: &'static str, value: impl Into<Value>) -> Self {
+        Filter::Ne(field, value.into())
+    }
+
+    pub fn gt(field: &'static str, value: impl Into<Value>) -> Self {
+        Filter::Gt(field, value.into())
+    }
+
+    pub fn lt(field: &'static str, value: impl Into<Value>) -> Self {
+        Filter::Lt(field, value.into())
+    }
+
+    pub fn is_in<V: Into<Value>>(field: &'static str, values: impl IntoIterator<Item = V>) -> Self {
+        Filter::In(field, values.into_iter().map(Into::into).collect())
+    }
+
+    pub fn range<V: Into<Value>>(field: &'static str, min: Option<V>, max: Option<V>) -> Self {
+        Filter::Range {
+            field,
+            min: min.map(Into::into),
+            max: max.map(Into::into),
+        }
+    }
+
+    pub fn contains(field: &'static str, value: impl Into<Value>) -> Self {
+        Filter::Contains(field, value.into())
+    }
+
+    pub fn contains_ignore_case(field: &'static str, value: impl Into<String>) -> Self {
+        Filter::ContainsIgnoreCase(field, value.into())
+    }
+
+    /// Combines with another filter, flattening nested `And`s.
+    pub fn and(self, other: Filter) -> Self {
+        match (self, other) {
+            (Filter::All, other) | (other, Filter::All) => other,
+            (Filter::And(mut filters), Filter::And(others)) => {
+                filters.extend(others);
+                Filter::And(filters)
+            }
+            (Filter::And(mut filters), other) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            (filter, other) => Filter::And(vec![filter, other]),
+        }
+    }
+
+    /// Matches either filter, flattening nested `Or`s.
+    pub fn or(self, other: Filter) -> Self {
+        match (self, other) {
+            (Filter::Or(mut filters), Filter::Or(others)) => {
+                filters.extend(others);
+                Filter::Or(filters)
+            }
+            (Filter::Or(mut filters), other) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            (filter, other) => Filter::Or(vec![filter, other]),
+        }
+    }
+}
+
+/* Fields returned for every matching record */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Columns {
+    All,
+    Only(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+/* Filter together with the order and window of the results, ordered fields must be selected */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub filter: Filter,
+    pub order_by: Vec<(&'static str, Direction)>,
+    pub limit: Option<u32>,
+    pub start: Option<u32>,
+}
+
+impl Query {
+    pub fn new(filter: Filter) -> Self {
+        Query {
+            filter,
+            order_by: Vec::new(),
+            limit: None,
+            start: None,
+        }
+    }
+
+    pub fn order_by(mut self, field: &'static str, direction: Direction) -> Self {
+        self.order_by.push((field, direction));
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn start(mut self, start: u32) -> Self {
+        self.start = Some(start);
+        self
+    }
+}
+
+impl From<Filter> for Query {
+    fn from(filter: Filter) -> Self {
+        Query::new(filter)
+    }
+}
@@ -0,0 +1,624 @@
+/* SQLite backend. Every table has an `id` column holding the `table:key` record id and a `data`
+column holding the record as JSON, with the id in the record link form SurrealDB returns so that
+the same models read both backends. Timestamps are stored with a fixed number of fractional
+digits, which keeps their text order chronological. */
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params_from_iter, types::Value as SqlValue, Connection};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value as JsonValue};
+
+use errors::{Error::DatabaseErrorExecution, Result};
+use model::domain::page::{Page, PageRequest};
+
+use crate::{
+    database::{
+        append_only_message, unique_index_name, unique_violation, SqliteDb, APPEND_ONLY_TABLES,
+        UNIQUE_FIELDS,
+    },
+    interface::DBInterface,
+    page,
+    query::{Columns, Direction, Filter, Query, Value},
+    transaction::{Operation, Transaction},
+};
+
+/* Fields the models store as timestamps */
+const DATETIME_FIELDS: &[&str] = &["created_at", "updated_at", "last_used_at", "retired_at"];
+
+/* SQLite statement with the values of its `?` placeholders in order */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqliteQuery {
+    pub sql: String,
+    pub params: Vec<SqlValue>,
+}
+
+impl SqliteQuery {
+    /// Builds the `SELECT` statement of a query, returning the `data` column.
+    pub fn select(tb_name: &str, query: &Query) -> Result<Self> {
+        let mut statement = SqliteQuery {
+            sql: String::new(),
+            params: Vec::new(),
+        };
+
+        let mut sql = format!("SELECT data FROM {}", table(tb_name)?);
+        if query.filter != Filter::All {
+            sql.push_str(&format!(" WHERE {}", statement.condition(&query.filter)));
+        }
+
+        if !query.order_by.is_empty() {
+            let order = query
+                .order_by
+                .iter()
+                .map(|(field_name, direction)| match direction {
+                    Direction::Asc => format!("{} ASC", field(field_name)),
+                    Direction::Desc => format!("{} DESC", field(field_name)),
+                })
+                .collect::<Vec<_>>();
+            sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        match (query.limit, query.start) {
+            (Some(limit), _) => sql.push_str(&format!(" LIMIT {}", limit)),
+            // SQLite only takes an offset after a limit
+            (None, Some(_)) => sql.push_str(" LIMIT -1"),
+            (None, None) => {}
+        }
+        if let Some(start) = query.start {
+            sql.push_str(&format!(" OFFSET {}", start));
+        }
+
+        statement.sql = sql;
+        Ok(statement)
+    }
+
+    /// Builds the statement counting the records matching a filter.
+    pub fn count(tb_name: &str, filter: &Filter) -> Result<Self> {
+        let mut statement = SqliteQuery {
+            sql: String::new(),
+            params: Vec::new(),
+        };
+
+        let mut sql = format!("SELECT count(*) FROM {}", table(tb_name)?);
+        if *filter != Filter::All {
+            sql.push_str(&format!(" WHERE {}", statement.condition(filter)));
+        }
+
+        statement.sql = sql;
+        Ok(statement)
+    }
+
+    fn bind(&mut self, value: SqlValue) -> String {
+        self.params.push(value);
+        "?".to_string()
+    }
+
+    fn value(&mut self, value: &Value) -> String {
+        let value = match value {
+            Value::Null => SqlValue::Null,
+            Value::Bool(value) => SqlValue::Integer(*value as i64),
+            Value::Int(value) => SqlValue::Integer(*value),
+            Value::Float(value) => SqlValue::Real(*value),
+            Value::String(value) | Value::Record(value) => SqlValue::Text(value.clone()),
+            Value::DateTime(value) => SqlValue::Text(timestamp(value)),
+            Value::Json(value) => json_value(value),
+        };
+        self.bind(value)
+    }
+
+    /// Renders a field compared against a value, record links are compared in `table:key` form.
+    fn comparison(&mut self, field_name: &str, operator: &str, value: &Value) -> String {
+        let field = match value {
+            Value::Record(_) => record_field(field_name),
+            _ => field(field_name),
+        };
+        format!("{} {} {}", field, operator, self.value(value))
+    }
+
+    fn condition(&mut self, filter: &Filter) -> String {
+        match filter {
+            Filter::All => "TRUE".to_string(),
+            // `IS` also matches a missing field against `Value::Null`
+            Filter::Eq(field, value) => self.comparison(field, "IS", value),
+            Filter::Ne(field, value) => self.comparison(field, "IS NOT", value),
+            Filter::Gt(field, value) => self.comparison(field, ">", value),
+            Filter::Lt(field, value) => self.comparison(field, "<", value),
+            Filter::In(_, values) if values.is_empty() => "FALSE".to_string(),
+            Filter::In(field_name, values) => {
+                let field = match values.first() {
+                    Some(Value::Record(_)) => record_field(field_name),
+                    _ => field(field_name),
+                };
+                let values = values
+                    .iter()
+                    .map(|value| self.value(value))
+                    .collect::<Vec<_>>();
+                format!("{} IN ({})", field, values.join(", "))
+            }
+            Filter::Range {
+                field: field_name,
+                min,
+                max,
+            } => {
+                let mut bounds = Vec::new();
+                if let Some(min) = min {
+                    bounds.push(self.comparison(field_name, ">=", min));
+                }
+                if let Some(max) = max {
+                    bounds.push(self.comparison(field_name, "<=", max));
+                }
+                if bounds.is_empty() {
+                    "TRUE".to_string()
+                } else {
+                    format!("({})", bounds.join(" AND "))
+                }
+            }
+            Filter::Contains(field_name, value) => {
+                let path = path(field_name);
+                let element = self.value(value);
+                let substring = self.value(value);
+                format!(
+                    "(CASE json_type(data, '{path}') \
+                     WHEN 'array' THEN EXISTS (SELECT 1 FROM json_each(data, '{path}') \
+                     WHERE json_each.value IS {element}) \
+                     ELSE instr({field}, {substring}) > 0 END)",
+                    path = path,
+                    element = element,
+                    field = field(field_name),
+                    substring = substring,
+                )
+            }
+            Filter::ContainsIgnoreCase(field_name, value) => format!(
+                "instr(lower({}), {}) > 0",
+                field(field_name),
+                self.bind(SqlValue::Text(value.to_lowercase()))
+            ),
+            Filter::And(filters) => self.group(filters, "AND", "TRUE"),
+            Filter::Or(filters) => self.group(filters, "OR", "FALSE"),
+        }
+    }
+
+    fn group(&mut self, filters: &[Filter], operator: &str, empty: &str) -> String {
+        if filters.is_empty() {
+            return empty.to_string();
+        }
+        let conditions = filters
+            .iter()
+            .map(|filter| self.condition(filter))
+            .collect::<Vec<_>>();
+        format!("({})", conditions.join(&format!(" {} ", operator)))
+    }
+}
+
+/// Quotes a table name, which cannot be a bound parameter and so is limited to word characters.
+fn table(tb_name: &str) -> Result<String> {
+    if tb_name.is_empty()
+        || !tb_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(DatabaseErrorExecution(format!(
+            "sqlite: invalid table name {}",
+            tb_name
+        )));
+    }
+    Ok(format!("\"{}\"", tb_name))
+}
+
+fn path(field_name: &str) -> String {
+    format!("$.{}", field_name)
+}
+
+fn field(field_name: &str) -> String {
+    match field_name {
+        "id" => "id".to_string(),
+        _ => format!("json_extract(data, '{}')", path(field_name)),
+    }
+}
+
+/// A field holding a record link, read in `table:key` form whether stored as a link or as text.
+fn record_field(field_name: &str) -> String {
+    match field_name {
+        "id" => "id".to_string(),
+        _ => format!(
+            "(CASE json_type(data, '{path}') WHEN 'object' \
+             THEN json_extract(data, '{path}.tb') || ':' || json_extract(data, '{path}.id.String') \
+             ELSE json_extract(data, '{path}') END)",
+            path = path(field_name)
+        ),
+    }
+}
+
+fn json_value(value: &JsonValue) -> SqlValue {
+    match value {
+        JsonValue::Null => SqlValue::Null,
+        JsonValue::Bool(value) => SqlValue::Integer(*value as i64),
+        JsonValue::Number(number) => match number.as_i64() {
+            Some(value) => SqlValue::Integer(value),
+            None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+        },
+        JsonValue::String(value) => SqlValue::Text(value.clone()),
+        // Nested values come out of `json_extract` as minified JSON text
+        value => SqlValue::Text(value.to_string()),
+    }
+}
+
+fn timestamp(value: &DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+/// Rewrites the timestamps of a document to the stored form. Only the datetime fields of the
+/// models are touched, any other text is stored as given even when it looks like a date.
+fn normalize(value: &mut JsonValue) {
+    match value {
+        JsonValue::Array(values) => values.iter_mut().for_each(normalize),
+        JsonValue::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    JsonValue::String(text) if DATETIME_FIELDS.contains(&key.as_str()) => {
+                        if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+                            *text = timestamp(&datetime.with_timezone(&Utc));
+                        }
+                    }
+                    value => normalize(value),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn document(value: JsonValue) -> Result<Map<String, JsonValue>> {
+    match value {
+        JsonValue::Object(map) => Ok(map),
+        _ => Err(DatabaseErrorExecution(
+            "sqlite: records must be objects".to_string(),
+        )),
+    }
+}
+
+fn split_id(id: &str) -> Result<(&str, &str)> {
+    id.split_once(':')
+        .ok_or_else(|| DatabaseErrorExecution(format!("sqlite: invalid record id {}", id)))
+}
+
+fn sqlite_error(error: rusqlite::Error) -> errors::Error {
+    let message = error.to_string();
+    unique_violation(&message)
+        .unwrap_or_else(|| DatabaseErrorExecution(format!("sqlite: {}", message)))
+}
+
+fn create_table(connection: &Connection, tb_name: &str) -> Result<()> {
+    connection
+        .execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (id TEXT PRIMARY KEY, data TEXT NOT NULL)",
+                table(tb_name)?
+            ),
+            [],
+        )
+        .map_err(sqlite_error)?;
+
+    for (_, field_name) in UNIQUE_FIELDS.iter().filter(|(name, _)| *name == tb_name) {
+        connection
+            .execute(
+                &format!(
+                    "CREATE UNIQUE INDEX IF NOT EXISTS {} ON {} ({})",
+                    unique_index_name(tb_name, field_name),
+                    table(tb_name)?,
+                    field(field_name)
+                ),
+                [],
+            )
+            .map_err(sqlite_error)?;
+    }
+
+    if APPEND_ONLY_TABLES.contains(&tb_name) {
+        for operation in ["UPDATE", "DELETE"] {
+            connection
+                .execute(
+                    &format!(
+                        "CREATE TRIGGER IF NOT EXISTS append_only_{}_{} BEFORE {} ON {} \
+                         BEGIN SELECT RAISE(ABORT, '{}'); END",
+                        tb_name,
+                        operation.to_lowercase(),
+                        operation,
+                        table(tb_name)?,
+                        append_only_message(tb_name)
+                    ),
+                    [],
+                )
+                .map_err(sqlite_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Stores a new record, keyed by its `id` field like SurrealDB does or by a random key.
+fn insert(connection: &Connection, tb_name: &str, data: JsonValue) -> Result<JsonValue> {
+    create_table(connection, tb_name)?;
+
+    let mut record = document(data)?;
+    let key = match record.remove("id") {
+        Some(JsonValue::String(id)) => id
+            .strip_prefix(&format!("{}:", tb_name))
+            .unwrap_or(&id)
+            .to_string(),
+        Some(JsonValue::Object(link)) => match link.get("id") {
+            Some(JsonValue::Object(key)) => match key.get("String").or_else(|| key.get("Number")) {
+                Some(JsonValue::String(key)) => key.clone(),
+                Some(key) => key.to_string(),
+                None => uuid::Uuid::new_v4().simple().to_string(),
+            },
+            _ => uuid::Uuid::new_v4().simple().to_string(),
+        },
+        Some(JsonValue::Number(key)) => key.to_string(),
+        _ => uuid::Uuid::new_v4().simple().to_string(),
+    };
+    record.insert(
+        "id".to_string(),
+        json!({ "tb": tb_name, "id": { "String": key } }),
+    );
+
+    let mut record = JsonValue::Object(record);
+    normalize(&mut record);
+
+    connection
+        .execute(
+            &format!("INSERT INTO {} (id, data) VALUES (?, ?)", table(tb_name)?),
+            [format!("{}:{}", tb_name, key), record.to_string()],
+        )
+        .map_err(sqlite_error)?;
+    Ok(record)
+}
+
+/// Merges the data into a record, nested objects are merged as well. `false` when there is none.
+fn update(connection: &Connection, id: &str, data: JsonValue) -> Result<bool> {
+    let (tb_name, _) = split_id(id)?;
+    create_table(connection, tb_name)?;
+
+    let stored = connection.query_row(
+        &format!("SELECT data FROM {} WHERE id = ?", table(tb_name)?),
+        [id],
+        |row| row.get::<_, String>(0),
+    );
+    let stored = match stored {
+        Ok(stored) => stored,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+        Err(e) => return Err(sqlite_error(e)),
+    };
+
+    let mut record = serde_json::from_str::<JsonValue>(&stored)
+        .map_err(|e| DatabaseErrorExecution(format!("sqlite: {}", e)))?;
+    let mut changes = document(data)?;
+    changes.remove("id");
+    merge(&mut record, JsonValue::Object(changes));
+    normalize(&mut record);
+
+    connection
+        .execute(
+            &format!("UPDATE {} SET data = ? WHERE id = ?", table(tb_name)?),
+            [record.to_string(), id.to_string()],
+        )
+        .map_err(sqlite_error)?;
+    Ok(true)
+}
+
+fn merge(target: &mut JsonValue, changes: JsonValue) {
+    match (target, changes) {
+        (JsonValue::Object(target), JsonValue::Object(changes)) => {
+            for (key, value) in changes {
+                match target.get_mut(&key) {
+                    Some(existing) if existing.is_object() && value.is_object() => {
+                        merge(existing, value)
+                    }
+                    _ => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, changes) => *target = changes,
+    }
+}
+
+fn delete(connection: &Connection, id: &str) -> Result<()> {
+    let (tb_name, _) = split_id(id)?;
+    create_table(connection, tb_name)?;
+
+    connection
+        .execute(
+            &format!("DELETE FROM {} WHERE id = ?", table(tb_name)?),
+            [id],
+        )
+        .map_err(sqlite_error)?;
+    Ok(())
+}
+
+fn select(connection: &Connection, tb_name: &str, query: &Query) -> Result<Vec<JsonValue>> {
+    create_table(connection, tb_name)?;
+
+    let statement = SqliteQuery::select(tb_name, query)?;
+    let mut prepared = connection.prepare(&statement.sql).map_err(sqlite_error)?;
+    let rows = prepared
+        .query_map(params_from_iter(statement.params.iter()), |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(sqlite_error)?;
+
+    rows.map(|row| {
+        let data = row.map_err(sqlite_error)?;
+        serde_json::from_str(&data).map_err(|e| DatabaseErrorExecution(format!("sqlite: {}", e)))
+    })
+    .collect()
+}
+
+fn count(connection: &Connection, tb_name: &str, filter: &Filter) -> Result<u64> {
+    create_table(connection, tb_name)?;
+
+    let statement = SqliteQuery::count(tb_name, filter)?;
+    let total = connection
+        .query_row(
+            &statement.sql,
+            params_from_iter(statement.params.iter()),
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(sqlite_error)?;
+    Ok(total as u64)
+}
+
+fn from_json<T: DeserializeOwned>(value: JsonValue) -> Result<T> {
+    serde_json::from_value(value).map_err(|e| DatabaseErrorExecution(format!("sqlite: {}", e)))
+}
+
+fn to_json(data: impl Serialize) -> Result<JsonValue> {
+    serde_json::to_value(data).map_err(|e| DatabaseErrorExecution(format!("sqlite: {}", e)))
+}
+
+impl SqliteDb {
+    /// Opens the database file at a path, creating it when missing.
+    pub fn open(path: &str) -> Result<Self> {
+        let connection = Connection::open(path).map_err(sqlite_error)?;
+        Ok(SqliteDb {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Runs blocking SQLite calls off the async runtime, one at a time.
+    async fn run<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<R> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|_| DatabaseErrorExecution("sqlite: connection poisoned".to_string()))?;
+            f(&mut connection)
+        })
+        .await
+        .map_err(|e| DatabaseErrorExecution(format!("sqlite: {}", e)))?
+    }
+}
+
+/* Implementation of DBInterface for SqliteDb */
+#[async_trait]
+impl DBInterface for SqliteDb {
+    /* Method to insert a record into the database */
+    async fn insert_record<T, U>(&self, tb_name: &str, data: T) -> Result<Option<U>>
+    where
+        T: Serialize + Sync + Send + 'static,
+        U: DeserializeOwned + Sync + Clone + 'static,
+    {
+        let tb_name = tb_name.to_string();
+        let data = to_json(data)?;
+        let record = self
+            .run(move |connection| insert(connection, &tb_name, data))
+            .await?;
+        from_json(record).map(Some)
+    }
+
+    /* Method to select records from the database */
+    async fn select<T: DeserializeOwned + Sync>(&self, tb_name: &str) -> Result<Vec<T>> {
+        self.select_where(tb_name, Filter::All, Columns::All).await
+    }
+
+    /* Method to delete a record from the database */
+    async fn delete(&self, id: &str) -> Result<bool> {
+        let id = id.to_string();
+        self.run(move |connection| delete(connection, &id)).await?;
+        Ok(true)
+    }
+
+    /* Method to update a record in the database */
+    async fn update_record<T>(&self, id: &str, tb_name: &str, data: T) -> Result<bool>
+    where
+        T: Serialize + for<'de> Deserialize<'de> + Sync + Send + 'static,
+    {
+        // Accepts the key alone like the SurrealDB backend does
+        let id = match id.split_once(':') {
+            Some((_, key)) => format!("{}:{}", tb_name, key),
+            None => format!("{}:{}", tb_name, id),
+        };
+        let data = to_json(data)?;
+        self.run(move |connection| update(connection, &id, data))
+            .await
+    }
+
+    /* Method to select records matching a query from the database */
+    async fn select_where<T: DeserializeOwned + Sync>(
+        &self,
+        tb_name: &str,
+        query: impl Into<Query> + Send,
+        columns: Columns,
+    ) -> Result<Vec<T>> {
+        let tb_name = tb_name.to_string();
+        let query = query.into();
+        let records = self
+            .run(move |connection| select(connection, &tb_name, &query))
+            .await?;
+
+        records
+            .into_iter()
+            .map(|record| match columns {
+                Columns::All => from_json(record),
+                Columns::Only(fields) => {
+                    let record = document(record)?
+                        .into_iter()
+                        .filter(|(key, _)| fields.contains(&key.as_str()))
+                        .collect::<Map<_, _>>();
+                    from_json(JsonValue::Object(record))
+                }
+            })
+            .collect()
+    }
+
+    /* Method to select one page of records matching a query from the database */
+    async fn select_page<T: Serialize + DeserializeOwned + Sync + Send>(
+        &self,
+        tb_name: &str,
+        query: impl Into<Query> + Send,
+        page: &PageRequest,
+    ) -> Result<Page<T>> {
+        page::select_page(self, tb_name, query.into(), page).await
+    }
+
+    /* Method to count the records matching a filter in the database */
+    async fn count(&self, tb_name: &str, filter: &Filter) -> Result<u64> {
+        let tb_name = tb_name.to_string();
+        let filter = filter.clone();
+        self.run(move |connection| count(connection, &tb_name, &filter))
+            .await
+    }
+
+    /* Method to apply the operations of a transaction at once */
+    async fn commit(&self, transaction: Transaction) -> Result<()> {
+        self.run(move |connection| {
+            // Dropping the SQLite transaction on an error rolls it back
+            let sqlite_transaction = connection.transaction().map_err(sqlite_error)?;
+            for operation in transaction.operations() {
+                match operation {
+                    Operation::Insert { tb_name, data } => {
+                        insert(&sqlite_transaction, tb_name, data.clone())?;
+                    }
+                    Operation::Update { id, data } => {
+                        update(&sqlite_transaction, id, data.clone())?;
+                    }
+                    Operation::Delete { id } => delete(&sqlite_transaction, id)?,
+                    Operation::EnsureNone {
+                        tb_name,
+                        filter,
+                        error,
+                    } => {
+                        if count(&sqlite_transaction, tb_name, filter)? > 0 {
+                            return Err(error.clone());
+                        }
+                    }
+                }
+            }
+            sqlite_transaction.commit().map_err(sqlite_error)
+        })
+        .await
+    }
+}
@@ -1,12 +1,256 @@
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
+use chrono::SecondsFormat;
 
 use super::interface;
-use crate::database::SurrealDb;
+use crate::{
+    database::{unique_violation, SurrealDb},
+    page,
+    query::{Columns, Direction, Filter, Query, Value},
+    transaction::{Operation, Transaction},
+};
 
-use errors::{Error::DatabaseErrorExecution, Result};
+use errors::{
+    Error::{DatabaseErrorExecution, TransactionConflict},
+    Result,
+};
 use interface::DBInterface;
+use model::domain::page::{Page, PageRequest};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+
+/* Marks the error thrown by a failed `ensure_none` of a transaction */
+const TRANSACTION_CHECK: &str = "transaction check failed: ";
+/* Part of the message of a transaction failed by a read or write conflict */
+const TRANSACTION_RETRYABLE: &str = "This transaction can be retried";
+
+/// Converts an engine error, a write rejected by a unique index becomes `DataExist`.
+fn database_error(error: surrealdb::Error) -> errors::Error {
+    unique_violation(&error.to_string()).unwrap_or_else(|| error.into())
+}
+
+/* SurrealQL statement with the values it refers to as `$p0`, `$p1`, ... */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurrealQuery {
+    pub sql: String,
+    pub params: BTreeMap<String, JsonValue>,
+}
+
+impl SurrealQuery {
+    /// Builds the `SELECT` statement of a query, the table name is bound as well.
+    pub fn select(tb_name: &str, query: &Query, columns: Columns) -> Self {
+        let mut statement = SurrealQuery {
+            sql: String::new(),
+            params: BTreeMap::new(),
+        };
+
+        let table = statement.bind(json!(tb_name));
+        let projection = match columns {
+            Columns::All => "*".to_string(),
+            Columns::Only(fields) => fields.join(", "),
+        };
+        let mut sql = format!("SELECT {} FROM type::table({})", projection, table);
+
+        if query.filter != Filter::All {
+            sql.push_str(&format!(" WHERE {}", statement.condition(&query.filter)));
+        }
+
+        if !query.order_by.is_empty() {
+            let order = query
+                .order_by
+                .iter()
+                .map(|(field, direction)| match direction {
+                    Direction::Asc => format!("{} ASC", field),
+                    Direction::Desc => format!("{} DESC", field),
+                })
+                .collect::<Vec<_>>();
+            sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(start) = query.start {
+            sql.push_str(&format!(" START {}", start));
+        }
+
+        statement.sql = sql;
+        statement
+    }
+
+    /// Builds the statement counting the records matching a filter, as a single `total` row.
+    pub fn count(tb_name: &str, filter: &Filter) -> Self {
+        let mut statement = SurrealQuery {
+            sql: String::new(),
+            params: BTreeMap::new(),
+        };
+
+        let table = statement.bind(json!(tb_name));
+        let mut sql = format!("SELECT count() AS total FROM type::table({})", table);
+        if *filter != Filter::All {
+            sql.push_str(&format!(" WHERE {}", statement.condition(filter)));
+        }
+        sql.push_str(" GROUP ALL");
+
+        statement.sql = sql;
+        statement
+    }
+
+    /// Builds the statement deleting the record with the `table:key` id, both parts are bound.
+    pub fn delete(id: &str) -> Result<Self> {
+        let mut statement = SurrealQuery {
+            sql: String::new(),
+            params: BTreeMap::new(),
+        };
+
+        let (tb_name, key) = id.split_once(':').ok_or_else(|| {
+            DatabaseErrorExecution(format!("surrealdb: '{}' is not a record id", id))
+        })?;
+        let table = statement.bind(json!(tb_name));
+        let key = statement.bind(json!(key));
+
+        statement.sql = format!("DELETE type::thing({}, {})", table, key);
+        Ok(statement)
+    }
+
+    /// Builds a `BEGIN ... COMMIT` block of the operations of a transaction. A failed
+    /// `ensure_none` throws `TRANSACTION_CHECK` followed by the index of its operation.
+    pub fn transaction(transaction: &Transaction) -> Self {
+        let mut statement = SurrealQuery {
+            sql: String::new(),
+            params: BTreeMap::new(),
+        };
+
+        let mut statements = vec!["BEGIN TRANSACTION".to_string()];
+        for (index, operation) in transaction.operations().iter().enumerate() {
+            let sql = match operation {
+                Operation::Insert { tb_name, data } => {
+                    let table = statement.bind(json!(tb_name));
+                    let data = statement.bind(data.clone());
+                    format!("CREATE type::table({}) CONTENT {}", table, data)
+                }
+                Operation::Update { id, data } => {
+                    let record = statement.value(&Value::record(id.as_str()));
+                    let data = statement.bind(data.clone());
+                    format!("UPDATE {} MERGE {}", record, data)
+                }
+                Operation::Delete { id } => {
+                    format!("DELETE {}", statement.value(&Value::record(id.as_str())))
+                }
+                Operation::EnsureNone {
+                    tb_name, filter, ..
+                } => {
+                    let table = statement.bind(json!(tb_name));
+                    let mut select = format!("SELECT id FROM type::table({})", table);
+                    if *filter != Filter::All {
+                        select.push_str(&format!(" WHERE {}", statement.condition(filter)));
+                    }
+                    format!(
+                        "IF array::len(({} LIMIT 1)) > 0 {{ THROW \"{}{}\" }}",
+                        select, TRANSACTION_CHECK, index
+                    )
+                }
+            };
+            statements.push(sql);
+        }
+        statements.push("COMMIT TRANSACTION".to_string());
+
+        statement.sql = format!("{};", statements.join("; "));
+        statement
+    }
+
+    fn bind(&mut self, value: JsonValue) -> String {
+        let name = format!("p{}", self.params.len());
+        self.params.insert(name.clone(), value);
+        format!("${}", name)
+    }
+
+    fn value(&mut self, value: &Value) -> String {
+        match value {
+            Value::Null => self.bind(JsonValue::Null),
+            Value::Bool(value) => self.bind(json!(value)),
+            Value::Int(value) => self.bind(json!(value)),
+            Value::Float(value) => self.bind(json!(value)),
+            Value::String(value) => self.bind(json!(value)),
+            Value::DateTime(value) => {
+                let value = self.bind(json!(value.to_rfc3339_opts(SecondsFormat::Micros, true)));
+                format!("type::datetime({})", value)
+            }
+            Value::Record(id) => match id.split_once(':') {
+                Some((table, key)) => {
+                    let table = self.bind(json!(table));
+                    let key = self.bind(json!(key));
+                    format!("type::thing({}, {})", table, key)
+                }
+                None => self.bind(json!(id)),
+            },
+            Value::Json(value) => self.bind(value.clone()),
+        }
+    }
+
+    /// Renders a field compared against a value, datetimes are stored as strings.
+    fn comparison(&mut self, field: &str, operator: &str, value: &Value) -> String {
+        let value_sql = self.value(value);
+        match value {
+            Value::DateTime(_) => format!("type::datetime({}) {} {}", field, operator, value_sql),
+            _ => format!("{} {} {}", field, operator, value_sql),
+        }
+    }
+
+    fn condition(&mut self, filter: &Filter) -> String {
+        match filter {
+            Filter::All => "true".to_string(),
+            Filter::Eq(field, value) => self.comparison(field, "=", value),
+            Filter::Ne(field, value) => self.comparison(field, "!=", value),
+            Filter::Gt(field, value) => self.comparison(field, ">", value),
+            Filter::Lt(field, value) => self.comparison(field, "<", value),
+            Filter::In(_, values) if values.is_empty() => "false".to_string(),
+            Filter::In(field, values) => {
+                let values = values
+                    .iter()
+                    .map(|value| self.value(value))
+                    .collect::<Vec<_>>();
+                format!("{} IN [{}]", field, values.join(", "))
+            }
+            Filter::Range { field, min, max } => {
+                let mut bounds = Vec::new();
+                if let Some(min) = min {
+                    bounds.push(self.comparison(field, ">=", min));
+                }
+                if let Some(max) = max {
+                    bounds.push(self.comparison(field, "<=", max));
+                }
+                if bounds.is_empty() {
+                    "true".to_string()
+                } else {
+                    format!("({})", bounds.join(" AND "))
+                }
+            }
+            Filter::Contains(field, value) => {
+                format!("{} CONTAINS {}", field, self.value(value))
+            }
+            Filter::ContainsIgnoreCase(field, value) => format!(
+                "string::lowercase({}) CONTAINS {}",
+                field,
+                self.bind(json!(value.to_lowercase()))
+            ),
+            Filter::And(filters) => self.group(filters, "AND", "true"),
+            Filter::Or(filters) => self.group(filters, "OR", "false"),
+        }
+    }
+
+    fn group(&mut self, filters: &[Filter], operator: &str, empty: &str) -> String {
+        if filters.is_empty() {
+            return empty.to_string();
+        }
+        let conditions = filters
+            .iter()
+            .map(|filter| self.condition(filter))
+            .collect::<Vec<_>>();
+        format!("({})", conditions.join(&format!(" {} ", operator)))
+    }
+}
 
 /* Implementation of DBInterface for SurrealDb */
 #[async_trait]
@@ -25,7 +269,11 @@ impl DBInterface for SurrealDb {
         let client = self.client.clone().ok_or_else(|| {
             DatabaseErrorExecution("surrealdb: Client connection error".to_string())
         })?;
-        let created: Vec<U> = client.insert(tb_name).content(data).await?;
+        let created: Vec<U> = client
+            .insert(tb_name)
+            .content(data)
+            .await
+            .map_err(database_error)?;
         let record = created.first().cloned();
         Ok(record)
     }
@@ -44,8 +292,13 @@ impl DBInterface for SurrealDb {
         let client = self.client.clone().ok_or_else(|| {
             DatabaseErrorExecution("surrealdb: Client connection error".to_string())
         })?;
-        let result = client.query(format!("DELETE {}", id)).await?.check();
-        Ok(result.is_ok())
+        let statement = SurrealQuery::delete(id)?;
+        client
+            .query(statement.sql)
+            .bind(statement.params)
+            .await?
+            .check()?;
+        Ok(true)
     }
 
     /* Method to update a record in the database */
@@ -53,42 +306,108 @@ impl DBInterface for SurrealDb {
     where
         T: Serialize + for<'de> Deserialize<'de> + Sync + Send + 'static,
     {
-        let data_id: Vec<&str> = id.split(':').collect();
+        let (_, key) = id.split_once(':').ok_or_else(|| {
+            DatabaseErrorExecution(format!("surrealdb: '{}' is not a record id", id))
+        })?;
         let client = self.client.clone().ok_or_else(|| {
             DatabaseErrorExecution("surrealdb: Client connection error".to_string())
         })?;
-        let updated_result: Option<model::surreal_db::user::ReturnedUser> =
-            client.update((tb_name, data_id[1])).merge(data).await?;
+        let updated_result: Option<model::surreal_db::user::ReturnedUser> = client
+            .update((tb_name, key))
+            .merge(data)
+            .await
+            .map_err(database_error)?;
         Ok(updated_result.is_some())
     }
 
-    /* Method to select records with parameters from the database */
+    /* Method to select records matching a query from the database */
     async fn select_where<T: DeserializeOwned + Sync>(
         &self,
         tb_name: &str,
-        filter: &str,
-        columns: &str,
+        query: impl Into<Query> + Send,
+        columns: Columns,
     ) -> Result<Vec<T>> {
         let client = self.client.clone().ok_or(DatabaseErrorExecution(
             "surrealdb: Client connection error".to_string(),
         ))?;
 
-        let filtered_query = if filter.is_empty() {
-            String::new()
-        } else {
-            format!("where {}", filter)
-        };
+        let statement = SurrealQuery::select(tb_name, &query.into(), columns);
 
-        let tb_columns = if columns.is_empty() {
-            String::from(" * ")
-        } else {
-            format!(" {} ", columns)
-        };
-
-        let sql = format!("SELECT {} FROM {} {}", tb_columns, tb_name, filtered_query);
-
-        let mut results = client.query(&sql).await?;
+        let mut results = client.query(statement.sql).bind(statement.params).await?;
         let data: Vec<T> = results.take(0)?;
         Ok(data)
     }
+
+    /* Method to select one page of records matching a query from the database */
+    async fn select_page<T: Serialize + DeserializeOwned + Sync + Send>(
+        &self,
+        tb_name: &str,
+        query: impl Into<Query> + Send,
+        page: &PageRequest,
+    ) -> Result<Page<T>> {
+        page::select_page(self, tb_name, query.into(), page).await
+    }
+
+    /* Method to count the records matching a filter in the database */
+    async fn count(&self, tb_name: &str, filter: &Filter) -> Result<u64> {
+        let client = self.client.clone().ok_or(DatabaseErrorExecution(
+            "surrealdb: Client connection error".to_string(),
+        ))?;
+
+        let statement = SurrealQuery::count(tb_name, filter);
+
+        let mut results = client.query(statement.sql).bind(statement.params).await?;
+        let total: Option<u64> = results.take((0, "total"))?;
+        // Nothing matching yields no row at all rather than a zero count
+        Ok(total.unwrap_or(0))
+    }
+
+    /* Method to apply the operations of a transaction at once */
+    async fn commit(&self, transaction: Transaction) -> Result<()> {
+        if transaction.is_empty() {
+            return Ok(());
+        }
+
+        let client = self.client.clone().ok_or(DatabaseErrorExecution(
+            "surrealdb: Client connection error".to_string(),
+        ))?;
+
+        let statement = SurrealQuery::transaction(&transaction);
+
+        let mut results = client.query(statement.sql).bind(statement.params).await?;
+        let errors = results.take_errors();
+
+        // Every statement of a failed transaction reports an error, a failed check or unique
+        // index names itself
+        let failed_check = errors.values().find_map(|error| {
+            let message = error.to_string();
+            let index = message.split(TRANSACTION_CHECK).nth(1)?;
+            let index: usize = index
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect::<String>()
+                .parse()
+                .ok()?;
+            match transaction.operations().get(index) {
+                Some(Operation::EnsureNone { error, .. }) => Some(error.clone()),
+                _ => None,
+            }
+        });
+        let failed_check = failed_check.or_else(|| {
+            errors
+                .values()
+                .find_map(|error| unique_violation(&error.to_string()))
+        });
+
+        let first_error = errors.into_iter().min_by_key(|(index, _)| *index);
+        match (failed_check, first_error) {
+            (Some(error), _) => Err(error),
+            // Optimistic transactions that touched the same records as a concurrent one fail
+            (None, Some((_, error))) if error.to_string().contains(TRANSACTION_RETRYABLE) => {
+                Err(TransactionConflict(error.to_string()))
+            }
+            (None, Some((_, error))) => Err(error.into()),
+            (None, None) => Ok(()),
+        }
+    }
 }
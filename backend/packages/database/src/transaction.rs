@@ -0,0 +1,90 @@
+/* Writes that are applied together or not at all. Operations are buffered and sent as a single
+transaction on `DBInterface::commit`, so nothing reaches the database before then and a
+transaction that is dropped or cancelled leaves no trace. Reads cannot see the buffered writes,
+conditions that must hold at commit time are expressed with `ensure_none`. */
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use errors::{Error, Error::DatabaseErrorExecution, Result};
+
+use crate::query::Filter;
+
+/* A single write of a transaction */
+#[derive(Debug, Clone)]
+pub enum Operation {
+    /// Creates a record, an `id` field in the data sets its key
+    Insert {
+        tb_name: String,
+        data: JsonValue,
+    },
+    /// Merges the data into the record with the `table:key` id
+    Update {
+        id: String,
+        data: JsonValue,
+    },
+    Delete {
+        id: String,
+    },
+    /// Aborts the transaction with the error when a record of the table matches the filter
+    EnsureNone {
+        tb_name: String,
+        filter: Filter,
+        error: Error,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    operations: Vec<Operation>,
+}
+
+impl Transaction {
+    pub fn begin() -> Self {
+        Transaction::default()
+    }
+
+    pub fn insert(&mut self, tb_name: &str, data: impl Serialize) -> Result<()> {
+        self.operations.push(Operation::Insert {
+            tb_name: tb_name.to_string(),
+            data: to_json(data)?,
+        });
+        Ok(())
+    }
+
+    pub fn update(&mut self, id: &str, data: impl Serialize) -> Result<()> {
+        self.operations.push(Operation::Update {
+            id: id.to_string(),
+            data: to_json(data)?,
+        });
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: &str) {
+        self.operations
+            .push(Operation::Delete { id: id.to_string() });
+    }
+
+    /// Fails the commit with `error` if any record of the table matches the filter by then.
+    pub fn ensure_none(&mut self, tb_name: &str, filter: Filter, error: Error) {
+        self.operations.push(Operation::EnsureNone {
+            tb_name: tb_name.to_string(),
+            filter,
+            error,
+        });
+    }
+
+    /// Discards the buffered operations, the same as dropping the transaction.
+    pub fn cancel(self) {}
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+fn to_json(data: impl Serialize) -> Result<JsonValue> {
+    serde_json::to_value(data).map_err(|e| DatabaseErrorExecution(format!("transaction: {}", e)))
+}
@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use database::{
+        page::Keyset,
+        query::{Columns, Direction, Filter, Query, Value},
+        surrealdb::SurrealQuery,
+        transaction::Transaction,
+    };
+    use errors::{
+        Error::{DataExist, DecodeError},
+        Result,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_values_are_bound() {
+        let statement = SurrealQuery::select(
+            "user",
+            &Filter::eq("email", "x' OR true; DELETE user; --").into(),
+            Columns::All,
+        );
+
+        assert_eq!(
+            statement.sql,
+            "SELECT * FROM type::table($p0) WHERE email = $p1"
+        );
+        assert_eq!(statement.params["p0"], json!("user"));
+        assert_eq!(statement.params["p1"], json!("x' OR true; DELETE user; --"));
+    }
+
+    #[test]
+    fn test_record_ids_and_datetimes() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let filter = Filter::eq("id", Value::record("user:user_1")).and(Filter::range(
+            "created_at",
+            Some(from),
+            None,
+        ));
+
+        let statement = SurrealQuery::select("audit_event", &filter.into(), Columns::All);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT * FROM type::table($p0) WHERE (id = type::thing($p1, $p2) \
+             AND (type::datetime(created_at) >= type::datetime($p3)))"
+        );
+        assert_eq!(statement.params["p1"], json!("user"));
+        assert_eq!(statement.params["p2"], json!("user_1"));
+        assert_eq!(statement.params["p3"], json!("2024-01-02T03:04:05.000000Z"));
+    }
+
+    #[test]
+    fn test_nested_conditions() {
+        let filter = Filter::contains_ignore_case("username", "Jane")
+            .or(Filter::contains_ignore_case("email", "Jane"))
+            .and(Filter::ne("disabled", true))
+            .and(Filter::is_in("role", ["buyer", "seller"]))
+            .and(Filter::contains("identities", json!({ "issuer": "idp" })));
+
+        let statement = SurrealQuery::select("user", &filter.into(), Columns::All);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT * FROM type::table($p0) WHERE ((string::lowercase(username) CONTAINS $p1 \
+             OR string::lowercase(email) CONTAINS $p2) AND disabled != $p3 \
+             AND role IN [$p4, $p5] AND identities CONTAINS $p6)"
+        );
+        assert_eq!(statement.params["p1"], json!("jane"));
+        assert_eq!(statement.params["p6"], json!({ "issuer": "idp" }));
+    }
+
+    #[test]
+    fn test_empty_groups() {
+        let statement = SurrealQuery::select(
+            "role",
+            &Filter::is_in("name", Vec::<String>::new())
+                .or(Filter::Or(Vec::new()))
+                .into(),
+            Columns::All,
+        );
+
+        assert_eq!(
+            statement.sql,
+            "SELECT * FROM type::table($p0) WHERE (false OR false)"
+        );
+    }
+
+    #[test]
+    fn test_order_and_window() {
+        let query = Query::new(Filter::All)
+            .order_by("created_at", Direction::Desc)
+            .order_by("username", Direction::Asc)
+            .limit(20)
+            .start(40);
+
+        let statement = SurrealQuery::select("user", &query, Columns::Only(&["id", "username"]));
+
+        assert_eq!(
+            statement.sql,
+            "SELECT id, username FROM type::table($p0) \
+             ORDER BY created_at DESC, username ASC LIMIT 20 START 40"
+        );
+    }
+
+    #[test]
+    fn test_count() {
+        let statement = SurrealQuery::count("api_key", &Filter::eq("user_id", "user:user_1"));
+
+        assert_eq!(
+            statement.sql,
+            "SELECT count() AS total FROM type::table($p0) WHERE user_id = $p1 GROUP ALL"
+        );
+        assert_eq!(
+            SurrealQuery::count("api_key", &Filter::All).sql,
+            "SELECT count() AS total FROM type::table($p0) GROUP ALL"
+        );
+    }
+
+    #[test]
+    fn test_delete() -> Result<()> {
+        let statement = SurrealQuery::delete("user:user_1; DELETE user")?;
+
+        assert_eq!(statement.sql, "DELETE type::thing($p0, $p1)");
+        assert_eq!(statement.params["p0"], json!("user"));
+        assert_eq!(statement.params["p1"], json!("user_1; DELETE user"));
+        assert!(SurrealQuery::delete("user_1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyset_ends_with_id() {
+        let keyset = Keyset::new(&[("created_at", Direction::Desc)]);
+        assert_eq!(
+            keyset.order_by(),
+            &[("created_at", Direction::Desc), ("id", Direction::Desc)]
+        );
+
+        let keyset = Keyset::new(&[("id", Direction::Asc), ("username", Direction::Desc)]);
+        assert_eq!(
+            keyset.order_by(),
+            &[("id", Direction::Asc), ("username", Direction::Desc)]
+        );
+    }
+
+    #[test]
+    fn test_keyset_cursor() -> Result<()> {
+        let keyset = Keyset::new(&[("created_at", Direction::Desc)]);
+        let cursor = keyset.cursor(&json!({
+            "id": "user:user_1",
+            "username": "jane",
+            "created_at": "2024-01-02T03:04:05Z"
+        }))?;
+
+        let statement = SurrealQuery::select("user", &keyset.after(&cursor)?.into(), Columns::All);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT * FROM type::table($p0) WHERE (type::datetime(created_at) < type::datetime($p1) \
+             OR (type::datetime(created_at) = type::datetime($p2) AND id < type::thing($p3, $p4)))"
+        );
+        assert_eq!(statement.params["p1"], json!("2024-01-02T03:04:05.000000Z"));
+        assert_eq!(statement.params["p3"], json!("user"));
+        assert_eq!(statement.params["p4"], json!("user_1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        let keyset = Keyset::new(&[("username", Direction::Asc)]);
+
+        assert!(keyset.cursor(&json!({ "id": "user:user_1" })).is_err());
+        for cursor in ["not base64!", "W10", "WyJqYW5lIl0"] {
+            assert!(matches!(keyset.after(cursor), Err(DecodeError(_))));
+        }
+    }
+
+    #[test]
+    fn test_transaction() -> Result<()> {
+        let mut transaction = Transaction::begin();
+        transaction.ensure_none(
+            "user",
+            Filter::eq("email", "jane@example.com"),
+            DataExist("jane@example.com".to_string()),
+        );
+        transaction.insert(
+            "user",
+            json!({ "id": "user_1", "email": "jane@example.com" }),
+        )?;
+        transaction.update("store:store_1", json!({ "name": "Jane's" }))?;
+        transaction.delete("store:store_2");
+
+        let statement = SurrealQuery::transaction(&transaction);
+
+        assert_eq!(
+            statement.sql,
+            "BEGIN TRANSACTION; \
+             IF array::len((SELECT id FROM type::table($p0) WHERE email = $p1 LIMIT 1)) > 0 \
+             { THROW \"transaction check failed: 0\" }; \
+             CREATE type::table($p2) CONTENT $p3; \
+             UPDATE type::thing($p4, $p5) MERGE $p6; \
+             DELETE type::thing($p7, $p8); \
+             COMMIT TRANSACTION;"
+        );
+        assert_eq!(statement.params["p3"]["id"], json!("user_1"));
+        assert_eq!(statement.params["p7"], json!("store"));
+        assert_eq!(statement.params["p8"], json!("store_2"));
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+#![cfg(feature = "sqlite")]
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use database::{
+        database::{DatabaseClient, DatabaseSource, DatabaseType, Sources},
+        interface::DBInterface as _,
+        query::{Columns, Direction, Filter, Query, Value},
+        sqlite::SqliteQuery,
+        transaction::Transaction,
+    };
+    use errors::{Error::DataExist, Result};
+    use model::domain::{
+        page::PageRequest,
+        user::{ExternalIdentity, User},
+    };
+    use rusqlite::types::Value as SqlValue;
+    use serde_json::{json, Value as JsonValue};
+
+    use tokio::test;
+
+    async fn setup_db() -> Result<DatabaseClient> {
+        let mut source = DatabaseSource {
+            db_type: DatabaseType::Sqlite(":memory:".to_string()),
+        };
+        source.connect().await
+    }
+
+    fn user(id: &str, username: &str, minutes: i64) -> User {
+        let created_at =
+            Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap() + Duration::minutes(minutes);
+        User {
+            id: id.to_string(),
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            role: "buyer".to_string(),
+            password: "hash".to_string(),
+            verified: false,
+            disabled: false,
+            full_name: None,
+            phone_number: None,
+            totp_secret: None,
+            recovery_codes: Vec::new(),
+            two_factor_required: false,
+            identities: vec![ExternalIdentity {
+                issuer: "https://issuer.example.com".to_string(),
+                subject: username.to_string(),
+            }],
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    async fn test_insert_select_update_delete() -> Result<()> {
+        let db = setup_db().await?;
+
+        let inserted: Option<User> = db.insert_record("user", user("user_1", "jane", 0)).await?;
+        assert_eq!(
+            inserted.map(|user| user.id),
+            Some("user:user_1".to_string())
+        );
+
+        assert!(
+            db.update_record("user:user_1", "user", json!({ "verified": true }))
+                .await?
+        );
+        assert!(
+            !db.update_record("user:user_2", "user", json!({ "verified": true }))
+                .await?
+        );
+
+        let users: Vec<User> = db
+            .select_where(
+                "user",
+                Filter::eq("id", Value::record("user:user_1")),
+                Columns::All,
+            )
+            .await?;
+        assert_eq!(users.len(), 1);
+        assert!(users[0].verified);
+        assert_eq!(users[0].username, "jane");
+
+        assert!(db.delete("user:user_1").await?);
+        let users: Vec<User> = db.select("user").await?;
+        assert!(users.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_date_like_text_is_kept() -> Result<()> {
+        let db = setup_db().await?;
+        let mut jane = user("user_1", "jane", 0);
+        jane.full_name = Some("2024-01-02T03:04:05+01:00".to_string());
+        let _: Option<User> = db.insert_record("user", jane).await?;
+
+        let users: Vec<User> = db.select("user").await?;
+        assert_eq!(
+            users[0].full_name.as_deref(),
+            Some("2024-01-02T03:04:05+01:00")
+        );
+        assert_eq!(
+            users[0].created_at,
+            Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()
+        );
+
+        let since = Utc.with_ymd_and_hms(2024, 1, 2, 3, 0, 0).unwrap();
+        let filter = Filter::range("created_at", Some(since), None);
+        assert_eq!(db.count("user", &filter).await?, 1);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_select_where_filters() -> Result<()> {
+        let db = setup_db().await?;
+        for (id, username, minutes) in [("user_1", "jane", 0), ("user_2", "john", 10)] {
+            let _: Option<User> = db
+                .insert_record("user", user(id, username, minutes))
+                .await?;
+        }
+        let since = Utc.with_ymd_and_hms(2024, 1, 2, 3, 10, 0).unwrap();
+
+        let filters = [
+            (Filter::eq("username", "jane"), 1),
+            (Filter::ne("username", "jane"), 1),
+            (Filter::eq("verified", false), 2),
+            (Filter::eq("full_name", Value::Null), 2),
+            (Filter::is_in("username", ["jane", "john", "joe"]), 2),
+            (Filter::range("created_at", Some(since), None), 1),
+            (Filter::contains_ignore_case("email", "JOHN@"), 1),
+            (
+                Filter::contains(
+                    "identities",
+                    json!({ "issuer": "https://issuer.example.com", "subject": "jane" }),
+                ),
+                1,
+            ),
+            (Filter::is_in::<&str>("username", []), 0),
+        ];
+        for (filter, expected) in filters {
+            assert_eq!(db.count("user", &filter).await?, expected, "{:?}", filter);
+        }
+        Ok(())
+    }
+
+    #[test]
+    async fn test_select_page() -> Result<()> {
+        let db = setup_db().await?;
+        for minutes in 0..5 {
+            let _: Option<User> = db
+                .insert_record(
+                    "user",
+                    user(
+                        &format!("user_{}", minutes),
+                        &format!("user{}", minutes),
+                        minutes,
+                    ),
+                )
+                .await?;
+        }
+        let query = Query::new(Filter::All).order_by("created_at", Direction::Desc);
+
+        let first = db
+            .select_page::<User>("user", query.clone(), &PageRequest::offset(2, 0))
+            .await?;
+        assert_eq!(first.total, 5);
+        let usernames = first.items.iter().map(|user| user.username.as_str());
+        assert_eq!(usernames.collect::<Vec<_>>(), ["user4", "user3"]);
+
+        let second = db
+            .select_page::<User>(
+                "user",
+                query.clone(),
+                &PageRequest::after(2, first.next_cursor.expect("a second page")),
+            )
+            .await?;
+        let usernames = second.items.iter().map(|user| user.username.as_str());
+        assert_eq!(usernames.collect::<Vec<_>>(), ["user2", "user1"]);
+
+        let last = db
+            .select_page::<User>("user", query.clone(), &PageRequest::offset(2, 4))
+            .await?;
+        assert_eq!(last.items.len(), 1);
+        assert!(last.next_cursor.is_none());
+
+        // The extra record asked for to find the next page must not overflow the limit
+        let everything = db
+            .select_page::<User>("user", query, &PageRequest::offset(u32::MAX, 0))
+            .await?;
+        assert_eq!(everything.items.len(), 5);
+        assert!(everything.next_cursor.is_none());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_commit_rolls_back_on_failed_check() -> Result<()> {
+        let db = setup_db().await?;
+
+        let mut transaction = Transaction::begin();
+        transaction.insert("user", user("user_1", "jane", 0))?;
+        db.commit(transaction).await?;
+
+        let mut transaction = Transaction::begin();
+        transaction.update("user:user_1", json!({ "verified": true }))?;
+        transaction.ensure_none(
+            "user",
+            Filter::eq("username", "jane"),
+            DataExist("jane".to_string()),
+        );
+        transaction.insert("user", user("user_2", "jane", 1))?;
+        assert!(matches!(db.commit(transaction).await, Err(DataExist(_))));
+
+        let users: Vec<User> = db.select("user").await?;
+        assert_eq!(users.len(), 1);
+        assert!(!users[0].verified);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_audit_events_are_append_only() -> Result<()> {
+        let db = setup_db().await?;
+        db.insert_record::<_, JsonValue>(
+            "audit_event",
+            json!({ "id": "event_1", "event_type": "login" }),
+        )
+        .await?;
+
+        assert!(db
+            .update_record(
+                "audit_event:event_1",
+                "audit_event",
+                json!({ "event_type": "logout" })
+            )
+            .await
+            .is_err());
+        assert!(db.delete("audit_event:event_1").await.is_err());
+
+        let events: Vec<JsonValue> = db.select("audit_event").await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["event_type"], "login");
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unique_fields_are_enforced() -> Result<()> {
+        let db = setup_db().await?;
+        db.insert_record::<_, User>("user", user("user_1", "jane", 0))
+            .await?;
+        db.insert_record::<_, User>("user", user("user_2", "john", 1))
+            .await?;
+
+        // Without a check in the transaction only the index stands in the way
+        let mut transaction = Transaction::begin();
+        transaction.insert("user", user("user_3", "jane", 2))?;
+        assert!(matches!(db.commit(transaction).await, Err(DataExist(_))));
+
+        let taken = db
+            .update_record(
+                "user:user_2",
+                "user",
+                json!({ "email": "jane@example.com" }),
+            )
+            .await;
+        assert!(matches!(taken, Err(DataExist(_))));
+
+        let users: Vec<User> = db.select("user").await?;
+        assert_eq!(users.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_select_statement() -> Result<()> {
+        let query = Query::new(
+            Filter::eq("user_id", Value::record("user:user_1"))
+                .and(Filter::contains_ignore_case("name", "Shop")),
+        )
+        .order_by("created_at", Direction::Desc)
+        .start(20);
+
+        let statement = SqliteQuery::select("store", &query)?;
+
+        assert_eq!(
+            statement.sql,
+            "SELECT data FROM \"store\" WHERE ((CASE json_type(data, '$.user_id') WHEN 'object' \
+             THEN json_extract(data, '$.user_id.tb') || ':' || json_extract(data, '$.user_id.id.String') \
+             ELSE json_extract(data, '$.user_id') END) IS ? \
+             AND instr(lower(json_extract(data, '$.name')), ?) > 0) \
+             ORDER BY json_extract(data, '$.created_at') DESC LIMIT -1 OFFSET 20"
+        );
+        assert_eq!(
+            statement.params,
+            [
+                SqlValue::Text("user:user_1".to_string()),
+                SqlValue::Text("shop".to_string())
+            ]
+        );
+        assert!(SqliteQuery::select("store; DROP TABLE user", &query).is_err());
+        Ok(())
+    }
+}
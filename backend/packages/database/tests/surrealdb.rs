@@ -6,17 +6,17 @@ mod tests {
     use super::*;
 
     use database::{
-        database::{DatabaseClient, Sources},
+        database::{DatabaseClient, DatabaseType, Sources, SurrealDb},
         interface::DBInterface as _,
+        query::{Columns, Filter},
+        transaction::Transaction,
     };
     use environment::Environment;
+    use errors::Error::DataExist;
+    use serde_json::json;
+    use uuid::Uuid;
 
-    use surrealdb::{
-        engine::remote::ws::{Client, Ws},
-        opt::auth::Root,
-        sql::Thing,
-        Surreal,
-    };
+    use surrealdb::{engine::any::Any, sql::Thing, Surreal};
 
     use tokio::test;
 
@@ -39,28 +39,15 @@ mod tests {
 
     async fn setup_db() -> Result<DatabaseClient> {
         let mut surreal_db = database::database::DatabaseSource {
-            db_type: database::database::DatabaseType::SurrealDB,
+            db_type: DatabaseType::from_env(&Environment::new())?,
         };
 
         let data = surreal_db.connect().await?;
         Ok(data)
     }
 
-    async fn setup_direct_db() -> Result<Surreal<Client>> {
-        let env = Environment::new();
-        let hostname = format!("{}:{}", env.db_host, env.db_port);
-        let client = Surreal::new::<Ws>(hostname).await?;
-
-        client
-            .signin(Root {
-                username: &env.db_user,
-                password: &env.db_pass,
-            })
-            .await?;
-
-        client.use_ns(env.db_namespace).use_db(env.db_name).await?;
-
-        Ok(client)
+    async fn setup_direct_db() -> Result<Surreal<Any>> {
+        SurrealDb::open(&DatabaseType::from_env(&Environment::new())?).await
     }
 
     #[test]
@@ -97,7 +84,7 @@ mod tests {
         };
 
         let success = db
-            .update_record("2", "test_update_table", updated_record)
+            .update_record("test_update_table:2", "test_update_table", updated_record)
             .await?;
         assert!(success);
 
@@ -157,7 +144,11 @@ mod tests {
             .await?;
 
         let records: Vec<ResultTestRecord> = db
-            .select_where("test_select_where_table", "name='Test'", "name")
+            .select_where(
+                "test_select_where_table",
+                Filter::eq("name", "Test"),
+                Columns::Only(&["name"]),
+            )
             .await?;
 
         assert_eq!(records.len(), 1);
@@ -168,4 +159,106 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    async fn test_memory_engine_is_shared() -> Result<()> {
+        let mut source = database::database::DatabaseSource {
+            db_type: DatabaseType::SurrealMemory,
+        };
+        let db = source.connect().await?;
+
+        SurrealDb::open(&DatabaseType::SurrealMemory)
+            .await?
+            .query("CREATE test_memory_table:1 SET name = 'Test'")
+            .await?;
+
+        let records: Vec<ResultTestRecord> = db.select("test_memory_table").await?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "Test");
+
+        db.delete("test_memory_table:1").await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_audit_events_are_append_only() -> Result<()> {
+        let db = setup_db().await?;
+        let key = Uuid::new_v4().simple().to_string();
+        let id = format!("audit_event:{}", key);
+
+        let mut transaction = Transaction::begin();
+        transaction.insert("audit_event", json!({ "id": key, "event_type": "login" }))?;
+        db.commit(transaction).await?;
+
+        assert!(db
+            .update_record(&id, "audit_event", json!({ "event_type": "logout" }))
+            .await
+            .is_err());
+        assert!(db.delete(&id).await.is_err());
+
+        let event_types: Vec<String> = setup_direct_db()
+            .await?
+            .query("SELECT VALUE event_type FROM type::thing('audit_event', $key)")
+            .bind(("key", key))
+            .await?
+            .take(0)?;
+        assert_eq!(event_types, vec!["login".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unique_fields_are_enforced() -> Result<()> {
+        let db = setup_db().await?;
+        let name = format!("unique{}", Uuid::new_v4().simple());
+        let email = format!("{}@example.com", name);
+
+        let mut transaction = Transaction::begin();
+        transaction.insert("user", json!({ "username": name, "email": email }))?;
+        db.commit(transaction).await?;
+
+        // Without a check in the transaction only the indexes stand in the way
+        let mut transaction = Transaction::begin();
+        transaction.insert(
+            "user",
+            json!({ "username": format!("{}x", name), "email": email }),
+        )?;
+        let duplicate_email = db.commit(transaction).await;
+
+        let mut transaction = Transaction::begin();
+        transaction.insert(
+            "user",
+            json!({ "username": name, "email": format!("x{}", email) }),
+        )?;
+        let duplicate_username = db.commit(transaction).await;
+
+        let users: Vec<serde_json::Value> = db
+            .select_where(
+                "user",
+                Filter::eq("email", email.as_str()),
+                Columns::Only(&["username"]),
+            )
+            .await?;
+        setup_direct_db()
+            .await?
+            .query("DELETE user WHERE username CONTAINS $name")
+            .bind(("name", name))
+            .await?;
+
+        assert!(matches!(duplicate_email, Err(DataExist(_))));
+        assert!(matches!(duplicate_username, Err(DataExist(_))));
+        assert_eq!(users.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unsupported_engine() {
+        let env = Environment {
+            db_engine: "postgres".to_string(),
+            ..Environment::new()
+        };
+        assert!(matches!(
+            DatabaseType::from_env(&env),
+            Err(errors::Error::UnsupportedEngine(_))
+        ));
+    }
 }
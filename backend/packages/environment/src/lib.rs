@@ -9,17 +9,45 @@ pub struct Environment {
     pub db_pass: String,
     pub db_name: String,
     pub db_namespace: String,
+    pub db_engine: String,
+    pub db_path: String,
     pub host_ip: String,
     pub host_port: String,
     pub refresh_token_private_key: String,
     pub refresh_token_public_key: String,
     pub access_token_private_key: String,
     pub access_token_public_key: String,
+    pub access_token_kid: String,
+    pub refresh_token_kid: String,
+    pub jwt_keyring_path: String,
+    pub jwt_key_grace_period: i64,
+    pub access_token_max_age: i64,
+    pub refresh_token_max_age: i64,
+    pub login_max_attempts: i64,
+    pub login_ip_max_attempts: i64,
+    pub login_lockout_max_age: i64,
+    pub trusted_proxies: String,
+    pub password_min_length: usize,
+    pub password_min_character_classes: usize,
+    pub breached_passwords_path: String,
+    pub oidc_providers_path: String,
+    pub webauthn_rp_id: String,
+    pub webauthn_rp_name: String,
+    pub webauthn_origin: String,
     pub redis_host: String,
     pub redis_username: String,
     pub redis_password: String,
     pub redis_port: String,
     pub host_name: String,
+    pub password_reset_url: String,
+    pub magic_link_url: String,
+    pub mail_transport: String,
+    pub mail_from: String,
+    pub mail_file_path: String,
+    pub smtp_host: String,
+    pub smtp_port: String,
+    pub smtp_username: String,
+    pub smtp_password: String,
     pub gcp_credentials: String,
     pub env: String,
     pub storage_bucket: String,
@@ -38,6 +66,14 @@ impl Environment {
         let db_pass = env::var("DB_PASS").unwrap_or(String::from("none"));
         let db_name = env::var("DB_NAME").unwrap_or(String::from("none"));
         let db_namespace = env::var("DB_NAMESPACE").unwrap_or(String::from("none"));
+        let db_engine = env::var("DB_ENGINE").unwrap_or(String::from("surrealdb"));
+        let db_path = env::var("DB_PATH").unwrap_or(String::from("none"));
+
+        let oidc_providers_path = env::var("OIDC_PROVIDERS_PATH").unwrap_or(String::from("none"));
+
+        let webauthn_rp_id = env::var("WEBAUTHN_RP_ID").unwrap_or(String::from("none"));
+        let webauthn_rp_name = env::var("WEBAUTHN_RP_NAME").unwrap_or(String::from("none"));
+        let webauthn_origin = env::var("WEBAUTHN_ORIGIN").unwrap_or(String::from("none"));
 
         let redis_host = env::var("REDIS_HOST").unwrap_or(String::from("none"));
         let redis_username = env::var("REDIS_USERNAME").unwrap_or(String::from("none"));
@@ -55,8 +91,55 @@ impl Environment {
             env::var("ACCESS_TOKEN_PRIVATE_KEY").unwrap_or(String::from("none"));
         let access_token_public_key =
             env::var("ACCESS_TOKEN_PUBLIC_KEY").unwrap_or(String::from("none"));
+        let access_token_kid = env::var("ACCESS_TOKEN_KID").unwrap_or(String::from("access-1"));
+        let refresh_token_kid = env::var("REFRESH_TOKEN_KID").unwrap_or(String::from("refresh-1"));
+        let jwt_keyring_path = env::var("JWT_KEYRING_PATH").unwrap_or(String::from("none"));
+        let jwt_key_grace_period = env::var("JWT_KEY_GRACE_PERIOD")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(60 * 24 * 7);
+        let access_token_max_age = env::var("ACCESS_TOKEN_MAXAGE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(60);
+        let refresh_token_max_age = env::var("REFRESH_TOKEN_MAXAGE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(60 * 24 * 7);
+        let login_max_attempts = env::var("LOGIN_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(5);
+        let login_ip_max_attempts = env::var("LOGIN_IP_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(20);
+        let login_lockout_max_age = env::var("LOGIN_LOCKOUT_MAXAGE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(15);
+        let trusted_proxies = env::var("TRUSTED_PROXIES").unwrap_or(String::from("none"));
+        let password_min_length = env::var("PASSWORD_MIN_LENGTH")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(8);
+        let password_min_character_classes = env::var("PASSWORD_MIN_CHARACTER_CLASSES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(1);
+        let breached_passwords_path =
+            env::var("BREACHED_PASSWORDS_PATH").unwrap_or(String::from("none"));
 
         let host_name = env::var("HOST_NAME").unwrap_or(String::from("none"));
+        let password_reset_url = env::var("PASSWORD_RESET_URL").unwrap_or(String::from("none"));
+        let magic_link_url = env::var("MAGIC_LINK_URL").unwrap_or(String::from("none"));
+        let mail_transport = env::var("MAIL_TRANSPORT").unwrap_or(String::from("smtp"));
+        let mail_from = env::var("MAIL_FROM").unwrap_or(String::from("none"));
+        let mail_file_path = env::var("MAIL_FILE_PATH").unwrap_or(String::from("none"));
+        let smtp_host = env::var("SMTP_HOST").unwrap_or(String::from("none"));
+        let smtp_port = env::var("SMTP_PORT").unwrap_or(String::from("none"));
+        let smtp_username = env::var("SMTP_USERNAME").unwrap_or(String::from("none"));
+        let smtp_password = env::var("SMTP_PASSWORD").unwrap_or(String::from("none"));
         let gcp_credentials = env::var("GCP_CREDENTIALS_PATH").unwrap_or(String::from("none"));
         let env = env::var("RUNNING_ENVIRONMENT").unwrap_or(String::from("none"));
         let storage_bucket = env::var("STORAGE_BUCKET").unwrap_or(String::from("none"));
@@ -71,17 +154,45 @@ impl Environment {
             db_pass,
             db_name,
             db_namespace,
+            db_engine,
+            db_path,
             redis_host,
             redis_username,
             redis_password,
             redis_port,
             host_name,
+            password_reset_url,
+            magic_link_url,
+            mail_transport,
+            mail_from,
+            mail_file_path,
+            smtp_host,
+            smtp_port,
+            smtp_username,
+            smtp_password,
             host_ip,
             host_port,
             refresh_token_private_key,
             refresh_token_public_key,
             access_token_private_key,
             access_token_public_key,
+            access_token_kid,
+            refresh_token_kid,
+            jwt_keyring_path,
+            jwt_key_grace_period,
+            access_token_max_age,
+            refresh_token_max_age,
+            login_max_attempts,
+            login_ip_max_attempts,
+            login_lockout_max_age,
+            trusted_proxies,
+            password_min_length,
+            password_min_character_classes,
+            breached_passwords_path,
+            oidc_providers_path,
+            webauthn_rp_id,
+            webauthn_rp_name,
+            webauthn_origin,
             gcp_credentials,
             env,
             storage_bucket,
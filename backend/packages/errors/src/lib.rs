@@ -2,7 +2,7 @@ use std::string::FromUtf8Error;
 
 use axum::{
     body::Body,
-    http::{Response, StatusCode},
+    http::{header, HeaderValue, Response, StatusCode},
     response::IntoResponse,
 };
 use redis::RedisError;
@@ -32,6 +32,10 @@ pub enum Error {
     UnsupportedEngine(String),
     TcpErrorConnection(String),
     DataNotValidate(String),
+    /// A transaction collided with a concurrent one and can be retried
+    TransactionConflict(String),
+    TooManyAttempts(i64),
+    PasswordRejected(ValidationErrors),
 }
 
 impl core::fmt::Display for Error {
@@ -98,12 +102,35 @@ impl IntoResponse for Error {
             Error::UserNotVerified(message) | Error::UnsupportedEngine(message) => {
                 (StatusCode::NOT_ACCEPTABLE, message.clone())
             }
+            Error::TransactionConflict(message) => (StatusCode::CONFLICT, message.clone()),
+            Error::TooManyAttempts(retry_after) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Too many failed attempts, retry in {} seconds", retry_after),
+            ),
+            Error::PasswordRejected(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Password does not meet the password policy".to_string(),
+            ),
         };
 
-        let body = Body::from(json!({ "status": "failed", "error": error_message }).to_string());
+        let body = match &self {
+            // Every rule the password broke is listed so clients can show them all at once
+            Error::PasswordRejected(errors) => json!({
+                "status": "failed",
+                "error": error_message,
+                "errors": errors
+            }),
+            _ => json!({ "status": "failed", "error": error_message }),
+        };
+        let body = Body::from(body.to_string());
 
         let mut response = Response::new(body);
         *response.status_mut() = status;
+        if let Error::TooManyAttempts(retry_after) = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
         response
     }
 }
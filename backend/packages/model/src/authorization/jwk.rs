@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/* Which kind of token a signing key is used for */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyUse {
+    Access,
+    Refresh,
+}
+
+/* A key pair entry of the JWT keyring file, PEMs are base64 encoded like in the environment */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SigningKeyConfig {
+    pub kid: String,
+    #[serde(rename = "use")]
+    pub key_use: KeyUse,
+    #[serde(default)]
+    pub private_key: Option<String>,
+    pub public_key: String,
+    #[serde(default)]
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyringConfig {
+    pub keys: Vec<SigningKeyConfig>,
+}
+
+/* RSA public key in JSON Web Key format (RFC 7517) */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub alg: String,
+    pub kid: String,
+    pub n: String,
+    pub e: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
@@ -1 +1,9 @@
+pub mod jwk;
+pub mod oidc;
+pub mod permission;
+pub mod role;
+pub mod scope;
+pub mod session;
 pub mod token;
+pub mod two_factor;
+pub mod webauthn;
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+fn default_scopes() -> Vec<String> {
+    vec![
+        "openid".to_string(),
+        "email".to_string(),
+        "profile".to_string(),
+    ]
+}
+
+/* An OpenID Connect issuer users can log in with */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OidcProviderConfig {
+    /// Name used in the login URLs, e.g. `google`
+    pub name: String,
+    /// Issuer URL, its discovery document lives at `{issuer}/.well-known/openid-configuration`
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OidcProvidersConfig {
+    pub providers: Vec<OidcProviderConfig>,
+}
+
+/* A login started with a provider, kept until its callback comes back */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OidcAuthorization {
+    pub provider: String,
+    pub code_verifier: String,
+    pub nonce: String,
+}
+
+/* The user an ID token was issued for */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Named permissions a role can be granted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum Permission {
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:manage")]
+    UsersManage,
+    #[serde(rename = "users:impersonate")]
+    UsersImpersonate,
+    #[serde(rename = "roles:manage")]
+    RolesManage,
+    #[serde(rename = "catalog:moderate")]
+    CatalogModerate,
+    #[serde(rename = "finance:read")]
+    FinanceRead,
+    #[serde(rename = "audit:read")]
+    AuditRead,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 7] = [
+        Permission::UsersRead,
+        Permission::UsersManage,
+        Permission::UsersImpersonate,
+        Permission::RolesManage,
+        Permission::CatalogModerate,
+        Permission::FinanceRead,
+        Permission::AuditRead,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::UsersRead => "users:read",
+            Permission::UsersManage => "users:manage",
+            Permission::UsersImpersonate => "users:impersonate",
+            Permission::RolesManage => "roles:manage",
+            Permission::CatalogModerate => "catalog:moderate",
+            Permission::FinanceRead => "finance:read",
+            Permission::AuditRead => "audit:read",
+        }
+    }
+}
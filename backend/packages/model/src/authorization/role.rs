@@ -0,0 +1,59 @@
+use std::{fmt, str::FromStr};
+
+use errors::Error::{self, InvalidUserRole};
+use serde::{Deserialize, Serialize};
+
+use super::permission::Permission;
+
+/// Built-in roles. Additional roles are defined by admins in the `role` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    Customer,
+    Seller,
+    Admin,
+}
+
+impl UserRole {
+    pub const ALL: [UserRole; 3] = [UserRole::Customer, UserRole::Seller, UserRole::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Customer => "customer",
+            UserRole::Seller => "seller",
+            UserRole::Admin => "admin",
+        }
+    }
+
+    /// Permissions granted to the built-in role; admins hold every permission.
+    pub fn permissions(&self) -> Vec<Permission> {
+        match self {
+            UserRole::Customer | UserRole::Seller => Vec::new(),
+            UserRole::Admin => Permission::ALL.to_vec(),
+        }
+    }
+
+    /// Whether the role can be picked through public registration.
+    pub fn is_self_assignable(&self) -> bool {
+        matches!(self, UserRole::Customer | UserRole::Seller)
+    }
+}
+
+impl FromStr for UserRole {
+    type Err = Error;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        match role {
+            "customer" => Ok(UserRole::Customer),
+            "seller" => Ok(UserRole::Seller),
+            "admin" => Ok(UserRole::Admin),
+            _ => Err(InvalidUserRole(format!("Unknown role '{}'", role))),
+        }
+    }
+}
+
+impl fmt::Display for UserRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
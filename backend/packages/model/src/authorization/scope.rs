@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Scopes an API key can be limited to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum ApiKeyScope {
+    #[serde(rename = "profile:read")]
+    ProfileRead,
+    #[serde(rename = "profile:write")]
+    ProfileWrite,
+    #[serde(rename = "catalog:read")]
+    CatalogRead,
+    #[serde(rename = "catalog:write")]
+    CatalogWrite,
+    #[serde(rename = "inventory:write")]
+    InventoryWrite,
+    #[serde(rename = "orders:read")]
+    OrdersRead,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::ProfileRead => "profile:read",
+            ApiKeyScope::ProfileWrite => "profile:write",
+            ApiKeyScope::CatalogRead => "catalog:read",
+            ApiKeyScope::CatalogWrite => "catalog:write",
+            ApiKeyScope::InventoryWrite => "inventory:write",
+            ApiKeyScope::OrdersRead => "orders:read",
+        }
+    }
+}
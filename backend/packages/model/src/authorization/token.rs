@@ -7,6 +7,15 @@ pub struct TokenDetails {
     pub user_id: String,
     pub expires_in: Option<i64>,
     pub user_role: String,
+    /// User acting on behalf of `user_id` when the token was minted for impersonation
+    #[serde(default)]
+    pub impersonator: Option<String>,
+}
+
+/// Party acting on behalf of the token subject, the `act` claim of RFC 8693.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorClaim {
+    pub sub: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,4 +26,6 @@ pub struct TokenClaims {
     pub iat: i64,
     pub nbf: i64,
     pub user_role: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
 }
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/* A login whose password was verified and which now waits on its second factor */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginChallenge {
+    pub user_id: String,
+    pub user_role: String,
+    pub email: String,
+    /// The user is required to use 2FA but has not enrolled yet
+    pub setup_required: bool,
+}
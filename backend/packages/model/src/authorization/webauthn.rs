@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/* A new credential whose registration ceremony has been verified, ready to be stored */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VerifiedCredential {
+    /// Base64url credential id chosen by the authenticator
+    pub credential_id: String,
+    /// Base64url COSE public key of the credential
+    pub public_key: String,
+    pub sign_count: u32,
+}
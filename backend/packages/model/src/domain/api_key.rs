@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use surrealdb::sql::Thing;
+
+use crate::authorization::scope::ApiKeyScope;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    #[serde(deserialize_with = "thing_to_string")]
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub key_hash: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+fn thing_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let t = Thing::deserialize(deserializer)?;
+    Ok(t.to_raw())
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+/* Security relevant actions recorded in the audit log */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventType {
+    Login,
+    TokenRejected,
+    ProfileUpdated,
+    PasswordChanged,
+    UserCreated,
+    UserUnlocked,
+    UserDisabled,
+    UserEnabled,
+    UserVerified,
+    UserLoggedOut,
+    RoleChanged,
+    TwoFactorRequirementChanged,
+    RoleCreated,
+    RoleUpdated,
+    RoleDeleted,
+    ImpersonationStarted,
+    ImpersonatedRequest,
+    PasskeyAdded,
+    PasskeyRemoved,
+}
+
+impl AuditEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEventType::Login => "login",
+            AuditEventType::TokenRejected => "token_rejected",
+            AuditEventType::ProfileUpdated => "profile_updated",
+            AuditEventType::PasswordChanged => "password_changed",
+            AuditEventType::UserCreated => "user_created",
+            AuditEventType::UserUnlocked => "user_unlocked",
+            AuditEventType::UserDisabled => "user_disabled",
+            AuditEventType::UserEnabled => "user_enabled",
+            AuditEventType::UserVerified => "user_verified",
+            AuditEventType::UserLoggedOut => "user_logged_out",
+            AuditEventType::RoleChanged => "role_changed",
+            AuditEventType::TwoFactorRequirementChanged => "two_factor_requirement_changed",
+            AuditEventType::RoleCreated => "role_created",
+            AuditEventType::RoleUpdated => "role_updated",
+            AuditEventType::RoleDeleted => "role_deleted",
+            AuditEventType::ImpersonationStarted => "impersonation_started",
+            AuditEventType::ImpersonatedRequest => "impersonated_request",
+            AuditEventType::PasskeyAdded => "passkey_added",
+            AuditEventType::PasskeyRemoved => "passkey_removed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEvent {
+    #[serde(deserialize_with = "thing_to_string")]
+    pub id: String,
+    pub event_type: AuditEventType,
+    pub outcome: AuditOutcome,
+    /// User who performed the action, if known
+    pub actor: Option<String>,
+    /// User or role the action was performed on
+    pub target: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/* Conditions the audit log is searched by, all optional */
+#[derive(Debug, Clone, Default)]
+pub struct AuditEventFilter {
+    /// Matches events where the user is either the actor or the target
+    pub user: Option<String>,
+    pub event_type: Option<AuditEventType>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+fn thing_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let t = Thing::deserialize(deserializer)?;
+    Ok(t.to_raw())
+}
@@ -1,2 +1,7 @@
+pub mod api_key;
+pub mod audit_event;
+pub mod page;
+pub mod passkey;
+pub mod role;
 pub mod store;
 pub mod user;
@@ -0,0 +1,66 @@
+use serde::Serialize;
+
+/* Where a page starts in the ordered results */
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageStart {
+    /// Skips that many records
+    Offset(u32),
+    /// Continues after the last record of a previous page, stable while records are added
+    After(String),
+}
+
+/* Size and position of the page to fetch */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRequest {
+    pub limit: u32,
+    pub start: PageStart,
+}
+
+impl PageRequest {
+    pub fn offset(limit: u32, offset: u32) -> Self {
+        PageRequest {
+            limit,
+            start: PageStart::Offset(offset),
+        }
+    }
+
+    pub fn after(limit: u32, cursor: impl Into<String>) -> Self {
+        PageRequest {
+            limit,
+            start: PageStart::After(cursor.into()),
+        }
+    }
+
+    /// Page of a listing endpoint, a cursor takes precedence over the page number starting at 1.
+    ///
+    /// Pages too far out to address are capped at the largest offset, which is past every record.
+    pub fn from_query(page: Option<u32>, per_page: u32, cursor: Option<String>) -> Self {
+        match cursor {
+            Some(cursor) => Self::after(per_page, cursor),
+            None => Self::offset(
+                per_page,
+                (page.unwrap_or(1).max(1) - 1).saturating_mul(per_page),
+            ),
+        }
+    }
+}
+
+/* One page of records */
+#[derive(Serialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of records matching the query across all pages
+    pub total: u64,
+    /// Cursor of the next page, `None` on the last one
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
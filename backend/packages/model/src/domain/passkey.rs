@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use surrealdb::sql::Thing;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Passkey {
+    #[serde(deserialize_with = "thing_to_string")]
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    /// Base64url credential id chosen by the authenticator
+    pub credential_id: String,
+    /// Base64url COSE public key of the credential
+    pub public_key: String,
+    pub sign_count: u32,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+fn thing_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let t = Thing::deserialize(deserializer)?;
+    Ok(t.to_raw())
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use surrealdb::sql::Thing;
+
+use crate::authorization::permission::Permission;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Role {
+    #[serde(deserialize_with = "thing_to_string")]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn thing_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let t = Thing::deserialize(deserializer)?;
+    Ok(t.to_raw())
+}
@@ -18,10 +18,31 @@ pub struct User {
     pub role: String,
     pub password: String,
     pub verified: bool,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub phone_number: Option<String>,
+    #[serde(default)]
+    pub totp_secret: Option<String>,
+    #[serde(default)]
+    pub recovery_codes: Vec<String>,
+    #[serde(default)]
+    pub two_factor_required: bool,
+    #[serde(default)]
+    pub identities: Vec<ExternalIdentity>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/* An account at an OpenID Connect provider linked to a user */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExternalIdentity {
+    pub issuer: String,
+    pub subject: String,
+}
+
 fn thing_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
@@ -1,2 +1,6 @@
+pub mod api_key;
+pub mod audit_event;
+pub mod passkey;
+pub mod role;
 pub mod store;
 pub mod user;
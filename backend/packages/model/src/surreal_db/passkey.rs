@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Passkey {
+    pub id: Option<Thing>,
+    pub user_id: String,
+    pub name: String,
+    pub credential_id: String,
+    pub public_key: String,
+    pub sign_count: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
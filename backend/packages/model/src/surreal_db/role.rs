@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+use crate::authorization::permission::Permission;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Role {
+    pub id: Option<Thing>,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
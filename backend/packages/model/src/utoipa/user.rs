@@ -10,6 +10,8 @@ pub struct User {
     pub username: String,
     pub email: String,
     pub role: String,
+    pub full_name: Option<String>,
+    pub phone_number: Option<String>,
     #[serde(deserialize_with = "datetime_to_string")]
     pub created_at: String,
     #[serde(deserialize_with = "datetime_to_string")]
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::authorization::scope::ApiKeyScope;
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct ApiKey {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    #[validate(length(min = 1, message = "An API key needs at least one scope"))]
+    pub scopes: Vec<ApiKeyScope>,
+}
@@ -0,0 +1,2 @@
+pub mod api_key_request;
+pub mod api_key_response;
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::domain::audit_event::AuditEventType;
+
+/* Query of the admin audit log listing */
+#[derive(Serialize, Deserialize, Debug, Default, Validate)]
+pub struct AuditEventQuery {
+    /// User id matched against both actor and target, e.g. `user:user_xxx`
+    pub user: Option<String>,
+    pub event_type: Option<AuditEventType>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[validate(range(min = 1))]
+    pub page: Option<u32>,
+    #[validate(range(min = 1, max = 100))]
+    pub per_page: Option<u32>,
+    /// `next_cursor` of the previous page, takes precedence over `page`
+    pub cursor: Option<String>,
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct RefreshToken {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct ResendVerification {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct ForgotPassword {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct MagicLinkRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct ResetPassword {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TwoFactorCode {
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TwoFactorChallenge {
+    pub challenge_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TwoFactorLogin {
+    pub challenge_token: String,
+    /// A code from the authenticator app or one of the recovery codes
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct DisableTwoFactor {
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TwoFactorRequirement {
+    pub required: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct UnlockAccount {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OidcCallback {
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+}
@@ -0,0 +1,2 @@
+pub mod passkey_request;
+pub mod passkey_response;
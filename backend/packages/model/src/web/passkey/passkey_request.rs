@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// Attestation returned by `navigator.credentials.create()`, binary fields base64url encoded.
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct PasskeyRegistration {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+/// Assertion returned by `navigator.credentials.get()`, binary fields base64url encoded.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PasskeyLogin {
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
@@ -0,0 +1,2 @@
+pub mod role_request;
+pub mod role_response;
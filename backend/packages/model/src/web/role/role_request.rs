@@ -0,0 +1,27 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::authorization::permission::Permission;
+
+pub static RE_ROLE_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z][a-z0-9_]{2,31}$").unwrap());
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct Role {
+    #[validate(regex(path = *RE_ROLE_NAME))]
+    pub name: String,
+    #[validate(length(max = 255))]
+    pub description: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct RoleUpdate {
+    #[validate(length(max = 255))]
+    pub description: Option<String>,
+    pub permissions: Option<Vec<Permission>>,
+}
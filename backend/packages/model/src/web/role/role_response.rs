@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use crate::authorization::permission::Permission;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Role {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+    pub built_in: bool,
+}
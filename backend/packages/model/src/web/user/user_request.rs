@@ -1,7 +1,9 @@
 use axum::Json;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
-use validator::{Validate, ValidationError};
+use validator::Validate;
+
+use crate::web::role::role_request::RE_ROLE_NAME;
 
 #[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
 pub struct User {
@@ -9,9 +11,8 @@ pub struct User {
     pub username: String,
     #[validate(email)]
     pub email: String,
-    #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
     pub password: String,
-    #[validate(custom(function = "validate_role"))]
+    #[validate(regex(path = *RE_ROLE_NAME, code = "invalid_role"))]
     pub role: String,
 }
 
@@ -23,6 +24,54 @@ pub struct UserLogin {
     pub password: String,
 }
 
+/* Fields a user may change on their own profile, anything else is rejected */
+#[derive(Serialize, Deserialize, Debug, Default, Validate, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UserUpdate {
+    #[validate(length(min = 5))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[validate(email)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_name: Option<String>,
+    #[validate(length(min = 6, max = 20))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct ChangePassword {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/* Query of the admin user listing */
+#[derive(Serialize, Deserialize, Debug, Default, Validate)]
+pub struct UserListQuery {
+    /// Matched case-insensitively against username and email
+    pub search: Option<String>,
+    #[validate(range(min = 1))]
+    pub page: Option<u32>,
+    #[validate(range(min = 1, max = 100))]
+    pub per_page: Option<u32>,
+    /// `next_cursor` of the previous page, takes precedence over `page`
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct UserDisabled {
+    pub disabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct UserRoleUpdate {
+    #[validate(regex(path = *RE_ROLE_NAME, code = "invalid_role"))]
+    pub role: String,
+}
+
 impl From<Json<User>> for User {
     fn from(payload: Json<User>) -> Self {
         Self {
@@ -33,14 +82,3 @@ impl From<Json<User>> for User {
         }
     }
 }
-
-/// Validates that the given role is one of the predefined valid roles.
-fn validate_role(role: &str) -> Result<(), ValidationError> {
-    const VALID_ROLES: [&str; 3] = ["customer", "seller", "admin"];
-
-    if !VALID_ROLES.contains(&role) {
-        return Err(ValidationError::new("invalid_role"));
-    }
-
-    Ok(())
-}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use surrealdb::sql::Thing;
 
+use crate::domain::user::User as UserData;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct User {
     #[serde(deserialize_with = "thing_to_string")]
@@ -9,10 +11,50 @@ pub struct User {
     pub username: String,
     pub email: String,
     pub role: String,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub phone_number: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/* A user as shown to admins, without any credentials */
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AdminUser {
+    pub id: String,
+    pub username: String,
+    pub email: String,
+    pub role: String,
+    pub verified: bool,
+    pub disabled: bool,
+    pub two_factor_enabled: bool,
+    pub two_factor_required: bool,
+    pub full_name: Option<String>,
+    pub phone_number: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl From<UserData> for AdminUser {
+    fn from(user: UserData) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            role: user.role,
+            verified: user.verified,
+            disabled: user.disabled,
+            two_factor_enabled: user.totp_secret.is_some(),
+            two_factor_required: user.two_factor_required,
+            full_name: user.full_name,
+            phone_number: user.phone_number,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UserResponseUsername {
     pub username: String,
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use model::domain::page::{PageRequest, PageStart};
+
+    #[test]
+    fn test_from_query() {
+        assert_eq!(
+            PageRequest::from_query(None, 20, None),
+            PageRequest::offset(20, 0)
+        );
+        assert_eq!(
+            PageRequest::from_query(Some(3), 20, None),
+            PageRequest::offset(20, 40)
+        );
+        assert_eq!(
+            PageRequest::from_query(Some(3), 20, Some("cursor".to_string())),
+            PageRequest::after(20, "cursor")
+        );
+    }
+
+    #[test]
+    fn test_from_query_does_not_overflow() {
+        let page = PageRequest::from_query(Some(u32::MAX), 100, None);
+        assert_eq!(page.start, PageStart::Offset(u32::MAX));
+    }
+}
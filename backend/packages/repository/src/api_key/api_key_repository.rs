@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use database::database::DatabaseClient;
+
+use errors::Result;
+
+use model::domain::api_key::ApiKey;
+
+use serde_json::Value;
+
+#[derive(Clone, Debug)]
+pub struct ApiKeyRepository {
+    pub db: Arc<DatabaseClient>,
+}
+
+#[async_trait]
+pub trait ApiKeyRepositoryTrait {
+    async fn insert_data(&self, data: ApiKey) -> Result<String>;
+    async fn get_by_id(&self, id: &str) -> Result<Option<ApiKey>>;
+    async fn get_by_user(&self, user_id: &str) -> Result<Vec<ApiKey>>;
+    async fn update_data(&self, id: &str, data: Value) -> Result<bool>;
+    async fn delete_data(&self, id: &str) -> Result<bool>;
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing;
+
+use super::api_key_repository::{ApiKeyRepository, ApiKeyRepositoryTrait};
+use database::{
+    interface::DBInterface as _,
+    query::{Columns, Direction, Filter, Query, Value as QueryValue},
+};
+use errors::{Error::DataNotAvailable, Result};
+use model::{domain::api_key::ApiKey, surreal_db::api_key::ApiKey as ApiKeySurreal};
+
+#[async_trait]
+impl ApiKeyRepositoryTrait for ApiKeyRepository {
+    #[tracing::instrument(err, skip_all)]
+    async fn insert_data(&self, data: ApiKey) -> Result<String> {
+        let result: Option<ApiKeySurreal> = self.db.insert_record("api_key", data).await?;
+
+        result
+            .and_then(|api_key| api_key.id.map(|id| id.id.to_string()))
+            .ok_or_else(|| DataNotAvailable("id".to_string()))
+            .map(|id| id.replace("⟨", "").replace("⟩", ""))
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_by_id(&self, id: &str) -> Result<Option<ApiKey>> {
+        let api_keys: Vec<ApiKey> = self
+            .db
+            .select_where(
+                "api_key",
+                Filter::eq("id", QueryValue::record(id)),
+                Columns::All,
+            )
+            .await?;
+
+        Ok(api_keys.into_iter().next())
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_by_user(&self, user_id: &str) -> Result<Vec<ApiKey>> {
+        self.db
+            .select_where(
+                "api_key",
+                Query::new(Filter::eq("user_id", user_id)).order_by("created_at", Direction::Desc),
+                Columns::All,
+            )
+            .await
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn update_data(&self, id: &str, data: Value) -> Result<bool> {
+        self.db.update_record(id, "api_key", data).await
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn delete_data(&self, id: &str) -> Result<bool> {
+        self.db.delete(id).await
+    }
+}
@@ -0,0 +1,2 @@
+pub mod api_key_repository;
+pub mod api_key_repository_impl;
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use database::database::DatabaseClient;
+
+use errors::Result;
+
+use model::domain::{
+    audit_event::{AuditEvent, AuditEventFilter},
+    page::{Page, PageRequest},
+};
+
+/* The audit log is append-only, the database refuses updates and deletes of its events */
+#[derive(Clone, Debug)]
+pub struct AuditEventRepository {
+    pub db: Arc<DatabaseClient>,
+}
+
+#[async_trait]
+pub trait AuditEventRepositoryTrait {
+    async fn insert_data(&self, data: AuditEvent) -> Result<String>;
+    async fn get_page(
+        &self,
+        filter: &AuditEventFilter,
+        page: &PageRequest,
+    ) -> Result<Page<AuditEvent>>;
+}
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use tracing;
+
+use super::audit_event_repository::{AuditEventRepository, AuditEventRepositoryTrait};
+use database::{
+    interface::DBInterface as _,
+    query::{Direction, Filter, Query},
+};
+use errors::{Error::DataNotAvailable, Result};
+use model::{
+    domain::{
+        audit_event::{AuditEvent, AuditEventFilter},
+        page::{Page, PageRequest},
+    },
+    surreal_db::audit_event::AuditEvent as AuditEventSurreal,
+};
+
+/// Builds the query filter of an audit event filter.
+fn query_filter(filter: &AuditEventFilter) -> Filter {
+    let mut query_filter = Filter::All;
+
+    if let Some(user) = &filter.user {
+        query_filter = query_filter.and(Filter::eq("actor", user).or(Filter::eq("target", user)));
+    }
+    if let Some(event_type) = &filter.event_type {
+        query_filter = query_filter.and(Filter::eq("event_type", event_type.as_str()));
+    }
+    if filter.from.is_some() || filter.to.is_some() {
+        query_filter = query_filter.and(Filter::range("created_at", filter.from, filter.to));
+    }
+
+    query_filter
+}
+
+#[async_trait]
+impl AuditEventRepositoryTrait for AuditEventRepository {
+    #[tracing::instrument(err, skip_all)]
+    async fn insert_data(&self, data: AuditEvent) -> Result<String> {
+        let result: Option<AuditEventSurreal> = self.db.insert_record("audit_event", data).await?;
+
+        result
+            .and_then(|event| event.id.map(|id| id.id.to_string()))
+            .ok_or_else(|| DataNotAvailable("id".to_string()))
+            .map(|id| id.replace("⟨", "").replace("⟩", ""))
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_page(
+        &self,
+        filter: &AuditEventFilter,
+        page: &PageRequest,
+    ) -> Result<Page<AuditEvent>> {
+        self.db
+            .select_page(
+                "audit_event",
+                Query::new(query_filter(filter)).order_by("created_at", Direction::Desc),
+                page,
+            )
+            .await
+    }
+}
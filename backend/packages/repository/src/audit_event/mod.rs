@@ -0,0 +1,2 @@
+pub mod audit_event_repository;
+pub mod audit_event_repository_impl;
@@ -0,0 +1,2 @@
+pub mod passkey_repository;
+pub mod passkey_repository_impl;
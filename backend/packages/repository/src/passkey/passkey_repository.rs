@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use database::database::DatabaseClient;
+
+use errors::Result;
+
+use model::domain::passkey::Passkey;
+
+use serde_json::Value;
+
+#[derive(Clone, Debug)]
+pub struct PasskeyRepository {
+    pub db: Arc<DatabaseClient>,
+}
+
+#[async_trait]
+pub trait PasskeyRepositoryTrait {
+    async fn insert_data(&self, data: Passkey) -> Result<String>;
+    async fn get_by_id(&self, id: &str) -> Result<Option<Passkey>>;
+    async fn get_by_credential_id(&self, credential_id: &str) -> Result<Option<Passkey>>;
+    async fn get_by_user(&self, user_id: &str) -> Result<Vec<Passkey>>;
+    async fn update_data(&self, id: &str, data: Value) -> Result<bool>;
+    async fn delete_data(&self, id: &str) -> Result<bool>;
+}
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing;
+
+use super::passkey_repository::{PasskeyRepository, PasskeyRepositoryTrait};
+use database::{
+    interface::DBInterface as _,
+    query::{Columns, Direction, Filter, Query, Value as QueryValue},
+};
+use errors::{Error::DataNotAvailable, Result};
+use model::{domain::passkey::Passkey, surreal_db::passkey::Passkey as PasskeySurreal};
+
+#[async_trait]
+impl PasskeyRepositoryTrait for PasskeyRepository {
+    #[tracing::instrument(err, skip_all)]
+    async fn insert_data(&self, data: Passkey) -> Result<String> {
+        let result: Option<PasskeySurreal> = self.db.insert_record("passkey", data).await?;
+
+        result
+            .and_then(|passkey| passkey.id.map(|id| id.id.to_string()))
+            .ok_or_else(|| DataNotAvailable("id".to_string()))
+            .map(|id| id.replace("⟨", "").replace("⟩", ""))
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_by_id(&self, id: &str) -> Result<Option<Passkey>> {
+        let passkeys: Vec<Passkey> = self
+            .db
+            .select_where(
+                "passkey",
+                Filter::eq("id", QueryValue::record(id)),
+                Columns::All,
+            )
+            .await?;
+
+        Ok(passkeys.into_iter().next())
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_by_credential_id(&self, credential_id: &str) -> Result<Option<Passkey>> {
+        let passkeys: Vec<Passkey> = self
+            .db
+            .select_where(
+                "passkey",
+                Filter::eq("credential_id", credential_id),
+                Columns::All,
+            )
+            .await?;
+
+        Ok(passkeys.into_iter().next())
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_by_user(&self, user_id: &str) -> Result<Vec<Passkey>> {
+        self.db
+            .select_where(
+                "passkey",
+                Query::new(Filter::eq("user_id", user_id)).order_by("created_at", Direction::Desc),
+                Columns::All,
+            )
+            .await
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn update_data(&self, id: &str, data: Value) -> Result<bool> {
+        self.db.update_record(id, "passkey", data).await
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn delete_data(&self, id: &str) -> Result<bool> {
+        self.db.delete(id).await
+    }
+}
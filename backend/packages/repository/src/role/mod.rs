@@ -0,0 +1,2 @@
+pub mod role_repository;
+pub mod role_repository_impl;
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use database::database::DatabaseClient;
+
+use errors::Result;
+
+use model::domain::role::Role;
+
+use serde_json::Value;
+
+#[derive(Clone, Debug)]
+pub struct RoleRepository {
+    pub db: Arc<DatabaseClient>,
+}
+
+#[async_trait]
+pub trait RoleRepositoryTrait {
+    async fn insert_data(&self, data: Role) -> Result<String>;
+    async fn get_all(&self) -> Result<Vec<Role>>;
+    async fn get_by_name(&self, name: &str) -> Result<Option<Role>>;
+    async fn update_data(&self, id: &str, data: Value) -> Result<bool>;
+    async fn delete_data(&self, id: &str) -> Result<bool>;
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing;
+
+use super::role_repository::{RoleRepository, RoleRepositoryTrait};
+use database::{
+    interface::DBInterface as _,
+    query::{Columns, Filter},
+};
+use errors::{Error::DataNotAvailable, Result};
+use model::{domain::role::Role, surreal_db::role::Role as RoleSurreal};
+
+#[async_trait]
+impl RoleRepositoryTrait for RoleRepository {
+    #[tracing::instrument(err, skip_all)]
+    async fn insert_data(&self, data: Role) -> Result<String> {
+        let result: Option<RoleSurreal> = self.db.insert_record("role", data).await?;
+
+        result
+            .and_then(|role| role.id.map(|id| id.id.to_string()))
+            .ok_or_else(|| DataNotAvailable("id".to_string()))
+            .map(|id| id.replace("⟨", "").replace("⟩", ""))
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_all(&self) -> Result<Vec<Role>> {
+        self.db.select("role").await
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn get_by_name(&self, name: &str) -> Result<Option<Role>> {
+        let roles: Vec<Role> = self
+            .db
+            .select_where("role", Filter::eq("name", name), Columns::All)
+            .await?;
+
+        Ok(roles.into_iter().next())
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn update_data(&self, id: &str, data: Value) -> Result<bool> {
+        self.db.update_record(id, "role", data).await
+    }
+
+    #[tracing::instrument(err, skip_all)]
+    async fn delete_data(&self, id: &str) -> Result<bool> {
+        self.db.delete(id).await
+    }
+}
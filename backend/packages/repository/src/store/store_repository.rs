@@ -2,11 +2,14 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use database::database::DatabaseClient;
+use database::{database::DatabaseClient, transaction::Transaction};
 
 use errors::Result;
 
-use model::domain::store::Store;
+use model::domain::{
+    page::{Page, PageRequest},
+    store::Store,
+};
 use serde_json::Value;
 
 #[derive(Clone, Debug)]
@@ -18,7 +21,11 @@ pub struct StoreRepository {
 pub trait StoreRepositoryTrait {
     async fn insert_data(&self, data: Store) -> Result<bool>;
     async fn get_by_user_id(&self, user_id: &str) -> Result<Vec<Store>>;
+    async fn get_page_by_user_id(&self, user_id: &str, page: &PageRequest) -> Result<Page<Store>>;
     async fn get_by_id(&self, id: &str) -> Result<Option<Store>>;
     async fn delete_data(&self, id: &str) -> Result<bool>;
     async fn update_data(&self, id: &str, data: Value) -> Result<bool>;
+    fn insert_data_in(&self, transaction: &mut Transaction, data: Store) -> Result<()>;
+    fn update_data_in(&self, transaction: &mut Transaction, id: &str, data: Value) -> Result<()>;
+    fn delete_data_in(&self, transaction: &mut Transaction, id: &str);
 }
@@ -1,6 +1,13 @@
 use async_trait::async_trait;
-use database::interface::DBInterface;
-use model::domain::store::Store;
+use database::{
+    interface::DBInterface,
+    query::{Columns, Direction, Filter, Query, Value as QueryValue},
+    transaction::Transaction,
+};
+use model::domain::{
+    page::{Page, PageRequest},
+    store::Store,
+};
 
 use super::store_repository::{StoreRepository, StoreRepositoryTrait};
 use errors::{Error::DataDuplicationError, Result};
@@ -20,17 +27,23 @@ impl StoreRepositoryTrait for StoreRepository {
         }
     }
     async fn get_by_user_id(&self, user_id: &str) -> Result<Vec<Store>> {
-        let filter = format!("user_id={}", user_id);
+        let filter = Filter::eq("user_id", QueryValue::record(user_id));
 
-        let stores: Vec<Store> = self.db.select_where("store", &filter, "*").await?;
+        let stores: Vec<Store> = self.db.select_where("store", filter, Columns::All).await?;
         Ok(stores)
     }
+    async fn get_page_by_user_id(&self, user_id: &str, page: &PageRequest) -> Result<Page<Store>> {
+        let query = Query::new(Filter::eq("user_id", QueryValue::record(user_id)))
+            .order_by("created_at", Direction::Desc);
+
+        self.db.select_page("store", query, page).await
+    }
     async fn get_by_id(&self, id: &str) -> Result<Option<Store>> {
-        let filter = format!("id={}", id);
+        let filter = Filter::eq("id", QueryValue::record(id));
 
         let stores: Option<Store> = self
             .db
-            .select_where("store", &filter, "*")
+            .select_where("store", filter, Columns::All)
             .await?
             .first()
             .cloned();
@@ -42,4 +55,13 @@ impl StoreRepositoryTrait for StoreRepository {
     async fn update_data(&self, id: &str, data: Value) -> Result<bool> {
         self.db.update_record(id, "store", data).await
     }
+    fn insert_data_in(&self, transaction: &mut Transaction, data: Store) -> Result<()> {
+        transaction.insert("store", data)
+    }
+    fn update_data_in(&self, transaction: &mut Transaction, id: &str, data: Value) -> Result<()> {
+        transaction.update(id, data)
+    }
+    fn delete_data_in(&self, transaction: &mut Transaction, id: &str) {
+        transaction.delete(id)
+    }
 }
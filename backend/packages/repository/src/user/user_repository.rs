@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use database::database::DatabaseClient;
+use database::{database::DatabaseClient, transaction::Transaction};
 
 use errors::Result;
 
@@ -19,4 +19,6 @@ pub struct UserRepository {
 pub trait UserRepositoryTrait {
     async fn insert_data(&self, data: User) -> Result<String>;
     async fn update_data(&self, id: &str, data: Value) -> Result<bool>;
+    fn insert_data_in(&self, transaction: &mut Transaction, data: User) -> Result<()>;
+    fn update_data_in(&self, transaction: &mut Transaction, id: &str, data: Value) -> Result<()>;
 }
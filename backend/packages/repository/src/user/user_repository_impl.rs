@@ -1,14 +1,24 @@
 use async_trait::async_trait;
-use serde_json::Value;
+use serde_json::{json, Value};
 use tracing;
 
 use super::user_repository::{UserRepository, UserRepositoryTrait};
-use database::interface::DBInterface as _;
+use database::{
+    interface::DBInterface as _,
+    query::{Columns, Direction, Filter, Query, Value as QueryValue},
+    transaction::Transaction,
+};
 use errors::{
-    Error::{DataDuplicationError, DataNotAvailable},
+    Error::{DataDuplicationError, DataExist, DataNotAvailable, DataNotValidate},
     Result,
 };
-use model::{domain::user::User, surreal_db::user::User as UserSurreal};
+use model::{
+    domain::{
+        page::{Page, PageRequest},
+        user::User,
+    },
+    surreal_db::user::User as UserSurreal,
+};
 
 #[async_trait]
 impl UserRepositoryTrait for UserRepository {
@@ -26,19 +36,48 @@ impl UserRepositoryTrait for UserRepository {
     async fn update_data(&self, id: &str, data: Value) -> Result<bool> {
         self.db.update_record(id, "user", data).await
     }
+
+    /// Adds the user to a transaction, whose commit fails with `DataExist` when the username or
+    /// email is taken by then, either by the checks or by the unique indexes.
+    fn insert_data_in(&self, transaction: &mut Transaction, data: User) -> Result<()> {
+        transaction.ensure_none(
+            "user",
+            Filter::eq("username", &data.username),
+            DataExist(format!("Username '{}' already exists", data.username)),
+        );
+        transaction.ensure_none(
+            "user",
+            Filter::eq("email", &data.email),
+            DataExist(format!("Email '{}' already exists", data.email)),
+        );
+        transaction.insert("user", data)
+    }
+
+    fn update_data_in(&self, transaction: &mut Transaction, id: &str, data: Value) -> Result<()> {
+        transaction.update(id, data)
+    }
+}
+
+/// Filter matching users whose username or email contains the search term.
+fn search_filter(search: Option<&str>) -> Filter {
+    match search.map(str::trim).filter(|search| !search.is_empty()) {
+        Some(search) => Filter::contains_ignore_case("username", search)
+            .or(Filter::contains_ignore_case("email", search)),
+        None => Filter::All,
+    }
 }
 
 impl UserRepository {
-    async fn is_data_empty(&self, field: &str, value: &str) -> Result<bool> {
-        let new_value = if field != "id" {
-            format!("'{}'", value)
-        } else {
-            value.to_string()
-        };
+    /// Applies a transaction built from any repositories sharing this database.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn commit(&self, transaction: Transaction) -> Result<()> {
+        self.db.commit(transaction).await
+    }
 
+    async fn is_data_empty(&self, field: &'static str, value: QueryValue) -> Result<bool> {
         let data: Vec<User> = self
             .db
-            .select_where("user", &format!("{} = {}", field, new_value), "*")
+            .select_where("user", Filter::eq(field, value), Columns::All)
             .await?;
 
         Ok(data.is_empty())
@@ -46,17 +85,17 @@ impl UserRepository {
 
     #[tracing::instrument(err, skip_all)]
     pub async fn is_data_empty_by_username(&self, username: &str) -> Result<bool> {
-        self.is_data_empty("username", username).await
+        self.is_data_empty("username", username.into()).await
     }
 
     #[tracing::instrument(err, skip_all)]
     pub async fn is_data_empty_by_email(&self, email: &str) -> Result<bool> {
-        self.is_data_empty("email", email).await
+        self.is_data_empty("email", email.into()).await
     }
 
     #[tracing::instrument(err, skip_all)]
     pub async fn is_data_empty_by_id(&self, id: &str) -> Result<bool> {
-        self.is_data_empty("id", id).await
+        self.is_data_empty("id", QueryValue::record(id)).await
     }
 
     #[tracing::instrument(err, skip_all)]
@@ -67,7 +106,11 @@ impl UserRepository {
 
         let data: Vec<User> = self
             .db
-            .select_where("user", &format!("id = {}", id), "*")
+            .select_where(
+                "user",
+                Filter::eq("id", QueryValue::record(id)),
+                Columns::All,
+            )
             .await?;
 
         data.first()
@@ -76,11 +119,27 @@ impl UserRepository {
             .ok_or_else(|| DataNotAvailable(format!("user with id {} not exists", id)))
     }
 
+    #[tracing::instrument(err, skip_all)]
+    pub async fn get_data_by_id(&self, id: &str) -> Result<User> {
+        let data: Vec<User> = self
+            .db
+            .select_where(
+                "user",
+                Filter::eq("id", QueryValue::record(id)),
+                Columns::All,
+            )
+            .await?;
+
+        data.into_iter()
+            .next()
+            .ok_or_else(|| DataNotAvailable(format!("user with id {} not exists", id)))
+    }
+
     #[tracing::instrument(err, skip_all)]
     pub async fn get_data_by_email(&self, email: &str) -> Result<User> {
         let data: Vec<User> = self
             .db
-            .select_where("user", &format!("email = '{}'", email), "*")
+            .select_where("user", Filter::eq("email", email), Columns::All)
             .await?;
 
         match data.len() {
@@ -92,4 +151,61 @@ impl UserRepository {
             _ => Err(DataDuplicationError("found more than one".to_string())),
         }
     }
+
+    /// Finds the user an OpenID Connect identity is linked to.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn get_data_by_identity(&self, issuer: &str, subject: &str) -> Result<Option<User>> {
+        let data: Vec<User> = self
+            .db
+            .select_where(
+                "user",
+                Filter::contains(
+                    "identities",
+                    json!({ "issuer": issuer, "subject": subject }),
+                ),
+                Columns::All,
+            )
+            .await?;
+
+        Ok(data.into_iter().next())
+    }
+
+    /// Makes the commit of a transaction fail with `DataNotValidate` when the recovery codes of
+    /// the user are no longer `current` by then.
+    pub fn ensure_recovery_codes_in(
+        &self,
+        transaction: &mut Transaction,
+        id: &str,
+        current: &[String],
+    ) {
+        transaction.ensure_none(
+            "user",
+            Filter::eq("id", QueryValue::record(id))
+                .and(Filter::ne("recovery_codes", json!(current))),
+            DataNotValidate("Recovery codes were changed concurrently".to_string()),
+        );
+    }
+
+    /// Counts the accounts with a role that are not disabled.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn count_enabled_by_role(&self, role: &str) -> Result<u64> {
+        self.db
+            .count(
+                "user",
+                &Filter::eq("role", role).and(Filter::ne("disabled", true)),
+            )
+            .await
+    }
+
+    /// Lists a page of users matching a search, newest first.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn list_page(&self, search: Option<&str>, page: &PageRequest) -> Result<Page<User>> {
+        self.db
+            .select_page(
+                "user",
+                Query::new(search_filter(search)).order_by("created_at", Direction::Desc),
+                page,
+            )
+            .await
+    }
 }
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use common::{cleanup_data, execute_sql, setup_direct_db};
+    use database::database::{DatabaseClient, SurrealDb};
+    use errors::Result;
+    use model::authorization::scope::ApiKeyScope;
+    use repository::api_key::api_key_repository::{ApiKeyRepository, ApiKeyRepositoryTrait};
+
+    use tokio::test;
+
+    use crate::setup_repo_with_surreal;
+
+    mod common;
+
+    setup_repo_with_surreal!(setup_api_key_repo, ApiKeyRepository, db);
+
+    #[test]
+    async fn test_insert_data() -> Result<()> {
+        let api_key_repo = setup_api_key_repo().await?;
+        let api_key = model::domain::api_key::ApiKey {
+            id: "vmk_00000000000000000000000000000001".to_string(),
+            user_id: "user:user_apikey1".to_string(),
+            name: "inventory sync".to_string(),
+            key_hash: "hash".to_string(),
+            scopes: vec![ApiKeyScope::InventoryWrite],
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+        let result = api_key_repo.insert_data(api_key).await?;
+        assert_eq!(result, "vmk_00000000000000000000000000000001");
+        cleanup_data("api_key:vmk_00000000000000000000000000000001", "api_key").await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_get_by_id_and_user() -> Result<()> {
+        let api_key_repo = setup_api_key_repo().await?;
+        execute_sql(
+            r#"CREATE api_key:vmk_00000000000000000000000000000002 CONTENT {
+                user_id: 'user:user_apikey2',
+                name: 'orders export',
+                key_hash: 'hash',
+                scopes: ['orders:read'],
+                created_at: time::now(),
+                last_used_at: NONE
+            };"#,
+        )
+        .await?;
+
+        let api_key = api_key_repo
+            .get_by_id("api_key:vmk_00000000000000000000000000000002")
+            .await?
+            .unwrap();
+        assert_eq!(api_key.scopes, vec![ApiKeyScope::OrdersRead]);
+        assert!(api_key.last_used_at.is_none());
+
+        let api_keys = api_key_repo.get_by_user("user:user_apikey2").await?;
+        assert_eq!(api_keys.len(), 1);
+        assert!(api_key_repo
+            .get_by_user("user:user_apikey404")
+            .await?
+            .is_empty());
+
+        cleanup_data("api_key:vmk_00000000000000000000000000000002", "api_key").await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_update_and_delete_data() -> Result<()> {
+        let api_key_repo = setup_api_key_repo().await?;
+        execute_sql(
+            r#"CREATE api_key:vmk_00000000000000000000000000000003 CONTENT {
+                user_id: 'user:user_apikey3',
+                name: 'catalog sync',
+                key_hash: 'hash',
+                scopes: ['catalog:write'],
+                created_at: time::now(),
+                last_used_at: NONE
+            };"#,
+        )
+        .await?;
+
+        let id = "api_key:vmk_00000000000000000000000000000003";
+        assert!(
+            api_key_repo
+                .update_data(id, serde_json::json!({ "last_used_at": Utc::now() }))
+                .await?
+        );
+        assert!(api_key_repo
+            .get_by_id(id)
+            .await?
+            .unwrap()
+            .last_used_at
+            .is_some());
+
+        assert!(api_key_repo.delete_data(id).await?);
+        assert!(api_key_repo.get_by_id(id).await?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use common::{cleanup_data, setup_direct_db};
+    use database::database::{DatabaseClient, SurrealDb};
+    use errors::Result;
+    use model::domain::{
+        audit_event::{AuditEvent, AuditEventFilter, AuditEventType, AuditOutcome},
+        page::PageRequest,
+    };
+    use repository::audit_event::audit_event_repository::{
+        AuditEventRepository, AuditEventRepositoryTrait,
+    };
+
+    use tokio::test;
+
+    use crate::setup_repo_with_surreal;
+
+    mod common;
+
+    setup_repo_with_surreal!(setup_audit_event_repo, AuditEventRepository, db);
+
+    fn audit_event(id: &str, event_type: AuditEventType, actor: &str) -> AuditEvent {
+        AuditEvent {
+            id: id.to_string(),
+            event_type,
+            outcome: AuditOutcome::Success,
+            actor: Some(actor.to_string()),
+            target: None,
+            ip: Some("127.0.0.1".to_string()),
+            user_agent: None,
+            detail: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    async fn test_insert_data() -> Result<()> {
+        let audit_event_repo = setup_audit_event_repo().await?;
+        let result = audit_event_repo
+            .insert_data(audit_event(
+                "evt_00000000000000000000000000000001",
+                AuditEventType::Login,
+                "user:user_audit1",
+            ))
+            .await?;
+        assert_eq!(result, "evt_00000000000000000000000000000001");
+        cleanup_data(
+            "audit_event:evt_00000000000000000000000000000001",
+            "audit_event",
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_get_page() -> Result<()> {
+        let audit_event_repo = setup_audit_event_repo().await?;
+        audit_event_repo
+            .insert_data(audit_event(
+                "evt_00000000000000000000000000000002",
+                AuditEventType::Login,
+                "user:user_audit2",
+            ))
+            .await?;
+        audit_event_repo
+            .insert_data(audit_event(
+                "evt_00000000000000000000000000000003",
+                AuditEventType::PasswordChanged,
+                "user:user_audit2",
+            ))
+            .await?;
+
+        let filter = AuditEventFilter {
+            user: Some("user:user_audit2".to_string()),
+            ..Default::default()
+        };
+        let events = audit_event_repo
+            .get_page(&filter, &PageRequest::offset(10, 0))
+            .await?;
+        assert_eq!(events.items.len(), 2);
+        assert_eq!(events.total, 2);
+        assert!(events.next_cursor.is_none());
+
+        let filter = AuditEventFilter {
+            user: Some("user:user_audit2".to_string()),
+            event_type: Some(AuditEventType::PasswordChanged),
+            ..Default::default()
+        };
+        let events = audit_event_repo
+            .get_page(&filter, &PageRequest::offset(10, 0))
+            .await?
+            .items;
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].id,
+            "audit_event:evt_00000000000000000000000000000003"
+        );
+
+        cleanup_data(
+            "audit_event:evt_00000000000000000000000000000002",
+            "audit_event",
+        )
+        .await?;
+        cleanup_data(
+            "audit_event:evt_00000000000000000000000000000003",
+            "audit_event",
+        )
+        .await?;
+        Ok(())
+    }
+}
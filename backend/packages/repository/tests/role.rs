@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use common::{cleanup_data, execute_sql, setup_direct_db};
+    use database::database::{DatabaseClient, SurrealDb};
+    use errors::Result;
+    use model::authorization::permission::Permission;
+    use repository::role::role_repository::{RoleRepository, RoleRepositoryTrait};
+
+    use tokio::test;
+
+    use crate::setup_repo_with_surreal;
+
+    mod common;
+
+    setup_repo_with_surreal!(setup_role_repo, RoleRepository, db);
+
+    #[test]
+    async fn test_insert_data() -> Result<()> {
+        let role_repo = setup_role_repo().await?;
+        let role = model::domain::role::Role {
+            id: "support_agent_1".to_string(),
+            name: "support_agent_1".to_string(),
+            description: "Customer support".to_string(),
+            permissions: vec![Permission::UsersRead],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let result = role_repo.insert_data(role).await?;
+        assert_eq!(result, "support_agent_1");
+        cleanup_data("role:support_agent_1", "role").await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_get_by_name() -> Result<()> {
+        let role_repo = setup_role_repo().await?;
+        execute_sql(
+            r#"CREATE role:finance_2 CONTENT {
+                name: 'finance_2',
+                description: 'Finance team',
+                permissions: ['finance:read'],
+                created_at: time::now(),
+                updated_at: time::now()
+            };"#,
+        )
+        .await?;
+
+        let role = role_repo.get_by_name("finance_2").await?;
+        assert!(role.is_some());
+        assert_eq!(role.unwrap().permissions, vec![Permission::FinanceRead]);
+        assert!(role_repo.get_by_name("finance_404").await?.is_none());
+
+        cleanup_data("role:finance_2", "role").await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_update_data() -> Result<()> {
+        let role_repo = setup_role_repo().await?;
+        execute_sql(
+            r#"CREATE role:moderator_3 CONTENT {
+                name: 'moderator_3',
+                description: 'Catalog moderator',
+                permissions: [],
+                created_at: time::now(),
+                updated_at: time::now()
+            };"#,
+        )
+        .await?;
+
+        let updated_role = serde_json::json!({ "permissions": ["catalog:moderate"] });
+        assert!(
+            role_repo
+                .update_data("role:moderator_3", updated_role)
+                .await?
+        );
+
+        let role = role_repo.get_by_name("moderator_3").await?.unwrap();
+        assert_eq!(role.permissions, vec![Permission::CatalogModerate]);
+        assert_eq!(role.description, "Catalog moderator");
+
+        cleanup_data("role:moderator_3", "role").await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_delete_data() -> Result<()> {
+        let role_repo = setup_role_repo().await?;
+        execute_sql(
+            r#"CREATE role:temporary_4 CONTENT {
+                name: 'temporary_4',
+                description: 'Temporary',
+                permissions: [],
+                created_at: time::now(),
+                updated_at: time::now()
+            };"#,
+        )
+        .await?;
+
+        assert!(role_repo.delete_data("role:temporary_4").await?);
+        assert!(role_repo.get_by_name("temporary_4").await?.is_none());
+        Ok(())
+    }
+}
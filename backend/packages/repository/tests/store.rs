@@ -4,6 +4,7 @@ mod tests {
     use common::{cleanup_data, execute_sql, setup_direct_db};
     use database::database::{DatabaseClient, SurrealDb};
     use errors::Result;
+    use model::domain::page::PageRequest;
     use repository::store::store_repository::{StoreRepository, StoreRepositoryTrait};
     use surrealdb::sql::Thing;
 
@@ -32,7 +33,7 @@ mod tests {
         };
 
         let is_stored = store_repo.insert_data(store).await?;
-        assert_eq!(is_stored, true);
+        assert!(is_stored);
         cleanup_data("store:store_12341", "store").await?;
 
         Ok(())
@@ -86,6 +87,20 @@ mod tests {
         let store_repo = store_repo().await?;
         let stores = store_repo.get_by_user_id("user:user_1_2_3").await?;
         assert_eq!(stores.len(), 2);
+
+        let page = store_repo
+            .get_page_by_user_id("user:user_1_2_3", &PageRequest::offset(1, 0))
+            .await?;
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.total, 2);
+        let next = store_repo
+            .get_page_by_user_id(
+                "user:user_1_2_3",
+                &PageRequest::after(1, page.next_cursor.expect("a second page")),
+            )
+            .await?;
+        assert_eq!(next.items.len(), 1);
+        assert_ne!(next.items[0].id, page.items[0].id);
         cleanup_data("store:store_12347", "store").await?;
         cleanup_data("store:store_12348", "store").await?;
         cleanup_data("user:user_1_2_3", "user").await?;
@@ -122,10 +137,10 @@ mod tests {
         )
         .await?;
         let store_repo = store_repo().await?;
-        let store = store_repo.get_by_id("store:store_12347").await?;
+        let store = store_repo.get_by_id("store:store_12349").await?;
         assert_eq!(store.unwrap().name, "Test Store");
-        cleanup_data("store:store_12347", "store").await?;
-        cleanup_data("user:user_1_2_3", "user").await?;
+        cleanup_data("store:store_12349", "store").await?;
+        cleanup_data("user:user_1_2_4", "user").await?;
         Ok(())
     }
 
@@ -1,22 +1,13 @@
+// Each test binary only uses some of these helpers
+#![allow(dead_code)]
+
+use database::database::{DatabaseType, SurrealDb};
 use environment::Environment;
 use errors::Result;
-use surrealdb::{
-    engine::remote::ws::{Client, Ws},
-    opt::auth::Root,
-    Response, Surreal,
-};
+use surrealdb::{engine::any::Any, Response, Surreal};
 
-pub(super) async fn setup_direct_db() -> Result<Surreal<Client>> {
-    let env = Environment::new();
-    let client = Surreal::new::<Ws>(format!("{}:{}", env.db_host, env.db_port)).await?;
-    client
-        .signin(Root {
-            username: &env.db_user,
-            password: &env.db_pass,
-        })
-        .await?;
-    client.use_ns(env.db_namespace).use_db(env.db_name).await?;
-    Ok(client)
+pub(super) async fn setup_direct_db() -> Result<Surreal<Any>> {
+    SurrealDb::open(&DatabaseType::from_env(&Environment::new())?).await
 }
 
 pub(super) async fn execute_sql(query: &str) -> Result<Response> {
@@ -30,7 +21,7 @@ pub(super) async fn execute_sql(query: &str) -> Result<Response> {
 pub(super) async fn cleanup_user(id: &str) -> Result<()> {
     setup_direct_db()
         .await?
-        .query(&format!("DELETE FROM user WHERE id = {}", id))
+        .query(format!("DELETE FROM user WHERE id = {}", id))
         .await?;
     Ok(())
 }
@@ -38,7 +29,7 @@ pub(super) async fn cleanup_user(id: &str) -> Result<()> {
 pub(super) async fn cleanup_data(id: &str, tb: &str) -> Result<()> {
     setup_direct_db()
         .await?
-        .query(&format!("DELETE FROM {} WHERE id = {}", tb, id))
+        .query(format!("DELETE FROM {} WHERE id = {}", tb, id))
         .await?;
     Ok(())
 }
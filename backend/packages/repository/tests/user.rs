@@ -3,8 +3,15 @@ mod tests {
     use chrono::Utc;
     use common::{cleanup_user, execute_sql, setup_direct_db};
     use database::database::{DatabaseClient, SurrealDb};
-    use errors::{Error::DataNotAvailable, Result};
-    use repository::user::user_repository::{UserRepository, UserRepositoryTrait};
+    use errors::{
+        Error::{DataExist, DataNotAvailable},
+        Result,
+    };
+    use model::domain::page::PageRequest;
+    use repository::{
+        user::user_repository::{UserRepository, UserRepositoryTrait},
+        Transaction,
+    };
     use serde::{Deserialize, Serialize};
 
     use tokio::test;
@@ -33,6 +40,13 @@ mod tests {
             role: "buyer".to_string(),
             email: "test@email.test".to_string(),
             verified: false,
+            disabled: false,
+            full_name: None,
+            phone_number: None,
+            totp_secret: None,
+            recovery_codes: Vec::new(),
+            two_factor_required: false,
+            identities: Vec::new(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -114,6 +128,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    async fn test_get_data_by_id() -> Result<()> {
+        let user_repo = setup_user_repo().await?;
+        execute_sql(
+            r#"CREATE user:user_byid CONTENT {
+                username: 'Tobies8',
+                password: 'password',
+                role: 'customer',
+                email: 'byid@gmail.com',
+                verified: true,
+                created_at: time::now(),
+                updated_at: time::now()
+            };"#,
+        )
+        .await?;
+
+        assert_eq!(
+            user_repo.get_data_by_id("user:user_byid").await?.email,
+            "byid@gmail.com"
+        );
+        assert!(user_repo.get_data_by_id("user:user_missing").await.is_err());
+        cleanup_user("user:user_byid").await?;
+        Ok(())
+    }
+
     #[test]
     async fn test_is_user_verified() -> Result<()> {
         let user_repo = setup_user_repo().await?;
@@ -173,4 +212,112 @@ mod tests {
         cleanup_user("user:user_12345").await?;
         Ok(())
     }
+
+    #[test]
+    async fn test_list_page() -> Result<()> {
+        let user_repo = setup_user_repo().await?;
+        execute_sql(
+            r#"CREATE user:user_list1 CONTENT {
+                username: 'ListSearchAlpha',
+                password: 'password',
+                role: 'customer',
+                email: 'list-alpha@gmail.com',
+                verified: true,
+                created_at: time::now(),
+                updated_at: time::now()
+            };
+            CREATE user:user_list2 CONTENT {
+                username: 'ListSearchBeta',
+                password: 'password',
+                role: 'customer',
+                email: 'list-beta@gmail.com',
+                verified: true,
+                created_at: time::now(),
+                updated_at: time::now()
+            };"#,
+        )
+        .await?;
+
+        let all = PageRequest::offset(10, 0);
+        assert_eq!(
+            user_repo.list_page(Some("listsearch"), &all).await?.total,
+            2
+        );
+        assert_eq!(
+            user_repo.list_page(Some("LIST-BETA@"), &all).await?.total,
+            1
+        );
+        assert_eq!(
+            user_repo
+                .list_page(Some("no-such-user'"), &all)
+                .await?
+                .total,
+            0
+        );
+
+        let page = user_repo
+            .list_page(Some("listsearch"), &PageRequest::offset(1, 1))
+            .await?;
+        assert_eq!(page.items.len(), 1);
+        assert!(!page.items[0].disabled);
+        assert!(page.next_cursor.is_none());
+
+        let first = user_repo
+            .list_page(Some("listsearch"), &PageRequest::offset(1, 0))
+            .await?;
+        let cursor = first.next_cursor.expect("a second page");
+        let second = user_repo
+            .list_page(Some("listsearch"), &PageRequest::after(1, cursor))
+            .await?;
+        assert_eq!(second.items.len(), 1);
+        assert_ne!(second.items[0].id, first.items[0].id);
+        assert!(second.next_cursor.is_none());
+
+        cleanup_user("user:user_list1").await?;
+        cleanup_user("user:user_list2").await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_insert_data_in_transaction() -> Result<()> {
+        let user_repo = setup_user_repo().await?;
+        let user = |id: &str, username: &str| model::domain::user::User {
+            id: id.to_string(),
+            username: username.to_string(),
+            password: "test".to_string(),
+            role: "buyer".to_string(),
+            email: "tx@email.test".to_string(),
+            verified: false,
+            disabled: false,
+            full_name: None,
+            phone_number: None,
+            totp_secret: None,
+            recovery_codes: Vec::new(),
+            two_factor_required: false,
+            identities: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let mut transaction = Transaction::begin();
+        user_repo.insert_data_in(&mut transaction, user("user_tx1", "tx_first"))?;
+        user_repo.commit(transaction).await?;
+        assert!(!user_repo.is_data_empty_by_id("user:user_tx1").await?);
+
+        // The email is taken, so neither the update nor the insert is applied
+        let mut transaction = Transaction::begin();
+        user_repo.update_data_in(
+            &mut transaction,
+            "user:user_tx1",
+            serde_json::json!({ "verified": true }),
+        )?;
+        user_repo.insert_data_in(&mut transaction, user("user_tx2", "tx_second"))?;
+        let result = user_repo.commit(transaction).await;
+        assert!(matches!(result, Err(DataExist(_))));
+        assert!(user_repo.is_data_empty_by_id("user:user_tx2").await?);
+        assert!(!user_repo.is_verified("user:user_tx1").await?);
+
+        cleanup_user("user:user_tx1").await?;
+        Ok(())
+    }
 }
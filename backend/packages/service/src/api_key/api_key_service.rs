@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use errors::Result;
+use model::{
+    domain::api_key::ApiKey as ApiKeyData,
+    web::api_key::{
+        api_key_request::ApiKey,
+        api_key_response::{ApiKey as ApiKeyResponse, CreatedApiKey},
+    },
+};
+use repository::api_key::api_key_repository::ApiKeyRepository;
+
+#[derive(Clone, Debug)]
+pub struct ApiKeyService {
+    pub api_key_repo: ApiKeyRepository,
+}
+
+#[async_trait]
+pub trait ApiKeyServiceTrait {
+    async fn create_api_key(&self, user_id: &str, data: ApiKey) -> Result<CreatedApiKey>;
+    async fn list_api_keys(&self, user_id: &str) -> Result<Vec<ApiKeyResponse>>;
+    async fn revoke_api_key(&self, user_id: &str, id: &str) -> Result<bool>;
+    async fn authenticate(&self, key: &str) -> Result<ApiKeyData>;
+}
@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use super::api_key_service::{ApiKeyService, ApiKeyServiceTrait};
+use errors::{
+    Error::{DataNotAvailable, UserUnauthorized},
+    Result,
+};
+use model::{
+    domain::api_key::ApiKey as ApiKeyData,
+    web::api_key::{
+        api_key_request::ApiKey,
+        api_key_response::{ApiKey as ApiKeyResponse, CreatedApiKey},
+    },
+};
+use repository::api_key::api_key_repository::ApiKeyRepositoryTrait as _;
+
+const KEY_ID_PREFIX: &str = "vmk_";
+
+impl ApiKeyService {
+    fn hash_key(key: &str) -> String {
+        format!("{:x}", Sha256::digest(key.as_bytes()))
+    }
+
+    /// Checks that a key id has the shape we generate before it is used in a query.
+    fn record_id(id: &str) -> Option<String> {
+        let hex = id.strip_prefix(KEY_ID_PREFIX)?;
+        let is_valid = hex.len() == 32 && hex.chars().all(|c| c.is_ascii_hexdigit());
+        is_valid.then(|| format!("api_key:{}", id))
+    }
+
+    fn to_response(api_key: ApiKeyData) -> ApiKeyResponse {
+        ApiKeyResponse {
+            id: api_key.id.trim_start_matches("api_key:").to_string(),
+            name: api_key.name,
+            scopes: api_key.scopes,
+            created_at: api_key.created_at,
+            last_used_at: api_key.last_used_at,
+        }
+    }
+}
+
+#[async_trait]
+impl ApiKeyServiceTrait for ApiKeyService {
+    /// Issues a new API key for a user. The plain key is only returned here.
+    #[tracing::instrument(err, skip_all)]
+    async fn create_api_key(&self, user_id: &str, data: ApiKey) -> Result<CreatedApiKey> {
+        let id = format!("{}{}", KEY_ID_PREFIX, Uuid::new_v4().simple());
+        let key = format!(
+            "{}.{}{}",
+            id,
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
+
+        let db_data = ApiKeyData {
+            id: id.clone(),
+            user_id: user_id.to_string(),
+            name: data.name,
+            key_hash: Self::hash_key(&key),
+            scopes: data.scopes,
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        self.api_key_repo.insert_data(db_data.clone()).await?;
+
+        Ok(CreatedApiKey {
+            api_key: Self::to_response(db_data),
+            key,
+        })
+    }
+
+    /// Lists the API keys of a user, newest first.
+    #[tracing::instrument(err, skip_all)]
+    async fn list_api_keys(&self, user_id: &str) -> Result<Vec<ApiKeyResponse>> {
+        let api_keys = self.api_key_repo.get_by_user(user_id).await?;
+        Ok(api_keys.into_iter().map(Self::to_response).collect())
+    }
+
+    /// Deletes one of the user's API keys.
+    #[tracing::instrument(err, skip_all)]
+    async fn revoke_api_key(&self, user_id: &str, id: &str) -> Result<bool> {
+        let not_found = || DataNotAvailable(format!("API key '{}' not found", id));
+
+        let record_id = Self::record_id(id).ok_or_else(not_found)?;
+        let api_key = self
+            .api_key_repo
+            .get_by_id(&record_id)
+            .await?
+            .filter(|api_key| api_key.user_id == user_id)
+            .ok_or_else(not_found)?;
+
+        self.api_key_repo.delete_data(&api_key.id).await
+    }
+
+    /// Resolves a presented key to its record and records its use.
+    #[tracing::instrument(err, skip_all)]
+    async fn authenticate(&self, key: &str) -> Result<ApiKeyData> {
+        let invalid = || UserUnauthorized("Invalid API key".to_string());
+
+        let (id, _) = key.split_once('.').ok_or_else(invalid)?;
+        let record_id = Self::record_id(id).ok_or_else(invalid)?;
+        let api_key = self
+            .api_key_repo
+            .get_by_id(&record_id)
+            .await?
+            // Compared in constant time so response timing does not leak the stored hash
+            .filter(|api_key| {
+                bool::from(
+                    api_key
+                        .key_hash
+                        .as_bytes()
+                        .ct_eq(Self::hash_key(key).as_bytes()),
+                )
+            })
+            .ok_or_else(invalid)?;
+
+        // Integrations call in bursts, a minute of precision is enough and saves a write per call
+        let now = Utc::now();
+        if api_key
+            .last_used_at
+            .is_none_or(|last_used_at| now - last_used_at > Duration::minutes(1))
+        {
+            self.api_key_repo
+                .update_data(&record_id, json!({ "last_used_at": now }))
+                .await?;
+        }
+
+        Ok(api_key)
+    }
+}
@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use errors::Result;
+use model::domain::{
+    audit_event::{AuditEvent, AuditEventFilter},
+    page::{Page, PageRequest},
+};
+use repository::audit_event::audit_event_repository::AuditEventRepository;
+
+#[derive(Clone, Debug)]
+pub struct AuditService {
+    pub audit_repo: AuditEventRepository,
+}
+
+#[async_trait]
+pub trait AuditServiceTrait {
+    async fn record_event(&self, event: AuditEvent) -> Result<String>;
+    async fn list_events(
+        &self,
+        filter: &AuditEventFilter,
+        page: &PageRequest,
+    ) -> Result<Page<AuditEvent>>;
+}
@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+
+use super::audit_service::{AuditService, AuditServiceTrait};
+use errors::Result;
+use model::domain::{
+    audit_event::{AuditEvent, AuditEventFilter},
+    page::{Page, PageRequest},
+};
+use repository::audit_event::audit_event_repository::AuditEventRepositoryTrait as _;
+
+#[async_trait]
+impl AuditServiceTrait for AuditService {
+    /// Appends an event to the audit log.
+    #[tracing::instrument(err, skip_all)]
+    async fn record_event(&self, event: AuditEvent) -> Result<String> {
+        self.audit_repo.insert_data(event).await
+    }
+
+    /// Lists a page of events matching a filter, newest first, with the number of matches.
+    #[tracing::instrument(err, skip_all)]
+    async fn list_events(
+        &self,
+        filter: &AuditEventFilter,
+        page: &PageRequest,
+    ) -> Result<Page<AuditEvent>> {
+        self.audit_repo.get_page(filter, page).await
+    }
+}
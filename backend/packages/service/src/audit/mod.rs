@@ -0,0 +1,2 @@
+pub mod audit_service;
+pub mod audit_service_impl;
@@ -1,19 +1,56 @@
-use base64::{engine::general_purpose, Engine};
 use chrono::Utc;
-use errors::{Error::DatabaseErrorExecution, Result};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use model::authorization::token::{TokenClaims, TokenDetails};
+use errors::{
+    Error::{DatabaseErrorExecution, TokenError},
+    Result,
+};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, Header, Validation};
+use model::authorization::{
+    jwk::KeyUse,
+    token::{ActorClaim, TokenClaims, TokenDetails},
+};
 use redis::{AsyncCommands, Client};
 use uuid::Uuid;
 
-/// Generates a JWT token for a given user.
+use super::keyring::{Keyring, SigningKey};
+
+/// Generates a JWT token for a given user, signed with `signing_key` and tagged with its `kid`.
 pub async fn generate_jwt_token(
     user_id: String,
     ttl: i64,
-    private_key: String,
+    signing_key: &SigningKey,
+    user_role: &str,
+) -> Result<TokenDetails> {
+    sign_token(user_id, ttl, signing_key, user_role, None)
+}
+
+/// Generates a token that lets `impersonator` act as `user_id`, recorded in its `act` claim.
+pub async fn generate_impersonation_token(
+    user_id: String,
+    impersonator: &str,
+    ttl: i64,
+    signing_key: &SigningKey,
     user_role: &str,
 ) -> Result<TokenDetails> {
-    let decoded_private_key = String::from_utf8(general_purpose::STANDARD.decode(private_key)?)?;
+    sign_token(
+        user_id,
+        ttl,
+        signing_key,
+        user_role,
+        Some(impersonator.to_string()),
+    )
+}
+
+fn sign_token(
+    user_id: String,
+    ttl: i64,
+    signing_key: &SigningKey,
+    user_role: &str,
+    impersonator: Option<String>,
+) -> Result<TokenDetails> {
+    let encoding_key = signing_key
+        .encoding_key
+        .as_ref()
+        .ok_or_else(|| TokenError(format!("Key '{}' cannot sign tokens", signing_key.kid)))?;
     let now = Utc::now();
 
     let token_details = TokenDetails {
@@ -22,6 +59,7 @@ pub async fn generate_jwt_token(
         expires_in: Some((now + chrono::Duration::minutes(ttl)).timestamp()),
         token: None,
         user_role: user_role.to_string(),
+        impersonator: impersonator.clone(),
     };
 
     let claims = TokenClaims {
@@ -31,13 +69,14 @@ pub async fn generate_jwt_token(
         iat: now.timestamp(),
         nbf: now.timestamp(),
         user_role: user_role.to_string(),
+        act: impersonator.map(|sub| ActorClaim { sub }),
     };
 
-    let token = encode(
-        &Header::new(Algorithm::RS256),
-        &claims,
-        &EncodingKey::from_rsa_pem(decoded_private_key.as_bytes())?,
-    )?;
+    let header = Header {
+        kid: Some(signing_key.kid.clone()),
+        ..Header::new(Algorithm::RS256)
+    };
+    let token = encode(&header, &claims, encoding_key)?;
 
     Ok(TokenDetails {
         token: Some(token),
@@ -45,16 +84,25 @@ pub async fn generate_jwt_token(
     })
 }
 
-/// Verifies a JWT token and extracts token details.
-pub async fn verify_jwt_token(public_key: String, token: &str) -> Result<TokenDetails> {
-    let decoded_public_key = String::from_utf8(general_purpose::STANDARD.decode(public_key)?)?;
+/// Verifies a JWT token against the keyring and extracts token details.
+pub async fn verify_jwt_token(
+    keyring: &Keyring,
+    key_use: KeyUse,
+    token: &str,
+) -> Result<TokenDetails> {
+    let kid = decode_header(token)?.kid;
     let validation = Validation::new(Algorithm::RS256);
 
-    let decoded = decode::<TokenClaims>(
-        token,
-        &DecodingKey::from_rsa_pem(decoded_public_key.as_bytes())?,
-        &validation,
-    )?;
+    let mut decoded = Err(TokenError(
+        "Token is not signed by a trusted key".to_string(),
+    ));
+    for key in keyring.verification_keys(key_use, kid.as_deref()) {
+        decoded = decode::<TokenClaims>(token, &key.decoding_key, &validation).map_err(Into::into);
+        if decoded.is_ok() {
+            break;
+        }
+    }
+    let decoded = decoded?;
 
     Ok(TokenDetails {
         user_id: decoded.claims.sub,
@@ -62,6 +110,7 @@ pub async fn verify_jwt_token(public_key: String, token: &str) -> Result<TokenDe
         expires_in: None,
         token: None,
         user_role: decoded.claims.user_role,
+        impersonator: decoded.claims.act.map(|act| act.sub),
     })
 }
 
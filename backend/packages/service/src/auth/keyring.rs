@@ -0,0 +1,162 @@
+/* JWT signing keys identified by a `kid` header.
+
+New tokens are signed with the first active key of their kind. Every active key verifies
+tokens, and a retired key keeps verifying for the grace period so rotating keys does not log
+anyone out. Without `JWT_KEYRING_PATH` the keyring holds the single key pairs of the
+environment. */
+use std::fs;
+
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Duration, Utc};
+use environment::Environment;
+use errors::{Error::TokenError, Result};
+use jsonwebtoken::{DecodingKey, EncodingKey};
+use model::authorization::jwk::{Jwk, JwkSet, KeyUse, KeyringConfig, SigningKeyConfig};
+use rsa::{
+    pkcs1::DecodeRsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts, RsaPublicKey,
+};
+
+/* A keyring entry with its keys parsed */
+#[derive(Clone)]
+pub struct SigningKey {
+    pub kid: String,
+    pub key_use: KeyUse,
+    pub retired_at: Option<DateTime<Utc>>,
+    pub(crate) encoding_key: Option<EncodingKey>,
+    pub(crate) decoding_key: DecodingKey,
+    jwk: Jwk,
+}
+
+impl SigningKey {
+    fn from_config(config: SigningKeyConfig) -> Result<Self> {
+        let public_key = decode_pem(&config.public_key)?;
+        let encoding_key = config
+            .private_key
+            .as_deref()
+            .map(|private_key| -> Result<EncodingKey> {
+                Ok(EncodingKey::from_rsa_pem(
+                    decode_pem(private_key)?.as_bytes(),
+                )?)
+            })
+            .transpose()?;
+
+        let rsa_key = RsaPublicKey::from_public_key_pem(&public_key)
+            .or_else(|_| RsaPublicKey::from_pkcs1_pem(&public_key))
+            .map_err(|e| TokenError(format!("Invalid public key '{}': {}", config.kid, e)))?;
+
+        Ok(SigningKey {
+            jwk: Jwk {
+                kty: "RSA".to_string(),
+                key_use: "sig".to_string(),
+                alg: "RS256".to_string(),
+                kid: config.kid.clone(),
+                n: general_purpose::URL_SAFE_NO_PAD.encode(rsa_key.n().to_bytes_be()),
+                e: general_purpose::URL_SAFE_NO_PAD.encode(rsa_key.e().to_bytes_be()),
+            },
+            kid: config.kid,
+            key_use: config.key_use,
+            retired_at: config.retired_at,
+            encoding_key,
+            decoding_key: DecodingKey::from_rsa_pem(public_key.as_bytes())?,
+        })
+    }
+
+    /// Whether new tokens may still be signed with this key.
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.retired_at.is_none_or(|retired_at| retired_at > now)
+    }
+
+    /// Whether tokens signed with this key are still accepted.
+    fn is_trusted(&self, now: DateTime<Utc>, grace_period: Duration) -> bool {
+        self.retired_at
+            .is_none_or(|retired_at| retired_at + grace_period > now)
+    }
+}
+
+fn decode_pem(key: &str) -> Result<String> {
+    Ok(String::from_utf8(general_purpose::STANDARD.decode(key)?)?)
+}
+
+#[derive(Clone)]
+pub struct Keyring {
+    keys: Vec<SigningKey>,
+    grace_period: Duration,
+}
+
+impl Keyring {
+    pub fn new(config: KeyringConfig, grace_period: i64) -> Result<Self> {
+        let keys = config
+            .keys
+            .into_iter()
+            .map(SigningKey::from_config)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Keyring {
+            keys,
+            grace_period: Duration::minutes(grace_period),
+        })
+    }
+
+    /// Loads the keyring file, or the single key pairs of the environment when there is none.
+    pub fn from_env(env: &Environment) -> Result<Self> {
+        let config = if env.jwt_keyring_path == "none" {
+            KeyringConfig {
+                keys: vec![
+                    SigningKeyConfig {
+                        kid: env.access_token_kid.clone(),
+                        key_use: KeyUse::Access,
+                        private_key: Some(env.access_token_private_key.clone()),
+                        public_key: env.access_token_public_key.clone(),
+                        retired_at: None,
+                    },
+                    SigningKeyConfig {
+                        kid: env.refresh_token_kid.clone(),
+                        key_use: KeyUse::Refresh,
+                        private_key: Some(env.refresh_token_private_key.clone()),
+                        public_key: env.refresh_token_public_key.clone(),
+                        retired_at: None,
+                    },
+                ],
+            }
+        } else {
+            let content = fs::read_to_string(&env.jwt_keyring_path)
+                .map_err(|e| TokenError(format!("Failed to read JWT keyring: {}", e)))?;
+            serde_json::from_str(&content)
+                .map_err(|e| TokenError(format!("Invalid JWT keyring: {}", e)))?
+        };
+
+        Self::new(config, env.jwt_key_grace_period)
+    }
+
+    /// Returns the key new tokens of the given kind are signed with.
+    pub fn signing_key(&self, key_use: KeyUse) -> Result<&SigningKey> {
+        let now = Utc::now();
+        self.keys
+            .iter()
+            .find(|key| key.key_use == key_use && key.encoding_key.is_some() && key.is_active(now))
+            .ok_or_else(|| TokenError(format!("No active {:?} signing key", key_use)))
+    }
+
+    /// Returns the keys a token may be verified with.
+    ///
+    /// Tokens issued before keys had ids carry no `kid` and are tried against every trusted key.
+    pub fn verification_keys(&self, key_use: KeyUse, kid: Option<&str>) -> Vec<&SigningKey> {
+        let now = Utc::now();
+        self.keys
+            .iter()
+            .filter(|key| key.key_use == key_use && key.is_trusted(now, self.grace_period))
+            .filter(|key| kid.is_none_or(|kid| key.kid == kid))
+            .collect()
+    }
+
+    /// Public keys other services need to verify access tokens.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verification_keys(KeyUse::Access, None)
+                .into_iter()
+                .map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+/* Failed login attempts are counted per email and per client IP. Requests for magic login links,
+password reset links and verification links are counted the same way, each one emails the address
+and must not be repeatable at will.
+
+Redis layout:
+- `login_failures:{email|ip}:{subject}` -> consecutive failed logins, reset after a quiet lockout period
+- `login_lock:{email|ip}:{subject}` -> present while the subject has to wait before trying again
+- `magic_link_requests:{email|ip}:{subject}` and `magic_link_lock:{email|ip}:{subject}` -> the same
+  for magic link requests
+- `password_reset_requests:{email|ip}:{subject}` and `password_reset_lock:{email|ip}:{subject}` -> the
+  same for password reset requests
+- `verification_requests:{email|ip}:{subject}` and `verification_lock:{email|ip}:{subject}` -> the same
+  for verification link requests */
+use environment::Environment;
+use errors::{
+    Error::{DatabaseErrorExecution, TooManyAttempts},
+    Result,
+};
+use redis::{aio::MultiplexedConnection, AsyncCommands, Client};
+
+/* Who a failed login is counted against */
+#[derive(Debug, Clone, Copy)]
+enum Subject<'a> {
+    Email(&'a str),
+    Ip(&'a str),
+}
+
+impl Subject<'_> {
+    fn key(&self, prefix: &str) -> String {
+        match self {
+            Subject::Email(email) => format!("{}:email:{}", prefix, email.trim().to_lowercase()),
+            Subject::Ip(ip) => format!("{}:ip:{}", prefix, ip),
+        }
+    }
+}
+
+/* Attempts that are counted and backed off separately */
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Login,
+    MagicLink,
+    PasswordReset,
+    Verification,
+}
+
+impl Action {
+    fn counter_prefix(&self) -> &'static str {
+        match self {
+            Action::Login => "login_failures",
+            Action::MagicLink => "magic_link_requests",
+            Action::PasswordReset => "password_reset_requests",
+            Action::Verification => "verification_requests",
+        }
+    }
+
+    fn lock_prefix(&self) -> &'static str {
+        match self {
+            Action::Login => "login_lock",
+            Action::MagicLink => "magic_link_lock",
+            Action::PasswordReset => "password_reset_lock",
+            Action::Verification => "verification_lock",
+        }
+    }
+}
+
+/* Limits applied to failed logins */
+#[derive(Debug, Clone, Copy)]
+pub struct LoginThrottle {
+    pub max_attempts: i64,
+    pub ip_max_attempts: i64,
+    pub lockout_max_age: i64,
+}
+
+impl LoginThrottle {
+    pub fn from_env(env: &Environment) -> Self {
+        LoginThrottle {
+            max_attempts: env.login_max_attempts,
+            ip_max_attempts: env.login_ip_max_attempts,
+            lockout_max_age: env.login_lockout_max_age,
+        }
+    }
+
+    /// Seconds a subject has to wait after its `failures`-th consecutive failed login.
+    ///
+    /// The first failure is free, then the delay doubles until `max_attempts` is reached,
+    /// which locks the subject out for the whole lockout period.
+    pub fn backoff_seconds(&self, failures: i64, max_attempts: i64) -> i64 {
+        let lockout = self.lockout_max_age * 60;
+        if failures >= max_attempts {
+            return lockout;
+        }
+        if failures <= 1 {
+            return 0;
+        }
+        2_i64.saturating_pow((failures - 1) as u32).min(lockout)
+    }
+}
+
+async fn connection(client: &Client) -> Result<MultiplexedConnection> {
+    client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|_| DatabaseErrorExecution("Failed to connect to Redis".to_string()))
+}
+
+fn subjects<'a>(email: &'a str, ip: Option<&'a str>) -> Vec<Subject<'a>> {
+    let mut subjects = vec![Subject::Email(email)];
+    subjects.extend(ip.map(Subject::Ip));
+    subjects
+}
+
+async fn check_allowed(
+    client: &Client,
+    action: Action,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+
+    let mut retry_after = 0;
+    for subject in subjects(email, ip) {
+        let ttl: i64 = redis_client.ttl(subject.key(action.lock_prefix())).await?;
+        retry_after = retry_after.max(ttl);
+    }
+
+    if retry_after > 0 {
+        return Err(TooManyAttempts(retry_after));
+    }
+    Ok(())
+}
+
+async fn record_attempt(
+    client: &Client,
+    throttle: &LoginThrottle,
+    action: Action,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+
+    for subject in subjects(email, ip) {
+        let max_attempts = match subject {
+            Subject::Email(_) => throttle.max_attempts,
+            Subject::Ip(_) => throttle.ip_max_attempts,
+        };
+
+        let counter_key = subject.key(action.counter_prefix());
+        let attempts: i64 = redis_client.incr(&counter_key, 1).await?;
+        let _: () = redis_client
+            .expire(&counter_key, throttle.lockout_max_age * 60)
+            .await?;
+
+        let backoff = throttle.backoff_seconds(attempts, max_attempts);
+        if backoff > 0 {
+            let _: () = redis_client
+                .set_ex(subject.key(action.lock_prefix()), attempts, backoff as u64)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects the attempt when the email or the IP is still backing off.
+pub async fn check_login_allowed(client: &Client, email: &str, ip: Option<&str>) -> Result<()> {
+    check_allowed(client, Action::Login, email, ip).await
+}
+
+/// Counts a failed login against the email and the IP and applies their backoff.
+pub async fn record_login_failure(
+    client: &Client,
+    throttle: &LoginThrottle,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<()> {
+    record_attempt(client, throttle, Action::Login, email, ip).await
+}
+
+/// Counts a magic link request against the email and the IP, rejecting it while either one is
+/// backing off from earlier requests.
+///
+/// Requests for unknown emails are counted as well, so being throttled reveals nothing.
+pub async fn throttle_magic_link_request(
+    client: &Client,
+    throttle: &LoginThrottle,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<()> {
+    check_allowed(client, Action::MagicLink, email, ip).await?;
+    record_attempt(client, throttle, Action::MagicLink, email, ip).await
+}
+
+/// Counts a password reset request like `throttle_magic_link_request` counts magic link
+/// requests.
+pub async fn throttle_password_reset_request(
+    client: &Client,
+    throttle: &LoginThrottle,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<()> {
+    check_allowed(client, Action::PasswordReset, email, ip).await?;
+    record_attempt(client, throttle, Action::PasswordReset, email, ip).await
+}
+
+/// Counts a request to resend the verification link like `throttle_magic_link_request` counts
+/// magic link requests.
+pub async fn throttle_verification_request(
+    client: &Client,
+    throttle: &LoginThrottle,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<()> {
+    check_allowed(client, Action::Verification, email, ip).await?;
+    record_attempt(client, throttle, Action::Verification, email, ip).await
+}
+
+/// Forgets the failed logins of an email, lifting any lockout on it.
+///
+/// Used after a successful login and by admins unlocking an account. IP counters are
+/// left alone so a valid login cannot be used to reset a credential stuffing source.
+pub async fn clear_login_failures(client: &Client, email: &str) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let subject = Subject::Email(email);
+
+    let _: () = redis_client
+        .del(&[
+            subject.key(Action::Login.counter_prefix()),
+            subject.key(Action::Login.lock_prefix()),
+        ])
+        .await?;
+    Ok(())
+}
@@ -1 +1,10 @@
 pub mod jwt;
+pub mod keyring;
+pub mod login_throttle;
+pub mod oidc;
+pub mod one_time_token;
+pub mod password_policy;
+pub mod session;
+pub mod two_factor;
+pub mod verification;
+pub mod webauthn;
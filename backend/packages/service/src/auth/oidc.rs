@@ -0,0 +1,317 @@
+/* OpenID Connect login against any standards-compliant issuer.
+
+The authorization code flow is used with PKCE (S256) and a nonce. Endpoints and signing
+keys come from the issuer's discovery document, so adding a provider only takes an entry in
+the `OIDC_PROVIDERS_PATH` file.
+
+The state is also bound to the browser that started the login: it gets a cookie holding
+`state_binding(state)`, and a callback whose state does not match the cookie is refused, so
+nobody can finish their own login in someone else's browser.
+
+Redis layout:
+- `oidc_state:{state}` -> JSON `OidcAuthorization` of a login waiting for its callback */
+use std::fs;
+
+use base64::{engine::general_purpose, Engine};
+use environment::Environment;
+use errors::{
+    Error::{DataNotAvailable, DatabaseErrorExecution, StringError, TokenError},
+    Result,
+};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use model::authorization::oidc::{
+    OidcAuthorization, OidcIdentity, OidcProviderConfig, OidcProvidersConfig,
+};
+use redis::{AsyncCommands, Client};
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+/// Minutes a started login waits for its callback.
+pub const AUTHORIZATION_MAX_AGE: i64 = 10;
+
+fn state_key(state: &str) -> String {
+    format!("oidc_state:{}", state)
+}
+
+fn random_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Value of the cookie binding a login's state to the browser that started it.
+pub fn state_binding(state: &str) -> String {
+    format!("{:x}", Sha256::digest(state.as_bytes()))
+}
+
+/// Whether a callback's state belongs to the login started with the `binding` cookie.
+pub fn state_matches(state: &str, binding: &str) -> bool {
+    state_binding(state)
+        .as_bytes()
+        .ct_eq(binding.as_bytes())
+        .into()
+}
+
+/* A started login: where to send the user and the state the callback has to return */
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub state: String,
+}
+
+/* The parts of the discovery document the login flow needs */
+#[derive(Deserialize, Debug)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct IdTokenClaims {
+    sub: String,
+    #[serde(default)]
+    nonce: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: Option<bool>,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// Derives the S256 PKCE code challenge of a code verifier.
+pub fn code_challenge(code_verifier: &str) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Checks an ID token's signature, issuer, audience and nonce, returning who it identifies.
+pub fn validate_id_token(
+    provider: &OidcProviderConfig,
+    jwks: &JwkSet,
+    id_token: &str,
+    nonce: &str,
+) -> Result<OidcIdentity> {
+    let header = decode_header(id_token)?;
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(TokenError(
+            "ID tokens must be signed with an asymmetric key".to_string(),
+        ));
+    }
+
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid),
+        None => jwks.keys.first(),
+    }
+    .ok_or_else(|| TokenError("ID token signed with an unknown key".to_string()))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[&provider.client_id]);
+    validation.set_issuer(&[&provider.issuer]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims =
+        decode::<IdTokenClaims>(id_token, &DecodingKey::from_jwk(jwk)?, &validation)?.claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(TokenError("ID token nonce does not match".to_string()));
+    }
+
+    Ok(OidcIdentity {
+        issuer: provider.issuer.clone(),
+        subject: claims.sub,
+        email: claims.email,
+        email_verified: claims.email_verified.unwrap_or(false),
+        name: claims.name,
+    })
+}
+
+#[derive(Clone)]
+pub struct OidcClient {
+    http: reqwest::Client,
+    providers: Vec<OidcProviderConfig>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcProvidersConfig) -> Self {
+        OidcClient {
+            http: reqwest::Client::new(),
+            providers: config.providers,
+        }
+    }
+
+    /// Loads the providers file; without `OIDC_PROVIDERS_PATH` no provider is configured.
+    pub fn from_env(env: &Environment) -> Result<Self> {
+        let config = if env.oidc_providers_path == "none" {
+            OidcProvidersConfig::default()
+        } else {
+            let content = fs::read_to_string(&env.oidc_providers_path)
+                .map_err(|e| StringError(format!("Failed to read OIDC providers: {}", e)))?;
+            serde_json::from_str(&content)
+                .map_err(|e| StringError(format!("Invalid OIDC providers: {}", e)))?
+        };
+
+        Ok(Self::new(config))
+    }
+
+    pub fn provider(&self, name: &str) -> Result<&OidcProviderConfig> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name == name)
+            .ok_or_else(|| DataNotAvailable(format!("OIDC provider '{}' not found", name)))
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| StringError(format!("OIDC request to {} failed: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| StringError(format!("Invalid OIDC response from {}: {}", url, e)))
+    }
+
+    async fn metadata(&self, provider: &OidcProviderConfig) -> Result<ProviderMetadata> {
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            provider.issuer.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self.get_json(&url).await?;
+
+        if metadata.issuer != provider.issuer {
+            return Err(TokenError(format!(
+                "OIDC discovery issuer '{}' does not match '{}'",
+                metadata.issuer, provider.issuer
+            )));
+        }
+        Ok(metadata)
+    }
+
+    /// Starts a login, returning the URL to send the user to and the state of the login.
+    pub async fn authorization_url(
+        &self,
+        client: &Client,
+        provider_name: &str,
+    ) -> Result<AuthorizationRequest> {
+        let provider = self.provider(provider_name)?;
+        let metadata = self.metadata(provider).await?;
+
+        let state = random_token();
+        let authorization = OidcAuthorization {
+            provider: provider.name.clone(),
+            code_verifier: random_token(),
+            nonce: random_token(),
+        };
+        save_authorization(client, &state, &authorization).await?;
+
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &provider.client_id),
+                ("redirect_uri", &provider.redirect_uri),
+                ("scope", &provider.scopes.join(" ")),
+                ("state", &state),
+                ("nonce", &authorization.nonce),
+                (
+                    "code_challenge",
+                    &code_challenge(&authorization.code_verifier),
+                ),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| StringError(format!("Invalid authorization endpoint: {}", e)))?;
+
+        Ok(AuthorizationRequest {
+            url: url.to_string(),
+            state,
+        })
+    }
+
+    /// Finishes a login from the provider's callback, returning the verified identity.
+    pub async fn complete(
+        &self,
+        client: &Client,
+        provider_name: &str,
+        code: &str,
+        state: &str,
+    ) -> Result<OidcIdentity> {
+        let authorization = consume_authorization(client, state)
+            .await?
+            .filter(|authorization| authorization.provider == provider_name)
+            .ok_or_else(|| TokenError("OIDC state is invalid or has expired".to_string()))?;
+
+        let provider = self.provider(provider_name)?;
+        let metadata = self.metadata(provider).await?;
+
+        let token_response: TokenResponse = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &provider.redirect_uri),
+                ("client_id", &provider.client_id),
+                ("client_secret", &provider.client_secret),
+                ("code_verifier", &authorization.code_verifier),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| TokenError(format!("OIDC code exchange failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| TokenError(format!("Invalid OIDC token response: {}", e)))?;
+
+        let jwks: JwkSet = self.get_json(&metadata.jwks_uri).await?;
+
+        validate_id_token(
+            provider,
+            &jwks,
+            &token_response.id_token,
+            &authorization.nonce,
+        )
+    }
+}
+
+async fn save_authorization(
+    client: &Client,
+    state: &str,
+    authorization: &OidcAuthorization,
+) -> Result<()> {
+    let mut redis_client = client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|_| DatabaseErrorExecution("Failed to connect to Redis".to_string()))?;
+
+    let value = serde_json::to_string(authorization).map_err(|e| StringError(e.to_string()))?;
+    let _: () = redis_client
+        .set_ex(state_key(state), value, (AUTHORIZATION_MAX_AGE * 60) as u64)
+        .await?;
+    Ok(())
+}
+
+/// Takes a pending login out of Redis so its state can only be used once.
+async fn consume_authorization(client: &Client, state: &str) -> Result<Option<OidcAuthorization>> {
+    let mut redis_client = client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|_| DatabaseErrorExecution("Failed to connect to Redis".to_string()))?;
+
+    let value: Option<String> = redis_client.get_del(state_key(state)).await?;
+    value
+        .map(|value| serde_json::from_str(&value).map_err(|e| StringError(e.to_string())))
+        .transpose()
+}
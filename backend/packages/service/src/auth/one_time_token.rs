@@ -0,0 +1,143 @@
+/* Single-use tokens sent out in email links.
+
+Redis layout:
+- `{purpose}:{token}` -> user id the token was issued for, followed by the subject of a bound token
+- `{purpose}_tokens:{user_id}` -> set of the token keys issued to a user, to revoke them early */
+use errors::{Error::DatabaseErrorExecution, Result};
+use redis::{aio::MultiplexedConnection, AsyncCommands, Client};
+use uuid::Uuid;
+
+/* Purposes a single-use token can be issued for */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenPurpose {
+    EmailVerification,
+    PasswordReset,
+    MagicLink,
+}
+
+impl TokenPurpose {
+    fn prefix(&self) -> &'static str {
+        match self {
+            TokenPurpose::EmailVerification => "verify_email",
+            TokenPurpose::PasswordReset => "password_reset",
+            TokenPurpose::MagicLink => "magic_link",
+        }
+    }
+
+    fn key(&self, token: &str) -> String {
+        format!("{}:{}", self.prefix(), token)
+    }
+
+    fn user_tokens_key(&self, user_id: &str) -> String {
+        format!("{}_tokens:{}", self.prefix(), user_id)
+    }
+}
+
+async fn connection(client: &Client) -> Result<MultiplexedConnection> {
+    client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|_| DatabaseErrorExecution("Failed to connect to Redis".to_string()))
+}
+
+async fn save(
+    client: &Client,
+    purpose: TokenPurpose,
+    user_id: &str,
+    value: &str,
+    max_age: i64,
+) -> Result<String> {
+    let mut redis_client = connection(client).await?;
+
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let key = purpose.key(&token);
+    let user_tokens = purpose.user_tokens_key(user_id);
+
+    let _: () = redis_client
+        .set_ex(&key, value, (max_age * 60) as u64)
+        .await?;
+    // Every token of a purpose lives equally long, so the set outlives the ones in it
+    let _: () = redis_client.sadd(&user_tokens, &key).await?;
+    let _: () = redis_client.expire(&user_tokens, max_age * 60).await?;
+
+    Ok(token)
+}
+
+/// Stores a new single-use token pointing at a user and returns it.
+pub async fn save_one_time_token(
+    client: &Client,
+    purpose: TokenPurpose,
+    user_id: &str,
+    max_age: i64,
+) -> Result<String> {
+    save(client, purpose, user_id, user_id, max_age).await
+}
+
+/// Stores a new single-use token pointing at a user and bound to `subject`, such as the address
+/// the link was emailed to, and returns it.
+pub async fn save_bound_one_time_token(
+    client: &Client,
+    purpose: TokenPurpose,
+    user_id: &str,
+    subject: &str,
+    max_age: i64,
+) -> Result<String> {
+    let value = format!("{} {}", user_id, subject);
+    save(client, purpose, user_id, &value, max_age).await
+}
+
+/// Consumes a single-use token, returning the user it was issued for.
+pub async fn consume_one_time_token(
+    client: &Client,
+    purpose: TokenPurpose,
+    token: &str,
+) -> Result<Option<String>> {
+    let mut redis_client = connection(client).await?;
+
+    let user_id: Option<String> = redis_client.get_del(purpose.key(token)).await?;
+    Ok(user_id)
+}
+
+/// Consumes a token made by `save_bound_one_time_token`, returning the user it was issued for
+/// and the subject it is bound to.
+pub async fn consume_bound_one_time_token(
+    client: &Client,
+    purpose: TokenPurpose,
+    token: &str,
+) -> Result<Option<(String, String)>> {
+    let value = consume_one_time_token(client, purpose, token).await?;
+
+    // Tokens issued before they were bound hold the user alone and are refused
+    Ok(value.and_then(|value| {
+        value
+            .split_once(' ')
+            .map(|(user_id, subject)| (user_id.to_string(), subject.to_string()))
+    }))
+}
+
+/// Looks up the user a single-use token was issued for without consuming it.
+pub async fn peek_one_time_token(
+    client: &Client,
+    purpose: TokenPurpose,
+    token: &str,
+) -> Result<Option<String>> {
+    let mut redis_client = connection(client).await?;
+
+    let user_id: Option<String> = redis_client.get(purpose.key(token)).await?;
+    Ok(user_id)
+}
+
+/// Deletes every outstanding token of a purpose issued to a user.
+pub async fn revoke_one_time_tokens(
+    client: &Client,
+    purpose: TokenPurpose,
+    user_id: &str,
+) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let user_tokens = purpose.user_tokens_key(user_id);
+
+    let mut keys: Vec<String> = redis_client.smembers(&user_tokens).await?;
+    keys.push(user_tokens);
+    let _: () = redis_client.del(keys).await?;
+    Ok(())
+}
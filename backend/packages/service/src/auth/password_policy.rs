@@ -0,0 +1,177 @@
+/* Rules a new password has to satisfy, applied on registration, password change and reset.
+
+Breached passwords are looked up offline in a directory of k-anonymity range files, the
+layout produced by the Have I Been Pwned downloader: the SHA-1 of a password is split after
+its first 5 hex characters, and `{prefix}.txt` lists the remaining `{suffix}:{count}` pairs.
+Only the file of the password's prefix is read, the full list never has to be loaded. */
+use std::{borrow::Cow, io::ErrorKind, path::PathBuf};
+
+use environment::Environment;
+use errors::{Error::PasswordRejected, Result};
+use sha1::{Digest, Sha1};
+use validator::{ValidationError, ValidationErrors};
+
+const HASH_PREFIX_LENGTH: usize = 5;
+
+/* Shorter usernames or email local parts match too many passwords by chance */
+const MIN_IDENTIFIER_LENGTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// How many of lowercase, uppercase, digits and symbols a password has to mix
+    pub min_character_classes: usize,
+    /// Directory of breached password range files, `None` disables the check
+    pub breached_passwords_path: Option<PathBuf>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: 8,
+            min_character_classes: 1,
+            breached_passwords_path: None,
+        }
+    }
+}
+
+fn violation(code: &'static str, message: String) -> ValidationError {
+    ValidationError::new(code).with_message(Cow::from(message))
+}
+
+fn character_classes(password: &str) -> usize {
+    let checks: [fn(&char) -> bool; 4] = [
+        |c| c.is_lowercase(),
+        |c| c.is_uppercase(),
+        |c| c.is_numeric(),
+        |c| !c.is_alphanumeric(),
+    ];
+    checks
+        .iter()
+        .filter(|check| password.chars().any(|c| check(&c)))
+        .count()
+}
+
+impl PasswordPolicy {
+    pub fn from_env(env: &Environment) -> Self {
+        let breached_passwords_path = match env.breached_passwords_path.as_str() {
+            "none" => None,
+            path => Some(PathBuf::from(path)),
+        };
+
+        PasswordPolicy {
+            min_length: env.password_min_length,
+            min_character_classes: env.password_min_character_classes,
+            breached_passwords_path,
+        }
+    }
+
+    /// Checks a password against every rule, reporting all broken rules under `field`.
+    ///
+    /// `username` and `email` must not appear in the password, ignoring case.
+    pub async fn check(
+        &self,
+        field: &'static str,
+        password: &str,
+        username: &str,
+        email: &str,
+    ) -> Result<()> {
+        let mut errors = ValidationErrors::new();
+
+        let length = password.chars().count();
+        if length < self.min_length {
+            let mut error = violation(
+                "too_short",
+                format!("Password must be at least {} characters", self.min_length),
+            );
+            error.add_param(Cow::from("min"), &self.min_length);
+            errors.add(field, error);
+        }
+
+        if character_classes(password) < self.min_character_classes {
+            let mut error = violation(
+                "too_few_character_classes",
+                format!(
+                    "Password must mix at least {} of lowercase letters, uppercase letters, digits and symbols",
+                    self.min_character_classes
+                ),
+            );
+            error.add_param(Cow::from("min"), &self.min_character_classes);
+            errors.add(field, error);
+        }
+
+        let lowercase_password = password.to_lowercase();
+        let contains = |identifier: &str| {
+            identifier.chars().count() >= MIN_IDENTIFIER_LENGTH
+                && lowercase_password.contains(&identifier.to_lowercase())
+        };
+        if contains(username.trim()) {
+            errors.add(
+                field,
+                violation(
+                    "contains_username",
+                    "Password must not contain the username".to_string(),
+                ),
+            );
+        }
+        let email_local_part = email.trim().split('@').next().unwrap_or_default();
+        if contains(email_local_part) {
+            errors.add(
+                field,
+                violation(
+                    "contains_email",
+                    "Password must not contain the email address".to_string(),
+                ),
+            );
+        }
+
+        if let Some(count) = self.breach_count(password).await {
+            let mut error = violation(
+                "breached",
+                "Password appeared in a data breach, choose another one".to_string(),
+            );
+            error.add_param(Cow::from("count"), &count);
+            errors.add(field, error);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(PasswordRejected(errors))
+        }
+    }
+
+    /// Times a password was seen in breaches, `None` when it is not in the list.
+    ///
+    /// A missing or unreadable range file is logged and treated as not breached, so an
+    /// incomplete download never blocks users from setting a password.
+    pub async fn breach_count(&self, password: &str) -> Option<u64> {
+        let directory = self.breached_passwords_path.as_ref()?;
+
+        let hash: String = Sha1::digest(password.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        let (prefix, suffix) = hash.split_at(HASH_PREFIX_LENGTH);
+
+        let path = directory.join(format!("{}.txt", prefix));
+        let ranges = match tokio::fs::read_to_string(&path).await {
+            Ok(ranges) => ranges,
+            Err(e) => {
+                if e.kind() == ErrorKind::NotFound {
+                    tracing::warn!("Breached password range {} is missing", path.display());
+                } else {
+                    tracing::error!("Failed to read {}: {}", path.display(), e);
+                }
+                return None;
+            }
+        };
+
+        ranges.lines().find_map(|line| {
+            let (line_suffix, count) = line.trim().split_once(':')?;
+            let count = count.trim().parse::<u64>().ok()?;
+            // Padded range files contain fake entries with a count of 0
+            (count > 0 && line_suffix.eq_ignore_ascii_case(suffix)).then_some(count)
+        })
+    }
+}
@@ -0,0 +1,261 @@
+/* A session is the family of access/refresh tokens descending from a single login.
+
+Redis layout:
+- `session:{session_id}` -> hash with the session metadata
+- `token_session:{token_uuid}` -> session id the token belongs to
+- `session_tokens:{session_id}` -> set of token uuids issued for the session
+- `used_refresh:{token_uuid}` -> session id of an already rotated refresh token
+- `user_sessions:{user_id}` -> set of session ids belonging to a user */
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use errors::{Error::DatabaseErrorExecution, Result};
+use model::authorization::{session::SessionDetails, token::TokenDetails};
+use redis::{aio::MultiplexedConnection, AsyncCommands, Client};
+
+fn session_key(session_id: &str) -> String {
+    format!("session:{}", session_id)
+}
+
+fn token_session_key(token_uuid: &str) -> String {
+    format!("token_session:{}", token_uuid)
+}
+
+fn session_tokens_key(session_id: &str) -> String {
+    format!("session_tokens:{}", session_id)
+}
+
+fn used_refresh_key(token_uuid: &str) -> String {
+    format!("used_refresh:{}", token_uuid)
+}
+
+fn user_sessions_key(user_id: &str) -> String {
+    format!("user_sessions:{}", user_id)
+}
+
+async fn connection(client: &Client) -> Result<MultiplexedConnection> {
+    client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|_| DatabaseErrorExecution("Failed to connect to Redis".to_string()))
+}
+
+/// Pushes the expiry of a key out to `max_age` minutes, never shortening it.
+async fn extend_ttl(
+    redis_client: &mut MultiplexedConnection,
+    key: &str,
+    max_age: i64,
+) -> Result<()> {
+    let ttl: i64 = redis_client.ttl(key).await?;
+    if ttl < max_age * 60 {
+        let _: () = redis_client.expire(key, max_age * 60).await?;
+    }
+    Ok(())
+}
+
+fn timestamp_to_datetime(value: Option<&String>) -> DateTime<Utc> {
+    value
+        .and_then(|timestamp| timestamp.parse().ok())
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        .unwrap_or_default()
+}
+
+/// Records the metadata of a new session.
+pub async fn create_session(client: &Client, session: &SessionDetails, max_age: i64) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let key = session_key(&session.session_id);
+
+    let _: () = redis_client
+        .hset_multiple(
+            &key,
+            &[
+                ("user_id", session.user_id.clone()),
+                ("ip", session.ip.clone().unwrap_or_default()),
+                ("user_agent", session.user_agent.clone().unwrap_or_default()),
+                ("created_at", session.created_at.timestamp().to_string()),
+                ("last_seen", session.last_seen.timestamp().to_string()),
+            ],
+        )
+        .await?;
+    extend_ttl(&mut redis_client, &key, max_age).await
+}
+
+/// Returns the metadata of a live session.
+pub async fn get_session(client: &Client, session_id: &str) -> Result<Option<SessionDetails>> {
+    let mut redis_client = connection(client).await?;
+    let fields: HashMap<String, String> = redis_client.hgetall(session_key(session_id)).await?;
+
+    let Some(user_id) = fields.get("user_id").cloned() else {
+        return Ok(None);
+    };
+
+    let non_empty = |field: &str| fields.get(field).filter(|value| !value.is_empty()).cloned();
+
+    Ok(Some(SessionDetails {
+        session_id: session_id.to_string(),
+        user_id,
+        ip: non_empty("ip"),
+        user_agent: non_empty("user_agent"),
+        created_at: timestamp_to_datetime(fields.get("created_at")),
+        last_seen: timestamp_to_datetime(fields.get("last_seen")),
+    }))
+}
+
+/// Updates the last time a session was used.
+pub async fn touch_session(client: &Client, session_id: &str) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let key = session_key(session_id);
+
+    // Only touch live sessions, otherwise HSET would recreate an expired one without a TTL
+    let exists: bool = redis_client.exists(&key).await?;
+    if exists {
+        let _: () = redis_client
+            .hset(&key, "last_seen", Utc::now().timestamp().to_string())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Returns every live session of a user, most recently created first.
+pub async fn get_user_sessions(client: &Client, user_id: &str) -> Result<Vec<SessionDetails>> {
+    let mut redis_client = connection(client).await?;
+    let session_ids: Vec<String> = redis_client.smembers(user_sessions_key(user_id)).await?;
+
+    let mut sessions = Vec::with_capacity(session_ids.len());
+    for session_id in &session_ids {
+        match get_session(client, session_id).await? {
+            Some(session) => sessions.push(session),
+            None => {
+                let _: () = redis_client
+                    .srem(user_sessions_key(user_id), session_id)
+                    .await?;
+            }
+        }
+    }
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.created_at));
+    Ok(sessions)
+}
+
+/// Attaches a freshly issued token to its session so it can be revoked with the family.
+pub async fn save_session_token(
+    client: &Client,
+    session_id: &str,
+    token_details: &TokenDetails,
+    max_age: i64,
+) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let token_uuid = token_details.token_uuid.to_string();
+    let session_tokens = session_tokens_key(session_id);
+    let user_sessions = user_sessions_key(&token_details.user_id);
+
+    let _: () = redis_client
+        .set_ex(
+            token_session_key(&token_uuid),
+            session_id,
+            (max_age * 60) as u64,
+        )
+        .await?;
+    let _: () = redis_client.sadd(&session_tokens, &token_uuid).await?;
+    let _: () = redis_client.sadd(&user_sessions, session_id).await?;
+
+    extend_ttl(&mut redis_client, &session_tokens, max_age).await?;
+    extend_ttl(&mut redis_client, &user_sessions, max_age).await?;
+    extend_ttl(&mut redis_client, &session_key(session_id), max_age).await
+}
+
+/// Returns the session a live token belongs to.
+pub async fn get_token_session(client: &Client, token_uuid: &str) -> Result<Option<String>> {
+    let mut redis_client = connection(client).await?;
+    let session_id: Option<String> = redis_client.get(token_session_key(token_uuid)).await?;
+    Ok(session_id)
+}
+
+/// Detaches a single token from its session.
+pub async fn delete_session_token(
+    client: &Client,
+    session_id: &str,
+    token_uuid: &str,
+) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let _: () = redis_client.del(token_session_key(token_uuid)).await?;
+    let _: () = redis_client
+        .srem(session_tokens_key(session_id), token_uuid)
+        .await?;
+    Ok(())
+}
+
+/// Marks a refresh token as consumed.
+///
+/// Returns `false` when the token had already been consumed, which means it is being replayed.
+pub async fn mark_refresh_token_used(
+    client: &Client,
+    token_uuid: &str,
+    session_id: &str,
+    max_age: i64,
+) -> Result<bool> {
+    let mut redis_client = connection(client).await?;
+    let marked: Option<String> = redis::cmd("SET")
+        .arg(used_refresh_key(token_uuid))
+        .arg(session_id)
+        .arg("NX")
+        .arg("EX")
+        .arg(max_age * 60)
+        .query_async(&mut redis_client)
+        .await?;
+    Ok(marked.is_some())
+}
+
+/// Returns the session of a refresh token that has already been rotated.
+pub async fn get_used_refresh_token_session(
+    client: &Client,
+    token_uuid: &str,
+) -> Result<Option<String>> {
+    let mut redis_client = connection(client).await?;
+    let session_id: Option<String> = redis_client.get(used_refresh_key(token_uuid)).await?;
+    Ok(session_id)
+}
+
+/// Deletes every token issued for a session along with its metadata.
+pub async fn revoke_session(client: &Client, user_id: &str, session_id: &str) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let session_tokens = session_tokens_key(session_id);
+    let token_uuids: Vec<String> = redis_client.smembers(&session_tokens).await?;
+
+    for token_uuid in &token_uuids {
+        let _: () = redis_client
+            .del(&[token_uuid.clone(), token_session_key(token_uuid)])
+            .await?;
+    }
+
+    let _: () = redis_client
+        .del(&[session_tokens, session_key(session_id)])
+        .await?;
+    let _: () = redis_client
+        .srem(user_sessions_key(user_id), session_id)
+        .await?;
+    Ok(())
+}
+
+/// Deletes every session belonging to a user.
+pub async fn revoke_user_sessions(client: &Client, user_id: &str) -> Result<()> {
+    revoke_other_sessions(client, user_id, None).await
+}
+
+/// Deletes every session belonging to a user except `keep_session_id`.
+pub async fn revoke_other_sessions(
+    client: &Client,
+    user_id: &str,
+    keep_session_id: Option<&str>,
+) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let session_ids: Vec<String> = redis_client.smembers(user_sessions_key(user_id)).await?;
+
+    for session_id in &session_ids {
+        if keep_session_id != Some(session_id.as_str()) {
+            revoke_session(client, user_id, session_id).await?;
+        }
+    }
+
+    Ok(())
+}
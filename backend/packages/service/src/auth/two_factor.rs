@@ -0,0 +1,244 @@
+/* TOTP second factor and the login challenges issued while it is pending.
+
+Redis layout:
+- `totp_enrollment:{user_id}` -> base32 secret waiting for its first code to be confirmed
+- `totp_last_step:{user_id}` -> time step of the last accepted code, older and equal steps are refused
+- `login_challenge:{token}` -> hash with the user a password-verified login is waiting on */
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use errors::{
+    Error::{DatabaseErrorExecution, StringError},
+    Result,
+};
+use model::authorization::two_factor::LoginChallenge;
+use redis::{aio::MultiplexedConnection, AsyncCommands, Client, Script};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+const ISSUER: &str = "VirtuMart";
+const RECOVERY_CODE_COUNT: usize = 10;
+const ENROLLMENT_MAX_AGE: i64 = 10;
+const CHALLENGE_MAX_AGE: i64 = 5;
+const CHALLENGE_MAX_ATTEMPTS: i64 = 5;
+const STEP_SECONDS: u64 = 30;
+/* Codes are accepted one step either side of the current one */
+const STEP_SKEW: u64 = 1;
+
+/* Stores the step unless it is not newer than the stored one, returning whether it did */
+const ACCEPT_STEP_SCRIPT: &str = r"
+local last = tonumber(redis.call('GET', KEYS[1]))
+if last and last >= tonumber(ARGV[1]) then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+return 1
+";
+
+fn enrollment_key(user_id: &str) -> String {
+    format!("totp_enrollment:{}", user_id)
+}
+
+fn last_step_key(user_id: &str) -> String {
+    format!("totp_last_step:{}", user_id)
+}
+
+fn challenge_key(token: &str) -> String {
+    format!("login_challenge:{}", token)
+}
+
+async fn connection(client: &Client) -> Result<MultiplexedConnection> {
+    client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|_| DatabaseErrorExecution("Failed to connect to Redis".to_string()))
+}
+
+fn totp(secret: &str, account_name: &str) -> Result<TOTP> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| StringError(e.to_string()))?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        STEP_SKEW as u8,
+        STEP_SECONDS,
+        secret,
+        Some(ISSUER.to_string()),
+        account_name.to_string(),
+    )
+    .map_err(|e| StringError(e.to_string()))
+}
+
+/// Generates a new base32 encoded TOTP secret.
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+/// Builds the `otpauth://` URI authenticator apps enroll from.
+pub fn otpauth_uri(secret: &str, account_name: &str) -> Result<String> {
+    Ok(totp(secret, account_name)?.get_url())
+}
+
+/// Checks a code against the current time step, allowing one step of clock drift.
+///
+/// Returns the time step the code belongs to, which `accept_code_step` uses to keep the code from
+/// being replayed.
+pub fn verify_code(secret: &str, code: &str) -> Result<Option<u64>> {
+    let totp = totp(secret, "")?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| StringError(e.to_string()))?
+        .as_secs();
+    let current_step = now / STEP_SECONDS;
+    let code = code.trim().as_bytes();
+
+    let step = (current_step.saturating_sub(STEP_SKEW)..=current_step + STEP_SKEW).find(|step| {
+        totp.generate(step * STEP_SECONDS)
+            .as_bytes()
+            .ct_eq(code)
+            .into()
+    });
+    Ok(step)
+}
+
+/// Records the time step of a verified code as used by the user.
+///
+/// Returns `false` when a code of the same or a later step was already accepted, so every code is
+/// only good once even though it stays valid for a few steps.
+pub async fn accept_code_step(client: &Client, user_id: &str, step: u64) -> Result<bool> {
+    let mut redis_client = connection(client).await?;
+    // Steps outside the skew window are refused by `verify_code` anyway
+    let max_age = STEP_SECONDS * (2 * STEP_SKEW + 1);
+
+    let accepted: i64 = Script::new(ACCEPT_STEP_SCRIPT)
+        .key(last_step_key(user_id))
+        .arg(step)
+        .arg(max_age)
+        .invoke_async(&mut redis_client)
+        .await?;
+    Ok(accepted == 1)
+}
+
+/// Verifies a code and uses it up, see `verify_code` and `accept_code_step`.
+pub async fn verify_code_once(
+    client: &Client,
+    user_id: &str,
+    secret: &str,
+    code: &str,
+) -> Result<bool> {
+    match verify_code(secret, code)? {
+        Some(step) => accept_code_step(client, user_id, step).await,
+        None => Ok(false),
+    }
+}
+
+/// Hashes a recovery code the way it is stored.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized = code.trim().to_lowercase();
+    format!("{:x}", Sha256::digest(normalized.as_bytes()))
+}
+
+/// Generates a fresh set of recovery codes, returning them with their hashes.
+pub fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code = Uuid::new_v4().simple().to_string();
+            format!("{}-{}", &code[..5], &code[5..10])
+        })
+        .collect();
+    let hashes = codes.iter().map(|code| hash_recovery_code(code)).collect();
+    (codes, hashes)
+}
+
+/// Keeps a secret until the user confirms it with a first code.
+pub async fn save_enrollment_secret(client: &Client, user_id: &str, secret: &str) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let _: () = redis_client
+        .set_ex(
+            enrollment_key(user_id),
+            secret,
+            (ENROLLMENT_MAX_AGE * 60) as u64,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Returns the secret a user is enrolling with.
+pub async fn get_enrollment_secret(client: &Client, user_id: &str) -> Result<Option<String>> {
+    let mut redis_client = connection(client).await?;
+    let secret: Option<String> = redis_client.get(enrollment_key(user_id)).await?;
+    Ok(secret)
+}
+
+/// Forgets a pending enrollment once it is confirmed.
+pub async fn delete_enrollment_secret(client: &Client, user_id: &str) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let _: () = redis_client.del(enrollment_key(user_id)).await?;
+    Ok(())
+}
+
+/// Stores a login waiting for its second factor and returns the challenge token.
+pub async fn create_login_challenge(client: &Client, challenge: &LoginChallenge) -> Result<String> {
+    let mut redis_client = connection(client).await?;
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let key = challenge_key(&token);
+
+    let _: () = redis_client
+        .hset_multiple(
+            &key,
+            &[
+                ("user_id", challenge.user_id.clone()),
+                ("user_role", challenge.user_role.clone()),
+                ("email", challenge.email.clone()),
+                ("setup_required", challenge.setup_required.to_string()),
+                ("attempts", "0".to_string()),
+            ],
+        )
+        .await?;
+    let _: () = redis_client.expire(&key, CHALLENGE_MAX_AGE * 60).await?;
+
+    Ok(token)
+}
+
+/// Returns the login a challenge token is waiting on.
+pub async fn get_login_challenge(client: &Client, token: &str) -> Result<Option<LoginChallenge>> {
+    let mut redis_client = connection(client).await?;
+    let fields: HashMap<String, String> = redis_client.hgetall(challenge_key(token)).await?;
+
+    let field = |name: &str| fields.get(name).cloned().unwrap_or_default();
+    Ok(fields.contains_key("user_id").then(|| LoginChallenge {
+        user_id: field("user_id"),
+        user_role: field("user_role"),
+        email: field("email"),
+        setup_required: field("setup_required") == "true",
+    }))
+}
+
+/// Counts a wrong code against a challenge, dropping the challenge after too many.
+pub async fn record_challenge_failure(client: &Client, token: &str) -> Result<()> {
+    let mut redis_client = connection(client).await?;
+    let key = challenge_key(token);
+
+    let attempts: i64 = redis_client.hincr(&key, "attempts", 1).await?;
+    // A challenge that expired in the meantime was recreated by the increment, without a TTL
+    let ttl: i64 = redis_client.ttl(&key).await?;
+    if attempts >= CHALLENGE_MAX_ATTEMPTS || ttl < 0 {
+        let _: () = redis_client.del(&key).await?;
+    }
+    Ok(())
+}
+
+/// Consumes a challenge once its second factor has been verified.
+///
+/// Returns `false` when a concurrent request already consumed it.
+pub async fn delete_login_challenge(client: &Client, token: &str) -> Result<bool> {
+    let mut redis_client = connection(client).await?;
+    let deleted: i64 = redis_client.del(challenge_key(token)).await?;
+    Ok(deleted == 1)
+}
@@ -0,0 +1,95 @@
+use environment::Environment;
+use errors::Result;
+use redis::Client;
+
+use super::one_time_token::{save_bound_one_time_token, save_one_time_token, TokenPurpose};
+use crate::mail::mailer::Mailer;
+
+/// Lifetime of an email verification link in minutes.
+const VERIFICATION_TOKEN_MAX_AGE: i64 = 60 * 24;
+
+/// Lifetime of a password reset link in minutes.
+const PASSWORD_RESET_TOKEN_MAX_AGE: i64 = 30;
+
+/// Lifetime of a magic login link in minutes.
+const MAGIC_LINK_TOKEN_MAX_AGE: i64 = 15;
+
+/// Issues a verification token for a user and emails the verification link.
+///
+/// The token is bound to `email`, so it verifies nothing once the account has moved to another
+/// address.
+pub async fn send_verification_email(
+    client: &Client,
+    mailer: &Mailer,
+    user_id: &str,
+    email: &str,
+) -> Result<()> {
+    let env = Environment::new();
+    let token = save_bound_one_time_token(
+        client,
+        TokenPurpose::EmailVerification,
+        user_id,
+        email,
+        VERIFICATION_TOKEN_MAX_AGE,
+    )
+    .await?;
+
+    let body = format!(
+        "Welcome to VirtuMart!\n\nPlease confirm your email address by opening the link below:\n\n{}{}\n\nThe link expires in 24 hours.",
+        env.host_name, token
+    );
+
+    mailer
+        .send(email, "Verify your VirtuMart account", body)
+        .await
+}
+
+/// Issues a password reset token for a user and emails the reset link.
+pub async fn send_password_reset_email(
+    client: &Client,
+    mailer: &Mailer,
+    user_id: &str,
+    email: &str,
+) -> Result<()> {
+    let env = Environment::new();
+    let token = save_one_time_token(
+        client,
+        TokenPurpose::PasswordReset,
+        user_id,
+        PASSWORD_RESET_TOKEN_MAX_AGE,
+    )
+    .await?;
+
+    let body = format!(
+        "A password reset was requested for your VirtuMart account.\n\nChoose a new password by opening the link below:\n\n{}{}\n\nThe link expires in 30 minutes. If you did not request this, you can ignore this email.",
+        env.password_reset_url, token
+    );
+
+    mailer
+        .send(email, "Reset your VirtuMart password", body)
+        .await
+}
+
+/// Issues a magic login token for a user and emails the login link.
+pub async fn send_magic_link_email(
+    client: &Client,
+    mailer: &Mailer,
+    user_id: &str,
+    email: &str,
+) -> Result<()> {
+    let env = Environment::new();
+    let token = save_one_time_token(
+        client,
+        TokenPurpose::MagicLink,
+        user_id,
+        MAGIC_LINK_TOKEN_MAX_AGE,
+    )
+    .await?;
+
+    let body = format!(
+        "Log in to VirtuMart by opening the link below:\n\n{}{}\n\nThe link expires in 15 minutes and works only once. If you did not request it, you can ignore this email.",
+        env.magic_link_url, token
+    );
+
+    mailer.send(email, "Your VirtuMart login link", body).await
+}
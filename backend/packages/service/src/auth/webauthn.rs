@@ -0,0 +1,481 @@
+/* Passkey (WebAuthn) registration and login ceremonies.
+
+Only the relying party side is implemented: challenges, client data, authenticator data and
+ES256 / RS256 assertion signatures. Attestation is not requested, so attestation statements
+are not checked and any authenticator the user chooses is accepted. Passkeys replace the
+password, so user verification (PIN or biometrics) is required in both ceremonies.
+
+Redis layout:
+- `webauthn_registration:{challenge}` -> user a passkey registration was started for
+- `webauthn_authentication:{challenge}` -> marker of a passkey login waiting for its assertion */
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::Value;
+use environment::Environment;
+use errors::{
+    Error::{self, DataNotAvailable, DatabaseErrorExecution, UserUnauthorized},
+    Result,
+};
+use model::{
+    authorization::webauthn::VerifiedCredential,
+    domain::passkey::Passkey,
+    web::passkey::passkey_request::{PasskeyLogin, PasskeyRegistration},
+};
+use p256::ecdsa::{signature::Verifier, Signature as EcdsaSignature, VerifyingKey};
+use redis::{aio::MultiplexedConnection, AsyncCommands, Client};
+use rsa::{pkcs1v15, BigUint, RsaPublicKey};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const CHALLENGE_MAX_AGE: i64 = 5;
+
+const COSE_ALG_ES256: i64 = -7;
+const COSE_ALG_RS256: i64 = -257;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+fn registration_key(challenge: &str) -> String {
+    format!("webauthn_registration:{}", challenge)
+}
+
+fn authentication_key(challenge: &str) -> String {
+    format!("webauthn_authentication:{}", challenge)
+}
+
+async fn connection(client: &Client) -> Result<MultiplexedConnection> {
+    client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|_| DatabaseErrorExecution("Failed to connect to Redis".to_string()))
+}
+
+fn rejected(reason: &str) -> Error {
+    UserUnauthorized(format!("Passkey rejected: {}", reason))
+}
+
+fn decode(field: &str, value: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| rejected(&format!("{} is not base64url", field)))
+}
+
+fn random_challenge() -> String {
+    let mut bytes = Uuid::new_v4().into_bytes().to_vec();
+    bytes.extend(Uuid::new_v4().into_bytes());
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[derive(Deserialize, Debug)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony: String,
+    challenge: String,
+    origin: String,
+    #[serde(default, rename = "crossOrigin")]
+    cross_origin: bool,
+}
+
+fn parse_client_data(client_data_json: &[u8]) -> Result<ClientData> {
+    serde_json::from_slice(client_data_json).map_err(|_| rejected("invalid client data"))
+}
+
+/// Returns the challenge a ceremony answered, to look up its pending state.
+pub fn client_data_challenge(client_data_json: &str) -> Result<String> {
+    Ok(parse_client_data(&decode("client_data_json", client_data_json)?)?.challenge)
+}
+
+struct AttestedCredential {
+    credential_id: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+struct AuthenticatorData {
+    rp_id_hash: Vec<u8>,
+    flags: u8,
+    sign_count: u32,
+    attested_credential: Option<AttestedCredential>,
+}
+
+fn parse_authenticator_data(data: &[u8]) -> Result<AuthenticatorData> {
+    let too_short = || rejected("authenticator data is too short");
+
+    if data.len() < 37 {
+        return Err(too_short());
+    }
+    let flags = data[32];
+    let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+    let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+        // AAGUID (16 bytes), credential id length (2 bytes), credential id, COSE public key
+        let rest = data
+            .get(37..)
+            .filter(|rest| rest.len() >= 18)
+            .ok_or_else(too_short)?;
+        let id_length = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let rest = &rest[18..];
+        if rest.len() < id_length {
+            return Err(too_short());
+        }
+        let (credential_id, key_and_extensions) = rest.split_at(id_length);
+
+        // The key is followed by optional extensions, decoding it tells where it ends
+        let mut remaining = key_and_extensions;
+        let _: Value = ciborium::from_reader(&mut remaining)
+            .map_err(|_| rejected("invalid credential public key"))?;
+        let key_length = key_and_extensions.len() - remaining.len();
+
+        Some(AttestedCredential {
+            credential_id: credential_id.to_vec(),
+            public_key: key_and_extensions[..key_length].to_vec(),
+        })
+    } else {
+        None
+    };
+
+    Ok(AuthenticatorData {
+        rp_id_hash: data[..32].to_vec(),
+        flags,
+        sign_count,
+        attested_credential,
+    })
+}
+
+enum PublicKey {
+    Es256(VerifyingKey),
+    Rs256(pkcs1v15::VerifyingKey<Sha256>),
+}
+
+impl PublicKey {
+    /// Parses a COSE_Key, accepting ES256 (P-256) and RS256 credentials.
+    fn from_cose(bytes: &[u8]) -> Result<Self> {
+        let invalid = || rejected("unsupported credential public key");
+
+        let value: Value = ciborium::from_reader(bytes).map_err(|_| invalid())?;
+        let map = value.as_map().ok_or_else(invalid)?;
+        let field = |label: i64| {
+            map.iter()
+                .find(|(key, _)| key.as_integer().map(i128::from) == Some(label.into()))
+                .map(|(_, value)| value)
+        };
+        let integer = |label: i64| {
+            field(label)
+                .and_then(Value::as_integer)
+                .map(i128::from)
+                .ok_or_else(invalid)
+        };
+        let bytes = |label: i64| field(label).and_then(Value::as_bytes).ok_or_else(invalid);
+
+        match integer(3)? as i64 {
+            COSE_ALG_ES256 => {
+                // kty EC2 on curve P-256
+                if integer(1)? != 2 || integer(-1)? != 1 {
+                    return Err(invalid());
+                }
+                let mut point = vec![0x04];
+                point.extend(bytes(-2)?);
+                point.extend(bytes(-3)?);
+                VerifyingKey::from_sec1_bytes(&point)
+                    .map(PublicKey::Es256)
+                    .map_err(|_| invalid())
+            }
+            COSE_ALG_RS256 => {
+                // kty RSA
+                if integer(1)? != 3 {
+                    return Err(invalid());
+                }
+                let key = RsaPublicKey::new(
+                    BigUint::from_bytes_be(bytes(-1)?),
+                    BigUint::from_bytes_be(bytes(-2)?),
+                )
+                .map_err(|_| invalid())?;
+                Ok(PublicKey::Rs256(pkcs1v15::VerifyingKey::new(key)))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match self {
+            PublicKey::Es256(key) => EcdsaSignature::from_der(signature)
+                .is_ok_and(|signature| key.verify(message, &signature).is_ok()),
+            PublicKey::Rs256(key) => pkcs1v15::Signature::try_from(signature)
+                .is_ok_and(|signature| key.verify(message, &signature).is_ok()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Webauthn {
+    rp_id: String,
+    rp_name: String,
+    origin: String,
+}
+
+impl Webauthn {
+    pub fn new(rp_id: &str, rp_name: &str, origin: &str) -> Self {
+        Webauthn {
+            rp_id: rp_id.to_string(),
+            rp_name: rp_name.to_string(),
+            origin: origin.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Reads the relying party; passkeys stay disabled without `WEBAUTHN_RP_ID`.
+    pub fn from_env(env: &Environment) -> Self {
+        Self::new(
+            &env.webauthn_rp_id,
+            &env.webauthn_rp_name,
+            &env.webauthn_origin,
+        )
+    }
+
+    fn ensure_enabled(&self) -> Result<()> {
+        if self.rp_id == "none" || self.origin == "none" {
+            return Err(DataNotAvailable("Passkeys are not configured".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Creation options for `navigator.credentials.create()`, in their JSON serialization.
+    pub fn registration_options(
+        &self,
+        challenge: &str,
+        user_id: &str,
+        user_name: &str,
+        exclude_credentials: &[String],
+    ) -> JsonValue {
+        let rp_name = if self.rp_name == "none" {
+            &self.rp_id
+        } else {
+            &self.rp_name
+        };
+
+        json!({
+            "challenge": challenge,
+            "rp": { "id": self.rp_id, "name": rp_name },
+            "user": {
+                "id": URL_SAFE_NO_PAD.encode(user_id),
+                "name": user_name,
+                "displayName": user_name
+            },
+            "pubKeyCredParams": [
+                { "type": "public-key", "alg": COSE_ALG_ES256 },
+                { "type": "public-key", "alg": COSE_ALG_RS256 }
+            ],
+            "timeout": CHALLENGE_MAX_AGE * 60 * 1000,
+            "attestation": "none",
+            "authenticatorSelection": {
+                "residentKey": "required",
+                "requireResidentKey": true,
+                "userVerification": "required"
+            },
+            "excludeCredentials": exclude_credentials
+                .iter()
+                .map(|id| json!({ "type": "public-key", "id": id }))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// Request options for `navigator.credentials.get()`, in their JSON serialization.
+    ///
+    /// No credentials are listed, the authenticator offers the passkeys it holds for this site.
+    pub fn authentication_options(&self, challenge: &str) -> JsonValue {
+        json!({
+            "challenge": challenge,
+            "rpId": self.rp_id,
+            "timeout": CHALLENGE_MAX_AGE * 60 * 1000,
+            "userVerification": "required",
+            "allowCredentials": []
+        })
+    }
+
+    fn verify_client_data(
+        &self,
+        client_data: &ClientData,
+        ceremony: &str,
+        challenge: &str,
+    ) -> Result<()> {
+        if client_data.ceremony != ceremony {
+            return Err(rejected("wrong ceremony type"));
+        }
+        if client_data.challenge != challenge {
+            return Err(rejected("challenge does not match"));
+        }
+        if client_data.origin != self.origin || client_data.cross_origin {
+            return Err(rejected("origin does not match"));
+        }
+        Ok(())
+    }
+
+    fn verify_authenticator_data(&self, authenticator_data: &AuthenticatorData) -> Result<()> {
+        if authenticator_data.rp_id_hash != Sha256::digest(self.rp_id.as_bytes()).as_slice() {
+            return Err(rejected("credential belongs to another site"));
+        }
+        if authenticator_data.flags & FLAG_USER_PRESENT == 0 {
+            return Err(rejected("user presence was not confirmed"));
+        }
+        if authenticator_data.flags & FLAG_USER_VERIFIED == 0 {
+            return Err(rejected("user verification was not performed"));
+        }
+        Ok(())
+    }
+
+    /// Checks an attestation answering `challenge`, returning the credential to store.
+    pub fn verify_registration(
+        &self,
+        challenge: &str,
+        registration: &PasskeyRegistration,
+    ) -> Result<VerifiedCredential> {
+        let client_data =
+            parse_client_data(&decode("client_data_json", &registration.client_data_json)?)?;
+        self.verify_client_data(&client_data, "webauthn.create", challenge)?;
+
+        let attestation_object = decode("attestation_object", &registration.attestation_object)?;
+        let attestation: Value = ciborium::from_reader(attestation_object.as_slice())
+            .map_err(|_| rejected("invalid attestation object"))?;
+        let auth_data = attestation
+            .as_map()
+            .and_then(|map| {
+                map.iter()
+                    .find(|(key, _)| key.as_text() == Some("authData"))
+                    .and_then(|(_, value)| value.as_bytes())
+            })
+            .ok_or_else(|| rejected("attestation object has no authenticator data"))?;
+
+        let authenticator_data = parse_authenticator_data(auth_data)?;
+        self.verify_authenticator_data(&authenticator_data)?;
+        let credential = authenticator_data
+            .attested_credential
+            .ok_or_else(|| rejected("no credential was created"))?;
+        PublicKey::from_cose(&credential.public_key)?;
+
+        Ok(VerifiedCredential {
+            credential_id: URL_SAFE_NO_PAD.encode(credential.credential_id),
+            public_key: URL_SAFE_NO_PAD.encode(credential.public_key),
+            sign_count: authenticator_data.sign_count,
+        })
+    }
+
+    /// Checks an assertion answering `challenge` made with `passkey`, returning its new counter.
+    pub fn verify_authentication(
+        &self,
+        challenge: &str,
+        passkey: &Passkey,
+        login: &PasskeyLogin,
+    ) -> Result<u32> {
+        let client_data_json = decode("client_data_json", &login.client_data_json)?;
+        let client_data = parse_client_data(&client_data_json)?;
+        self.verify_client_data(&client_data, "webauthn.get", challenge)?;
+
+        if login.credential_id.trim_end_matches('=') != passkey.credential_id {
+            return Err(rejected("unknown credential"));
+        }
+        if let Some(user_handle) = &login.user_handle {
+            if decode("user_handle", user_handle)? != passkey.user_id.as_bytes() {
+                return Err(rejected("credential belongs to another user"));
+            }
+        }
+
+        let raw_authenticator_data = decode("authenticator_data", &login.authenticator_data)?;
+        let authenticator_data = parse_authenticator_data(&raw_authenticator_data)?;
+        self.verify_authenticator_data(&authenticator_data)?;
+
+        let mut signed = raw_authenticator_data;
+        signed.extend(Sha256::digest(&client_data_json));
+        let public_key = PublicKey::from_cose(&decode("public_key", &passkey.public_key)?)?;
+        if !public_key.verify(&signed, &decode("signature", &login.signature)?) {
+            return Err(rejected("invalid signature"));
+        }
+
+        // Authenticators without a counter always report 0, anything else must keep increasing
+        let sign_count = authenticator_data.sign_count;
+        if (sign_count != 0 || passkey.sign_count != 0) && sign_count <= passkey.sign_count {
+            return Err(rejected(
+                "signature counter went backwards, the key may be cloned",
+            ));
+        }
+
+        Ok(sign_count)
+    }
+
+    /// Starts registering a passkey for a user, returning the creation options.
+    pub async fn start_registration(
+        &self,
+        client: &Client,
+        user_id: &str,
+        user_name: &str,
+        exclude_credentials: &[String],
+    ) -> Result<JsonValue> {
+        self.ensure_enabled()?;
+        let challenge = random_challenge();
+
+        let mut redis_client = connection(client).await?;
+        let _: () = redis_client
+            .set_ex(
+                registration_key(&challenge),
+                user_id,
+                (CHALLENGE_MAX_AGE * 60) as u64,
+            )
+            .await?;
+
+        Ok(self.registration_options(&challenge, user_id, user_name, exclude_credentials))
+    }
+
+    /// Finishes a registration started by the same user, returning the verified credential.
+    pub async fn finish_registration(
+        &self,
+        client: &Client,
+        user_id: &str,
+        registration: &PasskeyRegistration,
+    ) -> Result<VerifiedCredential> {
+        self.ensure_enabled()?;
+        let challenge = client_data_challenge(&registration.client_data_json)?;
+
+        let mut redis_client = connection(client).await?;
+        let pending: Option<String> = redis_client.get_del(registration_key(&challenge)).await?;
+        if pending.as_deref() != Some(user_id) {
+            return Err(rejected("registration has expired, please start again"));
+        }
+
+        self.verify_registration(&challenge, registration)
+    }
+
+    /// Starts a passkey login, returning the request options.
+    pub async fn start_authentication(&self, client: &Client) -> Result<JsonValue> {
+        self.ensure_enabled()?;
+        let challenge = random_challenge();
+
+        let mut redis_client = connection(client).await?;
+        let _: () = redis_client
+            .set_ex(
+                authentication_key(&challenge),
+                1,
+                (CHALLENGE_MAX_AGE * 60) as u64,
+            )
+            .await?;
+
+        Ok(self.authentication_options(&challenge))
+    }
+
+    /// Finishes a passkey login, consuming its challenge and returning the new counter.
+    pub async fn finish_authentication(
+        &self,
+        client: &Client,
+        passkey: &Passkey,
+        login: &PasskeyLogin,
+    ) -> Result<u32> {
+        self.ensure_enabled()?;
+        let challenge = client_data_challenge(&login.client_data_json)?;
+
+        let mut redis_client = connection(client).await?;
+        let pending: Option<String> = redis_client.get_del(authentication_key(&challenge)).await?;
+        if pending.is_none() {
+            return Err(rejected("login has expired, please start again"));
+        }
+
+        self.verify_authentication(&challenge, passkey, login)
+    }
+}
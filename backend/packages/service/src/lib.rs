@@ -1,3 +1,8 @@
+pub mod api_key;
+pub mod audit;
 pub mod auth;
+pub mod mail;
+pub mod passkey;
+pub mod role;
 pub mod store;
 pub mod user;
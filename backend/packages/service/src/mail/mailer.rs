@@ -0,0 +1,97 @@
+use environment::Environment;
+use errors::{Error::SmtpProcessingError, Result};
+use lettre::{
+    address::Envelope,
+    message::{header::ContentType, Mailbox},
+    transport::{smtp::authentication::Credentials, stub::AsyncStubTransport},
+    AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+/* Transports an email can be delivered through */
+#[derive(Clone)]
+pub enum MailTransport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    /// Writes every email as an `.eml` file into a directory
+    File(AsyncFileTransport<Tokio1Executor>),
+    /// Keeps every email in memory, meant for tests and local development
+    Memory(AsyncStubTransport),
+}
+
+#[derive(Clone)]
+pub struct Mailer {
+    pub from: Mailbox,
+    pub transport: MailTransport,
+}
+
+impl Mailer {
+    /// Builds the mailer selected by the `MAIL_TRANSPORT` environment variable.
+    pub fn from_env(env: &Environment) -> Result<Self> {
+        let from = env
+            .mail_from
+            .parse::<Mailbox>()
+            .map_err(|e| SmtpProcessingError(format!("Invalid sender address: {}", e)))?;
+
+        let transport = match env.mail_transport.as_str() {
+            "smtp" => {
+                let port = env.smtp_port.parse().unwrap_or(587);
+                let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(&env.smtp_host)?
+                    .port(port)
+                    .credentials(Credentials::new(
+                        env.smtp_username.to_owned(),
+                        env.smtp_password.to_owned(),
+                    ))
+                    .build();
+                MailTransport::Smtp(transport)
+            }
+            "file" => MailTransport::File(AsyncFileTransport::new(&env.mail_file_path)),
+            "memory" => MailTransport::Memory(AsyncStubTransport::new_ok()),
+            other => {
+                return Err(SmtpProcessingError(format!(
+                    "Unsupported mail transport '{}'",
+                    other
+                )))
+            }
+        };
+
+        Ok(Self { from, transport })
+    }
+
+    /// Sends a plain text email.
+    #[tracing::instrument(err, skip(self, body))]
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> Result<()> {
+        let to = to
+            .parse::<Mailbox>()
+            .map_err(|e| SmtpProcessingError(format!("Invalid recipient address: {}", e)))?;
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| SmtpProcessingError(e.to_string()))?;
+
+        match &self.transport {
+            MailTransport::Smtp(transport) => transport.send(email).await.map(|_| ())?,
+            MailTransport::File(transport) => transport
+                .send(email)
+                .await
+                .map(|_| ())
+                .map_err(|e| SmtpProcessingError(e.to_string()))?,
+            MailTransport::Memory(transport) => transport
+                .send(email)
+                .await
+                .map_err(|e| SmtpProcessingError(e.to_string()))?,
+        }
+
+        Ok(())
+    }
+
+    /// Returns the raw emails kept by the in-memory transport.
+    pub async fn sent_messages(&self) -> Vec<(Envelope, String)> {
+        match &self.transport {
+            MailTransport::Memory(transport) => transport.messages().await,
+            _ => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod passkey_service;
+pub mod passkey_service_impl;
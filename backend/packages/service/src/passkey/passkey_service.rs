@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use errors::Result;
+use model::{
+    authorization::webauthn::VerifiedCredential, domain::passkey::Passkey as PasskeyData,
+    web::passkey::passkey_response::Passkey as PasskeyResponse,
+};
+use repository::passkey::passkey_repository::PasskeyRepository;
+
+#[derive(Clone, Debug)]
+pub struct PasskeyService {
+    pub passkey_repo: PasskeyRepository,
+}
+
+#[async_trait]
+pub trait PasskeyServiceTrait {
+    async fn add_passkey(
+        &self,
+        user_id: &str,
+        name: &str,
+        credential: VerifiedCredential,
+    ) -> Result<PasskeyResponse>;
+    async fn list_passkeys(&self, user_id: &str) -> Result<Vec<PasskeyResponse>>;
+    async fn credential_ids(&self, user_id: &str) -> Result<Vec<String>>;
+    async fn remove_passkey(&self, user_id: &str, id: &str) -> Result<bool>;
+    async fn get_by_credential_id(&self, credential_id: &str) -> Result<PasskeyData>;
+    async fn record_use(&self, passkey: &PasskeyData, sign_count: u32) -> Result<bool>;
+}
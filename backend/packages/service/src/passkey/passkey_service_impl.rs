@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+use super::passkey_service::{PasskeyService, PasskeyServiceTrait};
+use errors::{
+    Error::{DataExist, DataNotAvailable, UserUnauthorized},
+    Result,
+};
+use model::{
+    authorization::webauthn::VerifiedCredential, domain::passkey::Passkey as PasskeyData,
+    web::passkey::passkey_response::Passkey as PasskeyResponse,
+};
+use repository::passkey::passkey_repository::PasskeyRepositoryTrait as _;
+
+const PASSKEY_ID_PREFIX: &str = "pk_";
+
+impl PasskeyService {
+    /// Checks that a passkey id has the shape we generate before it is used in a query.
+    fn record_id(id: &str) -> Option<String> {
+        let hex = id.strip_prefix(PASSKEY_ID_PREFIX)?;
+        let is_valid = hex.len() == 32 && hex.chars().all(|c| c.is_ascii_hexdigit());
+        is_valid.then(|| format!("passkey:{}", id))
+    }
+
+    /// Credential ids are base64url, anything else cannot belong to a stored passkey.
+    fn is_credential_id(credential_id: &str) -> bool {
+        !credential_id.is_empty()
+            && credential_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    fn to_response(passkey: PasskeyData) -> PasskeyResponse {
+        PasskeyResponse {
+            id: passkey.id.trim_start_matches("passkey:").to_string(),
+            name: passkey.name,
+            created_at: passkey.created_at,
+            last_used_at: passkey.last_used_at,
+        }
+    }
+}
+
+#[async_trait]
+impl PasskeyServiceTrait for PasskeyService {
+    /// Stores a credential verified by a registration ceremony.
+    #[tracing::instrument(err, skip_all)]
+    async fn add_passkey(
+        &self,
+        user_id: &str,
+        name: &str,
+        credential: VerifiedCredential,
+    ) -> Result<PasskeyResponse> {
+        if self
+            .passkey_repo
+            .get_by_credential_id(&credential.credential_id)
+            .await?
+            .is_some()
+        {
+            return Err(DataExist("Passkey is already registered".to_string()));
+        }
+
+        let db_data = PasskeyData {
+            id: format!("{}{}", PASSKEY_ID_PREFIX, Uuid::new_v4().simple()),
+            user_id: user_id.to_string(),
+            name: name.to_string(),
+            credential_id: credential.credential_id,
+            public_key: credential.public_key,
+            sign_count: credential.sign_count,
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        self.passkey_repo.insert_data(db_data.clone()).await?;
+
+        Ok(Self::to_response(db_data))
+    }
+
+    /// Lists the passkeys of a user, newest first.
+    #[tracing::instrument(err, skip_all)]
+    async fn list_passkeys(&self, user_id: &str) -> Result<Vec<PasskeyResponse>> {
+        let passkeys = self.passkey_repo.get_by_user(user_id).await?;
+        Ok(passkeys.into_iter().map(Self::to_response).collect())
+    }
+
+    /// Returns the credential ids of a user, so authenticators do not register them twice.
+    #[tracing::instrument(err, skip_all)]
+    async fn credential_ids(&self, user_id: &str) -> Result<Vec<String>> {
+        let passkeys = self.passkey_repo.get_by_user(user_id).await?;
+        Ok(passkeys
+            .into_iter()
+            .map(|passkey| passkey.credential_id)
+            .collect())
+    }
+
+    /// Deletes one of the user's passkeys.
+    #[tracing::instrument(err, skip_all)]
+    async fn remove_passkey(&self, user_id: &str, id: &str) -> Result<bool> {
+        let not_found = || DataNotAvailable(format!("Passkey '{}' not found", id));
+
+        let record_id = Self::record_id(id).ok_or_else(not_found)?;
+        let passkey = self
+            .passkey_repo
+            .get_by_id(&record_id)
+            .await?
+            .filter(|passkey| passkey.user_id == user_id)
+            .ok_or_else(not_found)?;
+
+        self.passkey_repo.delete_data(&passkey.id).await
+    }
+
+    /// Finds the passkey an assertion was made with.
+    #[tracing::instrument(err, skip_all)]
+    async fn get_by_credential_id(&self, credential_id: &str) -> Result<PasskeyData> {
+        let unknown = || UserUnauthorized("Passkey rejected: unknown credential".to_string());
+
+        let credential_id = credential_id.trim_end_matches('=');
+        if !Self::is_credential_id(credential_id) {
+            return Err(unknown());
+        }
+
+        self.passkey_repo
+            .get_by_credential_id(credential_id)
+            .await?
+            .ok_or_else(unknown)
+    }
+
+    /// Stores the counter of a successful login.
+    #[tracing::instrument(err, skip_all)]
+    async fn record_use(&self, passkey: &PasskeyData, sign_count: u32) -> Result<bool> {
+        self.passkey_repo
+            .update_data(
+                &passkey.id,
+                json!({ "sign_count": sign_count, "last_used_at": Utc::now() }),
+            )
+            .await
+    }
+}
@@ -0,0 +1,2 @@
+pub mod role_service;
+pub mod role_service_impl;
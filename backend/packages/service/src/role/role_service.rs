@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use errors::Result;
+use model::{
+    authorization::permission::Permission,
+    web::role::{
+        role_request::{Role, RoleUpdate},
+        role_response::Role as RoleResponse,
+    },
+};
+use repository::role::role_repository::RoleRepository;
+
+#[derive(Clone, Debug)]
+pub struct RoleService {
+    pub role_repo: RoleRepository,
+}
+
+#[async_trait]
+pub trait RoleServiceTrait {
+    async fn create_role(&self, data: Role) -> Result<RoleResponse>;
+    async fn update_role(&self, name: &str, data: RoleUpdate) -> Result<bool>;
+    async fn delete_role(&self, name: &str) -> Result<bool>;
+    async fn list_roles(&self) -> Result<Vec<RoleResponse>>;
+    async fn role_exists(&self, name: &str) -> Result<bool>;
+    async fn get_permissions(&self, name: &str) -> Result<Vec<Permission>>;
+}
@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+
+use super::role_service::{RoleService, RoleServiceTrait};
+use errors::{
+    Error::{DataExist, DataNotAvailable, InvalidUserRole},
+    Result,
+};
+use model::{
+    authorization::{permission::Permission, role::UserRole},
+    domain::role::Role as RoleData,
+    web::role::{
+        role_request::{Role, RoleUpdate},
+        role_response::Role as RoleResponse,
+    },
+};
+use repository::role::role_repository::RoleRepositoryTrait as _;
+
+impl RoleService {
+    fn built_in_response(role: UserRole) -> RoleResponse {
+        RoleResponse {
+            name: role.as_str().to_string(),
+            description: format!("Built-in {} role", role),
+            permissions: role.permissions(),
+            built_in: true,
+        }
+    }
+
+    /// Built-in roles are defined in code and cannot be changed through the API.
+    fn ensure_not_built_in(name: &str) -> Result<()> {
+        if name.parse::<UserRole>().is_ok() {
+            return Err(InvalidUserRole(format!(
+                "Built-in role '{}' cannot be modified",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RoleServiceTrait for RoleService {
+    /// Defines a new role with a set of permissions.
+    #[tracing::instrument(err, skip_all)]
+    async fn create_role(&self, data: Role) -> Result<RoleResponse> {
+        Self::ensure_not_built_in(&data.name)?;
+
+        if self.role_repo.get_by_name(&data.name).await?.is_some() {
+            return Err(DataExist(format!("Role '{}' already exists", data.name)));
+        }
+
+        let now = Utc::now();
+        let db_data = RoleData {
+            id: data.name.clone(),
+            name: data.name,
+            description: data.description,
+            permissions: data.permissions,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.role_repo.insert_data(db_data.clone()).await?;
+
+        Ok(RoleResponse {
+            name: db_data.name,
+            description: db_data.description,
+            permissions: db_data.permissions,
+            built_in: false,
+        })
+    }
+
+    /// Updates the description and/or permissions of a custom role.
+    #[tracing::instrument(err, skip_all)]
+    async fn update_role(&self, name: &str, data: RoleUpdate) -> Result<bool> {
+        Self::ensure_not_built_in(name)?;
+
+        let role = self
+            .role_repo
+            .get_by_name(name)
+            .await?
+            .ok_or_else(|| DataNotAvailable(format!("Role '{}' not found", name)))?;
+
+        let mut changes = Map::new();
+        if let Some(description) = data.description {
+            changes.insert("description".to_string(), Value::from(description));
+        }
+        if let Some(permissions) = data.permissions {
+            changes.insert("permissions".to_string(), json!(permissions));
+        }
+        changes.insert("updated_at".to_string(), json!(Utc::now()));
+
+        self.role_repo
+            .update_data(&role.id, Value::Object(changes))
+            .await
+    }
+
+    /// Deletes a custom role.
+    #[tracing::instrument(err, skip_all)]
+    async fn delete_role(&self, name: &str) -> Result<bool> {
+        Self::ensure_not_built_in(name)?;
+
+        let role = self
+            .role_repo
+            .get_by_name(name)
+            .await?
+            .ok_or_else(|| DataNotAvailable(format!("Role '{}' not found", name)))?;
+
+        self.role_repo.delete_data(&role.id).await
+    }
+
+    /// Lists built-in roles followed by the custom ones.
+    #[tracing::instrument(err, skip_all)]
+    async fn list_roles(&self) -> Result<Vec<RoleResponse>> {
+        let custom_roles = self.role_repo.get_all().await?;
+
+        Ok(UserRole::ALL
+            .into_iter()
+            .map(Self::built_in_response)
+            .chain(custom_roles.into_iter().map(|role| RoleResponse {
+                name: role.name,
+                description: role.description,
+                permissions: role.permissions,
+                built_in: false,
+            }))
+            .collect())
+    }
+
+    /// Checks whether a role is built-in or has been defined by an admin.
+    #[tracing::instrument(err, skip_all)]
+    async fn role_exists(&self, name: &str) -> Result<bool> {
+        if name.parse::<UserRole>().is_ok() {
+            return Ok(true);
+        }
+        Ok(self.role_repo.get_by_name(name).await?.is_some())
+    }
+
+    /// Resolves the permissions granted to a role; unknown roles grant nothing.
+    #[tracing::instrument(err, skip_all)]
+    async fn get_permissions(&self, name: &str) -> Result<Vec<Permission>> {
+        if let Ok(role) = name.parse::<UserRole>() {
+            return Ok(role.permissions());
+        }
+
+        Ok(self
+            .role_repo
+            .get_by_name(name)
+            .await?
+            .map(|role| role.permissions)
+            .unwrap_or_default())
+    }
+}
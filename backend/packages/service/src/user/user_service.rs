@@ -1,19 +1,48 @@
 use async_trait::async_trait;
 use errors::Result;
 
-use model::web::user::{user_request::User, user_response::User as UserResponse};
+use model::{
+    authorization::oidc::OidcIdentity,
+    domain::user::User as UserData,
+    web::user::{
+        user_request::{User, UserUpdate},
+        user_response::User as UserResponse,
+    },
+};
 
 use repository::user::user_repository::UserRepository;
 
-use serde_json::Value;
+use crate::auth::password_policy::PasswordPolicy;
 
 #[derive(Clone, Debug)]
 pub struct UserService {
     pub user_repo: UserRepository,
+    pub password_policy: PasswordPolicy,
 }
 
 #[async_trait]
 pub trait UserServiceTrait {
     async fn register_profile(&self, data: User) -> Result<UserResponse>;
-    async fn update_profile(&self, id: &str, data: Value) -> Result<bool>;
+    async fn update_profile(&self, id: &str, data: UserUpdate) -> Result<bool>;
+    async fn verify_profile(&self, id: &str) -> Result<bool>;
+    async fn check_password(&self, id: &str, password: &str) -> Result<()>;
+    async fn update_password(&self, id: &str, password: &str) -> Result<bool>;
+    async fn change_password(
+        &self,
+        id: &str,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<bool>;
+    async fn enable_two_factor(
+        &self,
+        id: &str,
+        secret: &str,
+        recovery_codes: Vec<String>,
+    ) -> Result<bool>;
+    async fn disable_two_factor(&self, id: &str, password: &str) -> Result<bool>;
+    async fn set_two_factor_required(&self, id: &str, required: bool) -> Result<bool>;
+    async fn consume_recovery_code(&self, id: &str, code_hash: &str) -> Result<bool>;
+    async fn set_disabled(&self, id: &str, disabled: bool) -> Result<bool>;
+    async fn change_role(&self, id: &str, role: &str) -> Result<bool>;
+    async fn login_with_identity(&self, identity: &OidcIdentity) -> Result<UserData>;
 }
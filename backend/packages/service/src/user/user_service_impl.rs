@@ -1,22 +1,33 @@
 use super::user_service::{UserService, UserServiceTrait};
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
-    Argon2, PasswordHasher,
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
 };
 use async_trait::async_trait;
 use chrono::Utc;
 use errors::{
-    Error::{DataExist, DataNotAvailable, UserNotVerified},
+    Error::{
+        DataExist, DataNotAvailable, DataNotValidate, InvalidUserRole, StringError,
+        TransactionConflict, UserNotVerified, UserUnauthorized,
+    },
     Result,
 };
 use model::{
-    domain::user::User as UserData, web::user::user_request::User,
+    authorization::{oidc::OidcIdentity, role::UserRole},
+    domain::{
+        page::{Page, PageRequest},
+        user::{ExternalIdentity, User as UserData},
+    },
+    web::user::user_request::{User, UserUpdate},
     web::user::user_response::User as UserResponse,
 };
-use repository::user::user_repository::UserRepositoryTrait as _;
-use serde_json::Value;
+use repository::{user::user_repository::UserRepositoryTrait as _, Transaction};
+use serde_json::{json, Value};
 use uuid::Uuid;
 
+/* Times a recovery code is retried when other codes are used concurrently */
+const RECOVERY_CODE_ATTEMPTS: usize = 3;
+
 impl UserService {
     /// Hashes a password using Argon2.
     #[tracing::instrument(err, skip_all)]
@@ -28,6 +39,64 @@ impl UserService {
         Ok(hashed_password)
     }
 
+    fn password_matches(password_hash: &str, password: &str) -> bool {
+        PasswordHash::new(password_hash)
+            .ok()
+            .and_then(|parsed_hash| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed_hash)
+                    .ok()
+            })
+            .is_some()
+    }
+
+    /// Stores a new password of a user once it satisfies the password policy.
+    async fn set_password(
+        &self,
+        id: &str,
+        user: &UserData,
+        field: &'static str,
+        password: &str,
+    ) -> Result<bool> {
+        self.password_policy
+            .check(field, password, &user.username, &user.email)
+            .await?;
+        let hashed_password = Self::password_hasher(password)?;
+
+        self.user_repo
+            .update_data(
+                id,
+                json!({ "password": hashed_password, "updated_at": Utc::now() }),
+            )
+            .await
+    }
+
+    /// Refuses a change that leaves no enabled admin account to manage the others.
+    async fn ensure_admin_remains(&self, user: &UserData) -> Result<()> {
+        if user.role != UserRole::Admin.as_str() || user.disabled {
+            return Ok(());
+        }
+
+        if self
+            .user_repo
+            .count_enabled_by_role(UserRole::Admin.as_str())
+            .await?
+            <= 1
+        {
+            return Err(InvalidUserRole(
+                "The last enabled admin cannot be disabled or given another role".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn ensure_enabled(user: UserData) -> Result<UserData> {
+        if user.disabled {
+            return Err(UserUnauthorized("User account is disabled".to_string()));
+        }
+        Ok(user)
+    }
+
     /// Authenticates a user by email.
     #[tracing::instrument(err, skip_all)]
     pub async fn login(&self, email: String) -> Result<UserData> {
@@ -43,7 +112,35 @@ impl UserService {
             )));
         }
 
-        Ok(user_response)
+        Self::ensure_enabled(user_response)
+    }
+
+    /// Fetches a user by email regardless of its verification state.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn get_user_by_email(&self, email: &str) -> Result<UserData> {
+        self.user_repo.get_data_by_email(email).await
+    }
+
+    /// Fetches a user by id regardless of its verification state.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn get_user_by_id(&self, id: &str) -> Result<UserData> {
+        self.user_repo.get_data_by_id(id).await
+    }
+
+    /// Fetches a user by id, rejecting disabled accounts.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn get_enabled_user_by_id(&self, id: &str) -> Result<UserData> {
+        Self::ensure_enabled(self.user_repo.get_data_by_id(id).await?)
+    }
+
+    /// Lists a page of users matching a search, together with the number of matches.
+    #[tracing::instrument(err, skip_all)]
+    pub async fn list_users(
+        &self,
+        search: Option<&str>,
+        page: &PageRequest,
+    ) -> Result<Page<UserData>> {
+        self.user_repo.list_page(search, page).await
     }
 }
 
@@ -52,22 +149,9 @@ impl UserServiceTrait for UserService {
     /// Registers a new user profile.
     #[tracing::instrument(err, skip_all)]
     async fn register_profile(&self, data: User) -> Result<UserResponse> {
-        let is_username_taken = !self
-            .user_repo
-            .is_data_empty_by_username(&data.username)
+        self.password_policy
+            .check("password", &data.password, &data.username, &data.email)
             .await?;
-        let is_email_taken = !self.user_repo.is_data_empty_by_email(&data.email).await?;
-
-        if is_username_taken {
-            return Err(DataExist(format!(
-                "Username '{}' already exists",
-                data.username
-            )));
-        }
-        if is_email_taken {
-            return Err(DataExist(format!("Email '{}' already exists", data.email)));
-        }
-
         let hashed_password = Self::password_hasher(&data.password)?;
         let now = Utc::now();
         let user_id = format!("user_{}", Uuid::new_v4().to_string().replace("-", "_"));
@@ -79,17 +163,31 @@ impl UserServiceTrait for UserService {
             role: data.role,
             password: hashed_password,
             verified: false,
+            disabled: false,
+            full_name: None,
+            phone_number: None,
+            totp_secret: None,
+            recovery_codes: Vec::new(),
+            two_factor_required: false,
+            identities: Vec::new(),
             created_at: now,
             updated_at: now,
         };
 
-        self.user_repo.insert_data(db_data.clone()).await?;
+        // The checks in the transaction name the field that is taken, registrations racing past
+        // them are stopped by the unique indexes on the username and email
+        let mut transaction = Transaction::begin();
+        self.user_repo
+            .insert_data_in(&mut transaction, db_data.clone())?;
+        self.user_repo.commit(transaction).await?;
 
         Ok(UserResponse {
             id: db_data.id,
             username: db_data.username,
             email: db_data.email,
             role: db_data.role,
+            full_name: None,
+            phone_number: None,
             created_at: now,
             updated_at: now,
         })
@@ -97,14 +195,329 @@ impl UserServiceTrait for UserService {
 
     /// Updates user profile if the user is verified.
     #[tracing::instrument(err, skip_all)]
-    async fn update_profile(&self, id: &str, data: Value) -> Result<bool> {
+    async fn update_profile(&self, id: &str, data: UserUpdate) -> Result<bool> {
+        let user = self.user_repo.get_data_by_id(id).await?;
+        if !user.verified {
+            return Err(UserNotVerified("User is not verified".to_string()));
+        }
+
+        if let Some(username) = data
+            .username
+            .as_ref()
+            .filter(|&name| *name != user.username)
+        {
+            if !self.user_repo.is_data_empty_by_username(username).await? {
+                return Err(DataExist(format!("Username '{}' already exists", username)));
+            }
+        }
+        let email_changed = data.email.as_ref().filter(|&email| *email != user.email);
+        if let Some(email) = email_changed {
+            if !self.user_repo.is_data_empty_by_email(email).await? {
+                return Err(DataExist(format!("Email '{}' already exists", email)));
+            }
+        }
+
+        let mut patch = serde_json::to_value(&data).map_err(|e| StringError(e.to_string()))?;
+        if patch.as_object().is_none_or(|fields| fields.is_empty()) {
+            return Err(DataNotValidate("No profile fields to update".to_string()));
+        }
+        // A new address has to be verified again before it can be trusted
+        if email_changed.is_some() {
+            patch["verified"] = json!(false);
+        }
+        patch["updated_at"] = json!(Utc::now());
+
+        self.user_repo.update_data(id, patch).await
+    }
+
+    /// Marks a user's email address as verified.
+    #[tracing::instrument(err, skip_all)]
+    async fn verify_profile(&self, id: &str) -> Result<bool> {
         if self.user_repo.is_data_empty_by_id(id).await? {
             return Err(DataNotAvailable(format!("User ID '{}' not found", id)));
         }
-        if !self.user_repo.is_verified(id).await? {
-            return Err(UserNotVerified("User is not verified".to_string()));
+
+        self.user_repo
+            .update_data(id, json!({ "verified": true, "updated_at": Utc::now() }))
+            .await
+    }
+
+    /// Checks a new password for a user against the password policy without storing it.
+    #[tracing::instrument(err, skip_all)]
+    async fn check_password(&self, id: &str, password: &str) -> Result<()> {
+        let user = self.user_repo.get_data_by_id(id).await?;
+
+        self.password_policy
+            .check("password", password, &user.username, &user.email)
+            .await
+    }
+
+    /// Replaces a user's password with a freshly hashed one.
+    #[tracing::instrument(err, skip_all)]
+    async fn update_password(&self, id: &str, password: &str) -> Result<bool> {
+        if self.user_repo.is_data_empty_by_id(id).await? {
+            return Err(DataNotAvailable(format!("User ID '{}' not found", id)));
+        }
+
+        let user = self.user_repo.get_data_by_id(id).await?;
+
+        self.set_password(id, &user, "password", password).await
+    }
+
+    /// Replaces a user's password after checking the current one.
+    #[tracing::instrument(err, skip_all)]
+    async fn change_password(
+        &self,
+        id: &str,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<bool> {
+        let user = self.user_repo.get_data_by_id(id).await?;
+
+        if !Self::password_matches(&user.password, current_password) {
+            return Err(UserUnauthorized(
+                "Current password is incorrect".to_string(),
+            ));
         }
 
-        self.user_repo.update_data(id, data).await
+        self.set_password(id, &user, "new_password", new_password)
+            .await
+    }
+
+    /// Turns on TOTP for a user with a confirmed secret and hashed recovery codes.
+    #[tracing::instrument(err, skip_all)]
+    async fn enable_two_factor(
+        &self,
+        id: &str,
+        secret: &str,
+        recovery_codes: Vec<String>,
+    ) -> Result<bool> {
+        self.user_repo
+            .update_data(
+                id,
+                json!({
+                    "totp_secret": secret,
+                    "recovery_codes": recovery_codes,
+                    "updated_at": Utc::now()
+                }),
+            )
+            .await
+    }
+
+    /// Turns off TOTP after checking the password, unless an admin requires it.
+    #[tracing::instrument(err, skip_all)]
+    async fn disable_two_factor(&self, id: &str, password: &str) -> Result<bool> {
+        let user = self.user_repo.get_data_by_id(id).await?;
+
+        if !Self::password_matches(&user.password, password) {
+            return Err(UserUnauthorized("Password is incorrect".to_string()));
+        }
+        if user.two_factor_required {
+            return Err(InvalidUserRole(
+                "Two-factor authentication is required for this account".to_string(),
+            ));
+        }
+
+        self.user_repo
+            .update_data(
+                id,
+                json!({
+                    "totp_secret": Value::Null,
+                    "recovery_codes": [],
+                    "updated_at": Utc::now()
+                }),
+            )
+            .await
+    }
+
+    /// Sets whether a user must use two-factor authentication to log in.
+    #[tracing::instrument(err, skip_all)]
+    async fn set_two_factor_required(&self, id: &str, required: bool) -> Result<bool> {
+        if self.user_repo.is_data_empty_by_id(id).await? {
+            return Err(DataNotAvailable(format!("User ID '{}' not found", id)));
+        }
+
+        self.user_repo
+            .update_data(
+                id,
+                json!({ "two_factor_required": required, "updated_at": Utc::now() }),
+            )
+            .await
+    }
+
+    /// Uses up a recovery code, returning whether it was valid.
+    ///
+    /// The remaining codes are only written if nobody changed the codes since they were read, so
+    /// concurrent requests can neither both use a code nor bring back one another used.
+    #[tracing::instrument(err, skip_all)]
+    async fn consume_recovery_code(&self, id: &str, code_hash: &str) -> Result<bool> {
+        for _ in 0..RECOVERY_CODE_ATTEMPTS {
+            let user = self.user_repo.get_data_by_id(id).await?;
+
+            if !user.recovery_codes.iter().any(|hash| hash == code_hash) {
+                return Ok(false);
+            }
+
+            let remaining: Vec<&String> = user
+                .recovery_codes
+                .iter()
+                .filter(|hash| *hash != code_hash)
+                .collect();
+
+            let mut transaction = Transaction::begin();
+            self.user_repo
+                .ensure_recovery_codes_in(&mut transaction, id, &user.recovery_codes);
+            self.user_repo.update_data_in(
+                &mut transaction,
+                id,
+                json!({ "recovery_codes": remaining, "updated_at": Utc::now() }),
+            )?;
+            match self.user_repo.commit(transaction).await {
+                Ok(()) => return Ok(true),
+                // Another code was used in the meantime, try again on the fresh codes
+                Err(DataNotValidate(_) | TransactionConflict(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Enables or disables a user account, refusing to disable the last enabled admin.
+    #[tracing::instrument(err, skip_all)]
+    async fn set_disabled(&self, id: &str, disabled: bool) -> Result<bool> {
+        if self.user_repo.is_data_empty_by_id(id).await? {
+            return Err(DataNotAvailable(format!("User ID '{}' not found", id)));
+        }
+
+        if disabled {
+            let user = self.user_repo.get_data_by_id(id).await?;
+            self.ensure_admin_remains(&user).await?;
+        }
+
+        self.user_repo
+            .update_data(
+                id,
+                json!({ "disabled": disabled, "updated_at": Utc::now() }),
+            )
+            .await
+    }
+
+    /// Assigns another role to a user, refusing to demote the last enabled admin.
+    #[tracing::instrument(err, skip_all)]
+    async fn change_role(&self, id: &str, role: &str) -> Result<bool> {
+        if self.user_repo.is_data_empty_by_id(id).await? {
+            return Err(DataNotAvailable(format!("User ID '{}' not found", id)));
+        }
+
+        let user = self.user_repo.get_data_by_id(id).await?;
+        if role != user.role {
+            self.ensure_admin_remains(&user).await?;
+        }
+
+        self.user_repo
+            .update_data(id, json!({ "role": role, "updated_at": Utc::now() }))
+            .await
+    }
+
+    /// Returns the user an OIDC identity belongs to, linking or creating one on first login.
+    ///
+    /// Identities are only linked to an existing account, or used to create one, when the
+    /// provider vouches for the email address. An existing account must have verified the email
+    /// itself, otherwise whoever registered it with someone else's address, and still knows its
+    /// password, would share the account with the identity.
+    #[tracing::instrument(err, skip_all)]
+    async fn login_with_identity(&self, identity: &OidcIdentity) -> Result<UserData> {
+        let repo = &self.user_repo;
+
+        if let Some(user) = repo
+            .get_data_by_identity(&identity.issuer, &identity.subject)
+            .await?
+        {
+            return Self::ensure_enabled(user);
+        }
+
+        let email = identity
+            .email
+            .as_ref()
+            .filter(|_| identity.email_verified)
+            .ok_or_else(|| {
+                UserUnauthorized("The identity provider did not verify an email".to_string())
+            })?;
+        let external_identity = ExternalIdentity {
+            issuer: identity.issuer.clone(),
+            subject: identity.subject.clone(),
+        };
+        let now = Utc::now();
+
+        if !repo.is_data_empty_by_email(email).await? {
+            let mut user = Self::ensure_enabled(repo.get_data_by_email(email).await?)?;
+            if !user.verified {
+                return Err(UserNotVerified(
+                    "An account with this email exists but has not verified it, verify the email \
+                     before signing in with this provider"
+                        .to_string(),
+                ));
+            }
+            user.identities.push(external_identity);
+            user.updated_at = now;
+
+            repo.update_data(
+                &user.id,
+                json!({
+                    "identities": user.identities,
+                    "updated_at": now
+                }),
+            )
+            .await?;
+            return Ok(user);
+        }
+
+        let local_part: String = email
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.')
+            .collect();
+        let mut username = local_part.clone();
+        while username.is_empty() || !repo.is_data_empty_by_username(&username).await? {
+            username = format!(
+                "{}_{}",
+                local_part,
+                &Uuid::new_v4().simple().to_string()[..6]
+            );
+        }
+
+        // Nobody knows this password; the user can set one through a password reset
+        let password = Self::password_hasher(&Uuid::new_v4().to_string())?;
+        let user_id = format!("user_{}", Uuid::new_v4().to_string().replace("-", "_"));
+
+        let db_data = UserData {
+            id: user_id.clone(),
+            username,
+            email: email.clone(),
+            role: UserRole::Customer.as_str().to_string(),
+            password,
+            verified: true,
+            disabled: false,
+            full_name: identity.name.clone(),
+            phone_number: None,
+            totp_secret: None,
+            recovery_codes: Vec::new(),
+            two_factor_required: false,
+            identities: vec![external_identity],
+            created_at: now,
+            updated_at: now,
+        };
+
+        let mut transaction = Transaction::begin();
+        repo.insert_data_in(&mut transaction, db_data.clone())?;
+        repo.commit(transaction).await?;
+
+        Ok(UserData {
+            id: format!("user:{}", user_id),
+            ..db_data
+        })
     }
 }
@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose, Engine};
+    use chrono::{Duration, Utc};
+    use errors::Result;
+    use jsonwebtoken::{decode_header, encode, Algorithm, EncodingKey, Header};
+    use model::authorization::{
+        jwk::{KeyUse, KeyringConfig, SigningKeyConfig},
+        token::TokenClaims,
+    };
+    use service::auth::{
+        jwt::{generate_impersonation_token, generate_jwt_token, verify_jwt_token},
+        keyring::Keyring,
+    };
+
+    use tokio::test;
+    use uuid::Uuid;
+
+    const CURRENT_PRIVATE_KEY: &str = include_str!("keys/current.pem");
+    const CURRENT_PUBLIC_KEY: &str = include_str!("keys/current.pub.pem");
+    const RETIRED_PRIVATE_KEY: &str = include_str!("keys/retired.pem");
+    const RETIRED_PUBLIC_KEY: &str = include_str!("keys/retired.pub.pem");
+
+    fn key(
+        kid: &str,
+        key_use: KeyUse,
+        private_key: &str,
+        public_key: &str,
+        retired_minutes_ago: Option<i64>,
+    ) -> SigningKeyConfig {
+        SigningKeyConfig {
+            kid: kid.to_string(),
+            key_use,
+            private_key: Some(general_purpose::STANDARD.encode(private_key)),
+            public_key: general_purpose::STANDARD.encode(public_key),
+            retired_at: retired_minutes_ago.map(|minutes| Utc::now() - Duration::minutes(minutes)),
+        }
+    }
+
+    fn keyring(retired_minutes_ago: i64, grace_period: i64) -> Result<Keyring> {
+        Keyring::new(
+            KeyringConfig {
+                keys: vec![
+                    key(
+                        "access-2",
+                        KeyUse::Access,
+                        CURRENT_PRIVATE_KEY,
+                        CURRENT_PUBLIC_KEY,
+                        None,
+                    ),
+                    key(
+                        "access-1",
+                        KeyUse::Access,
+                        RETIRED_PRIVATE_KEY,
+                        RETIRED_PUBLIC_KEY,
+                        Some(retired_minutes_ago),
+                    ),
+                    key(
+                        "refresh-1",
+                        KeyUse::Refresh,
+                        RETIRED_PRIVATE_KEY,
+                        RETIRED_PUBLIC_KEY,
+                        None,
+                    ),
+                ],
+            },
+            grace_period,
+        )
+    }
+
+    #[test]
+    async fn test_signs_with_active_key_and_kid() -> Result<()> {
+        let keyring = keyring(10, 60)?;
+        let signing_key = keyring.signing_key(KeyUse::Access)?;
+        assert_eq!(signing_key.kid, "access-2");
+
+        let token = generate_jwt_token("user:user_1".to_string(), 5, signing_key, "customer")
+            .await?
+            .token
+            .unwrap();
+        assert_eq!(decode_header(&token)?.kid.as_deref(), Some("access-2"));
+
+        let details = verify_jwt_token(&keyring, KeyUse::Access, &token).await?;
+        assert_eq!(details.user_id, "user:user_1");
+        assert_eq!(details.user_role, "customer");
+        Ok(())
+    }
+
+    #[test]
+    async fn test_impersonation_token_carries_actor() -> Result<()> {
+        let keyring = keyring(10, 60)?;
+        let signing_key = keyring.signing_key(KeyUse::Access)?;
+
+        let token = generate_impersonation_token(
+            "user:user_1".to_string(),
+            "user:support_1",
+            5,
+            signing_key,
+            "customer",
+        )
+        .await?
+        .token
+        .unwrap();
+        let details = verify_jwt_token(&keyring, KeyUse::Access, &token).await?;
+        assert_eq!(details.user_id, "user:user_1");
+        assert_eq!(details.impersonator.as_deref(), Some("user:support_1"));
+
+        let token = generate_jwt_token("user:user_1".to_string(), 5, signing_key, "customer")
+            .await?
+            .token
+            .unwrap();
+        let details = verify_jwt_token(&keyring, KeyUse::Access, &token).await?;
+        assert!(details.impersonator.is_none());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_retired_key_verifies_during_grace_period() -> Result<()> {
+        // Issued back when access-1 was the active key
+        let previous = Keyring::new(
+            KeyringConfig {
+                keys: vec![key(
+                    "access-1",
+                    KeyUse::Access,
+                    RETIRED_PRIVATE_KEY,
+                    RETIRED_PUBLIC_KEY,
+                    None,
+                )],
+            },
+            60,
+        )?;
+        let token = generate_jwt_token(
+            "user:user_1".to_string(),
+            5,
+            previous.signing_key(KeyUse::Access)?,
+            "customer",
+        )
+        .await?
+        .token
+        .unwrap();
+
+        assert!(verify_jwt_token(&keyring(10, 60)?, KeyUse::Access, &token)
+            .await
+            .is_ok());
+        assert!(verify_jwt_token(&keyring(90, 60)?, KeyUse::Access, &token)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_token_without_kid_verifies() -> Result<()> {
+        let now = Utc::now().timestamp();
+        let claims = TokenClaims {
+            sub: "user:user_1".to_string(),
+            token_uuid: Uuid::new_v4().to_string(),
+            exp: now + 300,
+            iat: now,
+            nbf: now,
+            user_role: "customer".to_string(),
+            act: None,
+        };
+        let token = encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &EncodingKey::from_rsa_pem(CURRENT_PRIVATE_KEY.as_bytes())?,
+        )?;
+
+        let details = verify_jwt_token(&keyring(10, 60)?, KeyUse::Access, &token).await?;
+        assert_eq!(details.user_id, "user:user_1");
+        Ok(())
+    }
+
+    #[test]
+    async fn test_rejects_token_of_other_use() -> Result<()> {
+        let keyring = keyring(10, 60)?;
+        let token = generate_jwt_token(
+            "user:user_1".to_string(),
+            5,
+            keyring.signing_key(KeyUse::Refresh)?,
+            "customer",
+        )
+        .await?
+        .token
+        .unwrap();
+
+        assert!(verify_jwt_token(&keyring, KeyUse::Access, &token)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_jwks_lists_trusted_access_keys() -> Result<()> {
+        let kids = |keyring: Keyring| -> Vec<String> {
+            keyring.jwks().keys.into_iter().map(|jwk| jwk.kid).collect()
+        };
+
+        assert_eq!(kids(keyring(10, 60)?), vec!["access-2", "access-1"]);
+        assert_eq!(kids(keyring(90, 60)?), vec!["access-2"]);
+
+        let jwk = &keyring(10, 60)?.jwks().keys[0];
+        assert_eq!(jwk.kty, "RSA");
+        assert_eq!(jwk.alg, "RS256");
+        assert_eq!(jwk.e, "AQAB");
+        Ok(())
+    }
+}
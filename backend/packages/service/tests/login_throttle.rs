@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+    use service::auth::login_throttle::LoginThrottle;
+
+    fn throttle() -> LoginThrottle {
+        LoginThrottle {
+            max_attempts: 5,
+            ip_max_attempts: 20,
+            lockout_max_age: 15,
+        }
+    }
+
+    #[test]
+    fn test_first_failure_is_free() {
+        assert_eq!(throttle().backoff_seconds(1, 5), 0);
+    }
+
+    #[test]
+    fn test_backoff_doubles_until_lockout() {
+        let throttle = throttle();
+        assert_eq!(throttle.backoff_seconds(2, 5), 2);
+        assert_eq!(throttle.backoff_seconds(3, 5), 4);
+        assert_eq!(throttle.backoff_seconds(4, 5), 8);
+        assert_eq!(throttle.backoff_seconds(5, 5), 15 * 60);
+        assert_eq!(throttle.backoff_seconds(9, 5), 15 * 60);
+    }
+
+    #[test]
+    fn test_backoff_never_exceeds_lockout() {
+        let throttle = throttle();
+        assert_eq!(throttle.backoff_seconds(19, 20), 15 * 60);
+        assert_eq!(throttle.backoff_seconds(70, 100), 15 * 60);
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use environment::Environment;
+    use errors::Result;
+    use service::mail::mailer::{MailTransport, Mailer};
+
+    use tokio::test;
+
+    fn memory_mailer() -> Result<Mailer> {
+        let env = Environment {
+            mail_transport: "memory".to_string(),
+            mail_from: "VirtuMart <no-reply@virtumart.local>".to_string(),
+            ..Environment::new()
+        };
+        Mailer::from_env(&env)
+    }
+
+    #[test]
+    async fn test_memory_transport_keeps_messages() -> Result<()> {
+        let mailer = memory_mailer()?;
+        assert!(matches!(mailer.transport, MailTransport::Memory(_)));
+
+        mailer
+            .send("buyer@example.com", "Hello", "Welcome!".to_string())
+            .await?;
+
+        let messages = mailer.sent_messages().await;
+        assert_eq!(messages.len(), 1);
+
+        let (envelope, raw) = &messages[0];
+        assert_eq!(envelope.to()[0].to_string(), "buyer@example.com");
+        assert!(raw.contains("Subject: Hello"));
+        assert!(raw.contains("Welcome!"));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_invalid_recipient_is_rejected() -> Result<()> {
+        let mailer = memory_mailer()?;
+        assert!(mailer
+            .send("not-an-email", "Hello", "Welcome!".to_string())
+            .await
+            .is_err());
+        assert!(mailer.sent_messages().await.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unsupported_transport() {
+        let env = Environment {
+            mail_transport: "carrier-pigeon".to_string(),
+            mail_from: "no-reply@virtumart.local".to_string(),
+            ..Environment::new()
+        };
+        assert!(Mailer::from_env(&env).is_err());
+    }
+}
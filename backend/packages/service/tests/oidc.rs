@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose, Engine};
+    use chrono::Utc;
+    use errors::Result;
+    use jsonwebtoken::{encode, jwk::JwkSet, Algorithm, EncodingKey, Header};
+    use model::authorization::oidc::OidcProviderConfig;
+    use rsa::{pkcs8::DecodePublicKey, traits::PublicKeyParts, RsaPublicKey};
+    use serde_json::{json, Value};
+    use service::auth::oidc::{code_challenge, state_binding, state_matches, validate_id_token};
+
+    const PRIVATE_KEY: &str = include_str!("keys/current.pem");
+    const PUBLIC_KEY: &str = include_str!("keys/current.pub.pem");
+
+    fn provider() -> OidcProviderConfig {
+        OidcProviderConfig {
+            name: "mock".to_string(),
+            issuer: "http://localhost:9000".to_string(),
+            client_id: "virtumart".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "http://localhost:3000/api/v1/oidc/mock/callback".to_string(),
+            scopes: vec!["openid".to_string(), "email".to_string()],
+        }
+    }
+
+    fn jwks() -> JwkSet {
+        let key = RsaPublicKey::from_public_key_pem(PUBLIC_KEY).unwrap();
+        serde_json::from_value(json!({
+            "keys": [{
+                "kty": "RSA",
+                "use": "sig",
+                "alg": "RS256",
+                "kid": "mock-1",
+                "n": general_purpose::URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                "e": general_purpose::URL_SAFE_NO_PAD.encode(key.e().to_bytes_be())
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn id_token(kid: &str, claims: Value) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(kid.to_string());
+        encode(
+            &header,
+            &claims,
+            &EncodingKey::from_rsa_pem(PRIVATE_KEY.as_bytes()).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn claims(audience: &str, nonce: &str) -> Value {
+        json!({
+            "iss": "http://localhost:9000",
+            "aud": audience,
+            "sub": "mock-user-1",
+            "exp": Utc::now().timestamp() + 300,
+            "nonce": nonce,
+            "email": "buyer@email.test",
+            "email_verified": true,
+            "name": "Buyer"
+        })
+    }
+
+    #[test]
+    fn test_code_challenge() {
+        // RFC 7636 appendix B
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_validates_id_token() -> Result<()> {
+        let token = id_token("mock-1", claims("virtumart", "nonce-1"));
+        let identity = validate_id_token(&provider(), &jwks(), &token, "nonce-1")?;
+
+        assert_eq!(identity.issuer, "http://localhost:9000");
+        assert_eq!(identity.subject, "mock-user-1");
+        assert_eq!(identity.email.as_deref(), Some("buyer@email.test"));
+        assert!(identity.email_verified);
+        assert_eq!(identity.name.as_deref(), Some("Buyer"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_invalid_id_tokens() {
+        let wrong_nonce = id_token("mock-1", claims("virtumart", "nonce-2"));
+        let wrong_audience = id_token("mock-1", claims("other-client", "nonce-1"));
+        let unknown_key = id_token("mock-2", claims("virtumart", "nonce-1"));
+
+        for token in [wrong_nonce, wrong_audience, unknown_key] {
+            assert!(validate_id_token(&provider(), &jwks(), &token, "nonce-1").is_err());
+        }
+    }
+
+    #[test]
+    fn test_state_binding() {
+        let binding = state_binding("state-1");
+
+        assert_ne!(binding, "state-1");
+        assert!(state_matches("state-1", &binding));
+        assert!(!state_matches("state-2", &binding));
+        assert!(!state_matches("state-1", ""));
+    }
+}
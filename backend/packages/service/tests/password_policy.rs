@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use errors::Error;
+    use service::auth::password_policy::PasswordPolicy;
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    const USERNAME: &str = "janedoe";
+    const EMAIL: &str = "jane.smith@example.com";
+
+    /* SHA-1 of "P@ssw0rd", split into its range prefix and suffix */
+    const BREACHED_PREFIX: &str = "21BD1";
+    const BREACHED_SUFFIX: &str = "2DC183F740EE76F27B78EB39C8AD972A757";
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 10,
+            min_character_classes: 3,
+            breached_passwords_path: None,
+        }
+    }
+
+    /// Writes a range directory holding a single breached password.
+    fn breached_ranges() -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("breached-{}", Uuid::new_v4()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join(format!("{}.txt", BREACHED_PREFIX)),
+            format!(
+                "0018A45C4D1DEF81644B54AB7F969B88D65:0\r\n{}:52579\r\n",
+                BREACHED_SUFFIX
+            ),
+        )
+        .unwrap();
+        directory
+    }
+
+    /// Codes of the rules a password breaks, in the order they are checked.
+    async fn violations(policy: &PasswordPolicy, password: &str) -> Vec<String> {
+        match policy.check("password", password, USERNAME, EMAIL).await {
+            Ok(()) => Vec::new(),
+            Err(Error::PasswordRejected(errors)) => errors
+                .field_errors()
+                .get("password")
+                .map(|errors| errors.iter().map(|error| error.code.to_string()).collect())
+                .unwrap_or_default(),
+            Err(e) => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    async fn test_strong_password_is_accepted() {
+        assert!(violations(&policy(), "Correct-horse-42").await.is_empty());
+    }
+
+    #[test]
+    async fn test_every_broken_rule_is_reported() {
+        assert_eq!(
+            violations(&policy(), "short").await,
+            vec!["too_short", "too_few_character_classes"]
+        );
+    }
+
+    #[test]
+    async fn test_character_classes_are_counted() {
+        assert_eq!(
+            violations(&policy(), "lowercaseonly").await,
+            vec!["too_few_character_classes"]
+        );
+        assert!(violations(&policy(), "lowercase and 42").await.is_empty());
+        assert!(violations(&policy(), "ÄÖÜ-äöü-straße").await.is_empty());
+    }
+
+    #[test]
+    async fn test_username_and_email_are_rejected() {
+        assert_eq!(
+            violations(&policy(), "My-JaneDoe-2024").await,
+            vec!["contains_username"]
+        );
+        assert_eq!(
+            violations(&policy(), "Jane.Smith-2024").await,
+            vec!["contains_email"]
+        );
+    }
+
+    #[test]
+    async fn test_short_identifiers_are_ignored() {
+        let result = policy()
+            .check("password", "Bobs-Garden-42", "bob", "bob@example.com")
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    async fn test_breached_password_is_rejected() {
+        let policy = PasswordPolicy {
+            min_length: 8,
+            min_character_classes: 1,
+            breached_passwords_path: Some(breached_ranges()),
+        };
+
+        assert_eq!(policy.breach_count("P@ssw0rd").await, Some(52579));
+        assert_eq!(violations(&policy, "P@ssw0rd").await, vec!["breached"]);
+        // Range files cover the whole hash space, other prefixes are missing from this one
+        assert_eq!(policy.breach_count("Correct-horse-42").await, None);
+    }
+
+    #[test]
+    async fn test_breach_check_is_optional() {
+        assert_eq!(
+            PasswordPolicy::default().breach_count("P@ssw0rd").await,
+            None
+        );
+        assert!(violations(&PasswordPolicy::default(), "P@ssw0rd")
+            .await
+            .is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use errors::Result;
+    use model::authorization::two_factor::LoginChallenge;
+    use redis::AsyncCommands;
+    use service::auth::two_factor::{
+        accept_code_step, create_login_challenge, generate_recovery_codes, generate_secret,
+        get_login_challenge, hash_recovery_code, otpauth_uri, record_challenge_failure,
+        verify_code, verify_code_once,
+    };
+    use totp_rs::{Algorithm, Secret, TOTP};
+    use uuid::Uuid;
+
+    use tokio::test;
+
+    fn current_code(secret: &str) -> String {
+        TOTP::new(
+            Algorithm::SHA1,
+            6,
+            1,
+            30,
+            Secret::Encoded(secret.to_string()).to_bytes().unwrap(),
+            None,
+            String::new(),
+        )
+        .unwrap()
+        .generate_current()
+        .unwrap()
+    }
+
+    fn current_step() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            / 30
+    }
+
+    fn redis_client() -> redis::Client {
+        redis::Client::open("redis://127.0.0.1:6379").unwrap()
+    }
+
+    #[test]
+    async fn test_verify_code() -> Result<()> {
+        let secret = generate_secret();
+        let code = current_code(&secret);
+
+        // The step may tick over between generating and checking the code
+        let step = verify_code(&secret, &code)?.unwrap();
+        assert!(step == current_step() || step + 1 == current_step());
+        assert!(verify_code(&secret, &format!(" {} ", code))?.is_some());
+        assert_eq!(verify_code(&secret, "abcdef")?, None);
+        assert_eq!(verify_code(&generate_secret(), &code)?, None);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_code_is_accepted_once() -> Result<()> {
+        let client = redis_client();
+        let user_id = format!("user:{}", Uuid::new_v4().simple());
+        let secret = generate_secret();
+        let code = current_code(&secret);
+
+        assert!(verify_code_once(&client, &user_id, &secret, &code).await?);
+        assert!(!verify_code_once(&client, &user_id, &secret, &code).await?);
+
+        // Once a step is used, earlier steps still inside the skew window are refused too
+        let other_user_id = format!("user:{}", Uuid::new_v4().simple());
+        assert!(accept_code_step(&client, &other_user_id, current_step()).await?);
+        assert!(!accept_code_step(&client, &other_user_id, current_step() - 1).await?);
+        assert!(accept_code_step(&client, &other_user_id, current_step() + 1).await?);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_failure_on_expired_challenge_leaves_nothing() -> Result<()> {
+        let client = redis_client();
+        let token = create_login_challenge(
+            &client,
+            &LoginChallenge {
+                user_id: format!("user:{}", Uuid::new_v4().simple()),
+                user_role: "customer".to_string(),
+                email: "buyer@email.test".to_string(),
+                setup_required: false,
+            },
+        )
+        .await?;
+        let key = format!("login_challenge:{}", token);
+
+        record_challenge_failure(&client, &token).await?;
+        let mut redis_client = client.get_multiplexed_async_connection().await?;
+        let ttl: i64 = redis_client.ttl(&key).await?;
+        assert!(ttl > 0);
+
+        // The challenge expires between reading it and counting the wrong code
+        let _: () = redis_client.del(&key).await?;
+        record_challenge_failure(&client, &token).await?;
+
+        let exists: bool = redis_client.exists(&key).await?;
+        assert!(!exists);
+        assert!(get_login_challenge(&client, &token).await?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_otpauth_uri() -> Result<()> {
+        let secret = generate_secret();
+        let uri = otpauth_uri(&secret, "buyer@email.test")?;
+
+        assert!(uri.starts_with("otpauth://totp/VirtuMart:buyer%40email.test?"));
+        assert!(uri.contains(&format!("secret={}", secret)));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_recovery_codes() -> Result<()> {
+        let (codes, hashes) = generate_recovery_codes();
+
+        assert_eq!(codes.len(), 10);
+        assert_eq!(hashes.len(), codes.len());
+        assert_eq!(hash_recovery_code(&codes[0]), hashes[0]);
+        assert_eq!(hash_recovery_code(&codes[0].to_uppercase()), hashes[0]);
+        assert_ne!(hashes[0], hashes[1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use chrono::Utc;
+    use ciborium::Value;
+    use errors::Result;
+    use model::{
+        authorization::webauthn::VerifiedCredential,
+        domain::passkey::Passkey,
+        web::passkey::passkey_request::{PasskeyLogin, PasskeyRegistration},
+    };
+    use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+    use serde_json::json;
+    use service::auth::webauthn::Webauthn;
+    use sha2::{Digest, Sha256};
+
+    use tokio::test;
+
+    const RP_ID: &str = "shop.test";
+    const ORIGIN: &str = "https://shop.test";
+    const USER_ID: &str = "user:user_passkey1";
+
+    const FLAG_USER_PRESENT: u8 = 0x01;
+    const FLAG_USER_VERIFIED: u8 = 0x04;
+    const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+    /* A software authenticator holding a single ES256 passkey */
+    struct SoftwareAuthenticator {
+        key: SigningKey,
+        credential_id: Vec<u8>,
+        sign_count: u32,
+        flags: u8,
+    }
+
+    impl SoftwareAuthenticator {
+        fn new(seed: u8) -> Self {
+            SoftwareAuthenticator {
+                key: SigningKey::from_slice(&[seed; 32]).unwrap(),
+                credential_id: vec![seed; 16],
+                sign_count: 0,
+                flags: FLAG_USER_PRESENT | FLAG_USER_VERIFIED,
+            }
+        }
+
+        fn cose_key(&self) -> Vec<u8> {
+            let point = self.key.verifying_key().to_encoded_point(false);
+            let cose_key = Value::Map(vec![
+                (Value::from(1), Value::from(2)),
+                (Value::from(3), Value::from(-7)),
+                (Value::from(-1), Value::from(1)),
+                (Value::from(-2), Value::Bytes(point.x().unwrap().to_vec())),
+                (Value::from(-3), Value::Bytes(point.y().unwrap().to_vec())),
+            ]);
+            let mut bytes = Vec::new();
+            ciborium::into_writer(&cose_key, &mut bytes).unwrap();
+            bytes
+        }
+
+        fn authenticator_data(&self, rp_id: &str, attested: bool) -> Vec<u8> {
+            let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+            let flags = if attested {
+                self.flags | FLAG_ATTESTED_CREDENTIAL
+            } else {
+                self.flags
+            };
+            data.push(flags);
+            data.extend(self.sign_count.to_be_bytes());
+            if attested {
+                data.extend([0; 16]);
+                data.extend((self.credential_id.len() as u16).to_be_bytes());
+                data.extend(&self.credential_id);
+                data.extend(self.cose_key());
+            }
+            data
+        }
+
+        fn client_data(ceremony: &str, challenge: &str, origin: &str) -> Vec<u8> {
+            json!({ "type": ceremony, "challenge": challenge, "origin": origin })
+                .to_string()
+                .into_bytes()
+        }
+
+        /// Answers `navigator.credentials.create()`.
+        fn create(&self, rp_id: &str, origin: &str, challenge: &str) -> PasskeyRegistration {
+            let attestation_object = Value::Map(vec![
+                (Value::from("fmt"), Value::from("none")),
+                (Value::from("attStmt"), Value::Map(Vec::new())),
+                (
+                    Value::from("authData"),
+                    Value::Bytes(self.authenticator_data(rp_id, true)),
+                ),
+            ]);
+            let mut attestation_bytes = Vec::new();
+            ciborium::into_writer(&attestation_object, &mut attestation_bytes).unwrap();
+
+            PasskeyRegistration {
+                name: "laptop".to_string(),
+                client_data_json: URL_SAFE_NO_PAD.encode(Self::client_data(
+                    "webauthn.create",
+                    challenge,
+                    origin,
+                )),
+                attestation_object: URL_SAFE_NO_PAD.encode(attestation_bytes),
+            }
+        }
+
+        /// Answers `navigator.credentials.get()`, bumping the signature counter.
+        fn get(&mut self, rp_id: &str, origin: &str, challenge: &str) -> PasskeyLogin {
+            self.sign_count += 1;
+            let authenticator_data = self.authenticator_data(rp_id, false);
+            let client_data = Self::client_data("webauthn.get", challenge, origin);
+
+            let mut signed = authenticator_data.clone();
+            signed.extend(Sha256::digest(&client_data));
+            let signature: Signature = self.key.sign(&signed);
+
+            PasskeyLogin {
+                credential_id: URL_SAFE_NO_PAD.encode(&self.credential_id),
+                client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                authenticator_data: URL_SAFE_NO_PAD.encode(authenticator_data),
+                signature: URL_SAFE_NO_PAD.encode(signature.to_der().as_bytes()),
+                user_handle: Some(URL_SAFE_NO_PAD.encode(USER_ID)),
+            }
+        }
+    }
+
+    fn webauthn() -> Webauthn {
+        Webauthn::new(RP_ID, "VirtuMart", ORIGIN)
+    }
+
+    fn stored_passkey(credential: VerifiedCredential) -> Passkey {
+        Passkey {
+            id: "passkey:pk_00000000000000000000000000000001".to_string(),
+            user_id: USER_ID.to_string(),
+            name: "laptop".to_string(),
+            credential_id: credential.credential_id,
+            public_key: credential.public_key,
+            sign_count: credential.sign_count,
+            created_at: Utc::now(),
+            last_used_at: None,
+        }
+    }
+
+    fn registered(authenticator: &SoftwareAuthenticator) -> Result<Passkey> {
+        let registration = authenticator.create(RP_ID, ORIGIN, "register-challenge");
+        let credential = webauthn().verify_registration("register-challenge", &registration)?;
+        Ok(stored_passkey(credential))
+    }
+
+    #[test]
+    async fn test_register_then_login() -> Result<()> {
+        let mut authenticator = SoftwareAuthenticator::new(7);
+        let mut passkey = registered(&authenticator)?;
+        assert_eq!(
+            passkey.credential_id,
+            URL_SAFE_NO_PAD.encode(&authenticator.credential_id)
+        );
+        assert_eq!(passkey.sign_count, 0);
+
+        let login = authenticator.get(RP_ID, ORIGIN, "login-challenge");
+        let sign_count = webauthn().verify_authentication("login-challenge", &passkey, &login)?;
+        assert_eq!(sign_count, 1);
+
+        passkey.sign_count = sign_count;
+        let login = authenticator.get(RP_ID, ORIGIN, "second-challenge");
+        assert_eq!(
+            webauthn().verify_authentication("second-challenge", &passkey, &login)?,
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    async fn test_registration_rejects_other_site() -> Result<()> {
+        let authenticator = SoftwareAuthenticator::new(7);
+
+        let registration = authenticator.create(RP_ID, "https://evil.test", "challenge");
+        assert!(webauthn()
+            .verify_registration("challenge", &registration)
+            .is_err());
+
+        let registration = authenticator.create("evil.test", ORIGIN, "challenge");
+        assert!(webauthn()
+            .verify_registration("challenge", &registration)
+            .is_err());
+
+        let registration = authenticator.create(RP_ID, ORIGIN, "challenge");
+        assert!(webauthn()
+            .verify_registration("other-challenge", &registration)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_registration_requires_user_verification() -> Result<()> {
+        let mut authenticator = SoftwareAuthenticator::new(7);
+        authenticator.flags = FLAG_USER_PRESENT;
+
+        let registration = authenticator.create(RP_ID, ORIGIN, "challenge");
+        assert!(webauthn()
+            .verify_registration("challenge", &registration)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_login_rejects_wrong_key_and_challenge() -> Result<()> {
+        let mut authenticator = SoftwareAuthenticator::new(7);
+        let passkey = registered(&authenticator)?;
+
+        let login = authenticator.get(RP_ID, ORIGIN, "challenge");
+        assert!(webauthn()
+            .verify_authentication("other-challenge", &passkey, &login)
+            .is_err());
+
+        // Same credential id, signed by a different key
+        let mut impostor = SoftwareAuthenticator::new(9);
+        impostor.credential_id = authenticator.credential_id.clone();
+        let login = impostor.get(RP_ID, ORIGIN, "challenge");
+        assert!(webauthn()
+            .verify_authentication("challenge", &passkey, &login)
+            .is_err());
+
+        let mut login = authenticator.get(RP_ID, ORIGIN, "challenge");
+        login.user_handle = Some(URL_SAFE_NO_PAD.encode("user:someone_else"));
+        assert!(webauthn()
+            .verify_authentication("challenge", &passkey, &login)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_login_rejects_counter_going_backwards() -> Result<()> {
+        let mut authenticator = SoftwareAuthenticator::new(7);
+        let mut passkey = registered(&authenticator)?;
+        passkey.sign_count = 5;
+
+        let login = authenticator.get(RP_ID, ORIGIN, "challenge");
+        assert!(webauthn()
+            .verify_authentication("challenge", &passkey, &login)
+            .is_err());
+        Ok(())
+    }
+}
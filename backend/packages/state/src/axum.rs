@@ -1,8 +1,24 @@
 use redis::Client;
-use service::user::user_service::UserService;
+use service::{
+    api_key::api_key_service::ApiKeyService,
+    audit::audit_service::AuditService,
+    auth::{keyring::Keyring, oidc::OidcClient, webauthn::Webauthn},
+    mail::mailer::Mailer,
+    passkey::passkey_service::PasskeyService,
+    role::role_service::RoleService,
+    user::user_service::UserService,
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub user_service: UserService,
+    pub role_service: RoleService,
+    pub api_key_service: ApiKeyService,
+    pub audit_service: AuditService,
+    pub passkey_service: PasskeyService,
     pub redis_client: Client,
+    pub mailer: Mailer,
+    pub keyring: Keyring,
+    pub oidc_client: OidcClient,
+    pub webauthn: Webauthn,
 }
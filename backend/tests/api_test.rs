@@ -40,7 +40,7 @@ mod tests {
         json!({
             "email": "abdule@example.com",
             "password": "1234567890",
-            "role": "admin",
+            "role": "customer",
             "username": "asoi909090"
         })
     );